@@ -0,0 +1,10 @@
+CREATE TABLE message_reaction (
+  id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+  message_internal_id INTEGER NOT NULL REFERENCES message (internal_id),
+  emoji               TEXT,
+  custom_sticker_path TEXT,
+  from_id             INTEGER NOT NULL,
+  time_sent           INTEGER -- epoch seconds
+) STRICT;
+
+CREATE INDEX message_reaction_idx ON message_reaction(message_internal_id);
@@ -0,0 +1,5 @@
+-- Message may now have several contents, ordinal 0 being the main one
+ALTER TABLE message_content ADD COLUMN ordinal INTEGER NOT NULL DEFAULT 0;
+
+DROP INDEX message_content_idx;
+CREATE UNIQUE INDEX message_content_idx ON message_content(message_internal_id, ordinal);
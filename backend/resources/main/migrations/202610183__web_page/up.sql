@@ -0,0 +1,4 @@
+-- Link preview, title and image path are stored in title and path
+ALTER TABLE message_content ADD COLUMN url TEXT;
+ALTER TABLE message_content ADD COLUMN description TEXT;
+ALTER TABLE message_content ADD COLUMN site_name TEXT;
@@ -0,0 +1,2 @@
+-- Mentioned user ID, for mention elements only
+ALTER TABLE message_text_element ADD COLUMN user_id INTEGER;
@@ -0,0 +1,2 @@
+-- Custom emoji file path, for custom emoji elements only
+ALTER TABLE message_text_element ADD COLUMN path TEXT;
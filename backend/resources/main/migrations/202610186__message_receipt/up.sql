@@ -0,0 +1,11 @@
+ALTER TABLE message ADD COLUMN delivery_status TEXT;
+
+CREATE TABLE message_receipt (
+  id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+  message_internal_id INTEGER NOT NULL REFERENCES message (internal_id),
+  user_id             INTEGER NOT NULL,
+  time_delivered      INTEGER, -- epoch seconds
+  time_read           INTEGER  -- epoch seconds
+) STRICT;
+
+CREATE INDEX message_receipt_idx ON message_receipt(message_internal_id);
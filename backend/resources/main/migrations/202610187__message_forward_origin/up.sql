@@ -0,0 +1,5 @@
+ALTER TABLE message ADD COLUMN forward_from_id INTEGER;
+ALTER TABLE message ADD COLUMN forward_chat_name TEXT;
+ALTER TABLE message ADD COLUMN forward_time INTEGER; -- epoch seconds
+ALTER TABLE message ADD COLUMN forward_source_id INTEGER;
+ALTER TABLE message ADD COLUMN forward_author_name TEXT;
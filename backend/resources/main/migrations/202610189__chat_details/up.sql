@@ -0,0 +1,6 @@
+ALTER TABLE chat ADD COLUMN description TEXT;
+ALTER TABLE chat ADD COLUMN time_created INTEGER; -- epoch seconds
+
+ALTER TABLE chat_member ADD COLUMN role TEXT;
+ALTER TABLE chat_member ADD COLUMN time_joined INTEGER; -- epoch seconds
+ALTER TABLE chat_member ADD COLUMN time_left INTEGER; -- epoch seconds
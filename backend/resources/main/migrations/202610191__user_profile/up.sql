@@ -0,0 +1,3 @@
+ALTER TABLE user ADD COLUMN emails TEXT; -- serialized
+ALTER TABLE user ADD COLUMN bio TEXT;
+ALTER TABLE user ADD COLUMN img_path TEXT;
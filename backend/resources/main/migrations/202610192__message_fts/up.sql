@@ -0,0 +1,24 @@
+-- Full-text index over message.searchable_string, rows are referenced by message.internal_id.
+-- Content is not duplicated, but has to be kept in sync by the triggers below.
+CREATE VIRTUAL TABLE message_fts USING fts5(
+  searchable_string,
+  content = 'message',
+  content_rowid = 'internal_id',
+  tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER message_fts_ai AFTER INSERT ON message BEGIN
+  INSERT INTO message_fts (rowid, searchable_string) VALUES (new.internal_id, new.searchable_string);
+END;
+
+CREATE TRIGGER message_fts_ad AFTER DELETE ON message BEGIN
+  INSERT INTO message_fts (message_fts, rowid, searchable_string) VALUES ('delete', old.internal_id, old.searchable_string);
+END;
+
+CREATE TRIGGER message_fts_au AFTER UPDATE OF searchable_string ON message BEGIN
+  INSERT INTO message_fts (message_fts, rowid, searchable_string) VALUES ('delete', old.internal_id, old.searchable_string);
+  INSERT INTO message_fts (rowid, searchable_string) VALUES (new.internal_id, new.searchable_string);
+END;
+
+-- Index messages that are already there
+INSERT INTO message_fts (message_fts) VALUES ('rebuild');
@@ -0,0 +1,10 @@
+CREATE INDEX message_time_sent_idx ON message(ds_uuid, chat_id, time_sent);
+CREATE INDEX message_from_id_idx ON message(ds_uuid, chat_id, from_id);
+CREATE INDEX message_content_element_type_idx ON message_content(element_type, message_internal_id);
+
+-- Media items are ordered by (time_sent, ds_uuid, chat_id, internal_id), latest first.
+-- Chat scope is covered by message_time_sent_idx, since internal_id is implicitly the last column of every index.
+CREATE INDEX message_time_sent_global_idx ON message(time_sent, ds_uuid, chat_id, internal_id);
+
+-- Timeline within a dataset is ordered and paged by (time_sent, chat_id, internal_id)
+CREATE INDEX message_timeline_idx ON message(ds_uuid, time_sent, chat_id, internal_id);
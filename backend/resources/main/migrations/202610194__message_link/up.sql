@@ -0,0 +1,11 @@
+-- URLs found in message text, extracted when a message is inserted.
+-- Links of messages inserted before this migration are extracted by the app right after it's applied.
+CREATE TABLE message_link (
+  id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+  message_internal_id INTEGER NOT NULL REFERENCES message (internal_id),
+  url                 TEXT NOT NULL,
+  domain              TEXT NOT NULL
+) STRICT;
+
+CREATE INDEX message_link_domain_idx ON message_link(domain);
+CREATE INDEX message_link_message_idx ON message_link(message_internal_id);
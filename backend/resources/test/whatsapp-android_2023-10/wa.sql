@@ -8,7 +8,7 @@ CREATE TABLE wa_contacts(_id INTEGER PRIMARY KEY AUTOINCREMENT,jid TEXT NOT NULL
 --
 
 -- Myself
-INSERT INTO wa_contacts VALUES(33,'00000@s.whatsapp.net',1,'.',1696406327000,'+00000',74,'Hey look it''s me!',7,NULL,NULL,0,0,0,'Hey look','it''s me!',NULL,'Hey look it''s me!',NULL,NULL,NULL,NULL,NULL,0,0,1,NULL,NULL,NULL,0);
+INSERT INTO wa_contacts VALUES(33,'00000@s.whatsapp.net',1,'Available',1696406327000,'+00000',74,'Hey look it''s me!',7,NULL,NULL,0,0,0,'Hey look','it''s me!',NULL,'Hey look it''s me!',NULL,NULL,NULL,NULL,NULL,0,0,1,NULL,NULL,NULL,0);
 
 -- User 1
 INSERT INTO wa_contacts VALUES(181,'111111@s.whatsapp.net',1,'User 1 status message',1576087611000,NULL,NULL,NULL,NULL,NULL,NULL,0,1574081200,1689079868147,NULL,NULL,NULL,NULL,NULL,NULL,NULL,NULL,NULL,NULL,1,1,NULL,NULL,NULL,NULL);
@@ -1,14 +1,20 @@
 use std::cell::{Ref, RefCell};
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::thread::JoinHandle;
 
+use chrono::{Local, TimeZone};
 use deepsize::DeepSizeOf;
 use itertools::Itertools;
 
 use crate::prelude::*;
 
+use statistics::StatisticsAccumulator;
+
 pub mod in_memory_dao;
+pub mod links;
 pub mod sqlite_dao;
+pub mod statistics;
 
 pub trait WithCache {
     /// For internal use
@@ -44,6 +50,9 @@ pub trait WithCache {
 /**
  * Everything except for messages should be pre-cached and readily available.
  * Should support equality.
+ *
+ * Default implementations of aggregating methods (links, link domains, statistics) go through every message
+ * within the scope, so it's better to override them when storage allows for something smarter.
  */
 pub trait ChatHistoryDao: WithCache + Send {
     /** User-friendly name of a loaded data */
@@ -164,6 +173,112 @@ pub trait ChatHistoryDao: WithCache + Send {
 
     fn message_option(&self, chat: &Chat, source_id: MessageSourceId) -> Result<Option<Message>>;
 
+    /// Return N chat messages matching the filter after skipping first M of them.
+    fn filter_messages(&self, chat: &Chat, filter: &MessageFilter, offset: usize, limit: usize) -> Result<Vec<Message>>;
+
+    /// Return N media items of the given kinds (all media kinds if empty) within the scope after skipping first M of them,
+    /// latest first. Messages with equal timestamps are ordered by dataset UUID, chat ID and then their order within
+    /// a chat, all descending. Items of a single message keep their order.
+    fn media_items(&self, scope: SearchScope, kinds: &[ContentKind], offset: usize, limit: usize) -> Result<Vec<MediaItem>> {
+        for kind in kinds {
+            ensure!(MEDIA_CONTENT_KINDS.contains(kind), "{kind:?} is not a media kind!");
+        }
+        let kinds = if kinds.is_empty() { MEDIA_CONTENT_KINDS.as_slice() } else { kinds };
+        self.media_items_impl(scope, kinds, offset, limit)
+    }
+
+    fn media_items_impl(&self, scope: SearchScope, kinds: &[ContentKind], offset: usize, limit: usize) -> Result<Vec<MediaItem>>;
+
+    /// Return N URLs shared within the scope after skipping first M of them, latest first.
+    /// URL has to contain the query (ASCII case-insensitive) and belong to the given domain, if any.
+    /// Generic implementation collects all links matching the query first, and only then filters them by domain.
+    fn links(&self, scope: SearchScope, query: &str, domain_option: Option<&str>, offset: usize, limit: usize) -> Result<Vec<LinkItem>> {
+        Ok(self.links_impl(scope, query)?.into_iter()
+            .filter(|l| domain_option.is_none_or(|d| l.domain == d))
+            .skip(offset)
+            .take(limit)
+            .collect_vec())
+    }
+
+    /// Return domains of URLs shared within the scope along with number of links to them, most linked first.
+    fn link_domains(&self, scope: SearchScope, query: &str) -> Result<Vec<DomainCount>> {
+        Ok(self.links_impl(scope, query)?.into_iter()
+            .counts_by(|l| l.domain)
+            .into_iter()
+            .sorted_by(|(d1, c1), (d2, c2)| c2.cmp(c1).then_with(|| d1.cmp(d2)))
+            .map(|(domain, count)| DomainCount { domain, count: count as i64 })
+            .collect_vec())
+    }
+
+    /// All URLs shared within the scope containing the query (ASCII case-insensitive), latest first.
+    /// Messages with equal timestamps are ordered like in `media_items`.
+    /// Within a message, URLs go in the order of text elements.
+    fn links_impl(&self, scope: SearchScope, query: &str) -> Result<Vec<LinkItem>>;
+
+    /// Search messages within the given scope by their searchable strings, best matches first.
+    /// Every whitespace-separated query term has to be present in a message, terms are matched as word prefixes.
+    /// Matched terms in snippets are wrapped in `SEARCH_HIGHLIGHT_START`/`SEARCH_HIGHLIGHT_END`.
+    fn search_messages(&self, scope: SearchScope, query: &str, offset: usize, limit: usize) -> Result<Vec<MessageSearchHit>>;
+
+    /// Return N messages within the given scope and time range (from inclusive, to exclusive) positioned after
+    /// the given one (taken from the last entry of a previous page), ordered by timestamp.
+    /// Messages with equal timestamps are ordered by dataset UUID, chat ID and then their order within a chat.
+    fn timeline(&self,
+                scope: SearchScope,
+                timestamp_from_option: Option<i64>,
+                timestamp_to_option: Option<i64>,
+                after_option: Option<&TimelinePosition>,
+                limit: usize) -> Result<Vec<TimelineEntry>>;
+
+    /// Compute statistics for a chat or a whole dataset.
+    fn statistics(&self, scope: SearchScope, top_words_limit: usize) -> Result<Statistics> {
+        const BATCH_SIZE: usize = 5_000;
+        let chats = match scope {
+            SearchScope::Everywhere => bail!("Statistics can only be computed for a chat or a dataset!"),
+            SearchScope::Dataset(ds_uuid) => self.chats(ds_uuid)?.into_iter().map(|cwd| cwd.chat).collect_vec(),
+            SearchScope::Chat(chat) => vec![chat.clone()],
+        };
+        let mut acc = StatisticsAccumulator::default();
+        for chat in chats.iter() {
+            acc.start_chat();
+            let mut offset = 0;
+            loop {
+                let msgs = self.scroll_messages(chat, offset, BATCH_SIZE)?;
+                msgs.iter().for_each(|m| acc.add(m));
+                if msgs.len() < BATCH_SIZE { break; }
+                offset += msgs.len();
+            }
+        }
+        Ok(acc.finish(top_words_limit))
+    }
+
+    /// Count chat messages sent within the given time range (from inclusive, to exclusive), grouped by local date
+    /// of the given granularity. Dates without messages are omitted.
+    fn activity_counts(&self,
+                       chat: &Chat,
+                       timestamp_from_option: Option<i64>,
+                       timestamp_to_option: Option<i64>,
+                       granularity: ActivityGranularity) -> Result<Vec<DateCount>> {
+        const BATCH_SIZE: usize = 5_000;
+        let mut counts: BTreeMap<String, i64> = BTreeMap::new();
+        let mut offset = 0;
+        loop {
+            let msgs = self.scroll_messages(chat, offset, BATCH_SIZE)?;
+            for msg in msgs.iter() {
+                if timestamp_from_option.is_some_and(|ts| msg.timestamp < ts) ||
+                    timestamp_to_option.is_some_and(|ts| msg.timestamp >= ts) {
+                    continue;
+                }
+                if let Some(dt) = Local.timestamp_opt(msg.timestamp, 0).single() {
+                    *counts.entry(dt.format(granularity.date_format()).to_string()).or_default() += 1;
+                }
+            }
+            if msgs.len() < BATCH_SIZE { break; }
+            offset += msgs.len();
+        }
+        Ok(counts.into_iter().map(|(date, count)| DateCount { date, count }).collect_vec())
+    }
+
     /** Whether given data path is the one loaded in this DAO */
     fn is_loaded(&self, storage_path: &Path) -> bool {
         self.storage_path() == storage_path
@@ -187,7 +302,8 @@ pub trait MutableChatHistoryDao: ChatHistoryDao {
     /// Delete a dataset with all the related entities. Deleted dataset root will be moved to backup folder.
     fn delete_dataset(&mut self, uuid: PbUuid) -> EmptyRes;
 
-    fn insert_user(&mut self, user: User, is_myself: bool) -> Result<User>;
+    /// Copies avatar (if any) from dataset root.
+    fn insert_user(&mut self, user: User, is_myself: bool, src_ds_root: &DatasetRoot) -> Result<User>;
 
     /// Update a user, renaming relevant personal chats and updating messages mentioning that user in plaintext.
     fn update_user(&mut self, old_id: UserId, user: User) -> Result<User>;
@@ -215,6 +331,89 @@ pub trait ShiftableChatHistoryDao: ChatHistoryDao {
     fn shift_dataset_time(&mut self, uuid: &PbUuid, hours_shift: i32) -> EmptyRes;
 }
 
+pub const MEDIA_CONTENT_KINDS: [ContentKind; 7] = [
+    ContentKind::Sticker,
+    ContentKind::Photo,
+    ContentKind::VoiceMsg,
+    ContentKind::Audio,
+    ContentKind::VideoMsg,
+    ContentKind::Video,
+    ContentKind::File,
+];
+
+// Control characters that don't occur in a message text, used to mark matched terms in search snippets.
+pub const SEARCH_HIGHLIGHT_START: &str = "\u{2}";
+pub const SEARCH_HIGHLIGHT_END: &str = "\u{3}";
+
+#[derive(Debug, Clone, Copy)]
+pub enum SearchScope<'a> {
+    Everywhere,
+    Dataset(&'a PbUuid),
+    Chat(&'a Chat),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageSearchHit {
+    pub ds_uuid: PbUuid,
+    pub chat_id: ChatId,
+    pub message: Message,
+    /// Part of the searchable string around the match, with matched terms highlighted.
+    pub snippet: String,
+}
+
+impl MessageFilter {
+    pub fn matches(&self, msg: &Message) -> bool {
+        let (content_kinds, service_kind_option, has_reply, is_deleted, is_edited) = match msg.typed() {
+            message_regular_pat! { content_option, extra_contents, reply_to_message_id_option, is_deleted, edit_timestamp_option, .. } => {
+                let content_kinds = content_option.iter().chain(extra_contents.iter())
+                    .filter_map(|c| c.sealed_value_optional.as_ref())
+                    .map(|c| ContentKind::from(c) as i32)
+                    .collect_vec();
+                (content_kinds, None, reply_to_message_id_option.is_some(), *is_deleted, edit_timestamp_option.is_some())
+            }
+            message_service_pat!(ms) =>
+                (vec![], Some(ServiceKind::from(ms) as i32), false, false, false),
+            message_service_pat_unreachable!() => unreachable!()
+        };
+
+        let kind_matches = match (self.content_kinds.is_empty(), self.service_kinds.is_empty()) {
+            (true, true) => true,
+            _ => content_kinds.iter().any(|k| self.content_kinds.contains(k)) ||
+                service_kind_option.is_some_and(|k| self.service_kinds.contains(&k)),
+        };
+
+        (self.from_ids.is_empty() || self.from_ids.contains(&msg.from_id)) &&
+            self.timestamp_from_option.is_none_or(|ts| msg.timestamp >= ts) &&
+            self.timestamp_to_option.is_none_or(|ts| msg.timestamp < ts) &&
+            kind_matches &&
+            self.has_reply_option.is_none_or(|v| v == has_reply) &&
+            self.is_deleted_option.is_none_or(|v| v == is_deleted) &&
+            self.is_edited_option.is_none_or(|v| v == is_edited)
+    }
+}
+
+impl TimelineEntry {
+    /// Position to pass to a timeline request to get the entries following this one.
+    pub fn position(&self) -> TimelinePosition {
+        TimelinePosition {
+            timestamp: self.message.timestamp,
+            ds_uuid: self.ds_uuid.clone(),
+            chat_id: self.chat_id,
+            message_internal_id: self.message.internal_id,
+        }
+    }
+}
+
+impl ActivityGranularity {
+    /// Date format string, understood by both chrono and SQLite `strftime`.
+    pub fn date_format(&self) -> &'static str {
+        match self {
+            ActivityGranularity::Day => "%Y-%m-%d",
+            ActivityGranularity::Month => "%Y-%m",
+        }
+    }
+}
+
 type UserCache = HashMap<PbUuid, UserCacheForDataset>;
 
 #[derive(DeepSizeOf)]
@@ -3,6 +3,8 @@ use std::path::{Path, PathBuf};
 
 use deepsize::DeepSizeOf;
 use itertools::Itertools;
+use regex::{Captures, Regex};
+use tempfile::TempDir;
 
 use super::*;
 
@@ -14,13 +16,25 @@ macro_rules! subtract_or_zero {
     ($idx:expr, $limit:expr) => { if $idx > $limit { $idx - $limit } else { 0 } };
 }
 
-#[derive(DeepSizeOf)]
 pub struct InMemoryDao {
     pub name: String,
     pub storage_path: PathBuf,
     pub ds_roots: HashMap<PbUuid, DatasetRoot>,
     pub cwms: HashMap<PbUuid, Vec<ChatWithMessages>>,
     cache: DaoCache,
+    /// Directory dataset files were extracted to, if any. Removed once DAO is dropped.
+    temp_dir_option: Option<TempDir>,
+}
+
+impl DeepSizeOf for InMemoryDao {
+    fn deep_size_of_children(&self, context: &mut deepsize::Context) -> usize {
+        // Temporary directory is not held in memory
+        self.name.deep_size_of_children(context) +
+            self.storage_path.deep_size_of_children(context) +
+            self.ds_roots.deep_size_of_children(context) +
+            self.cwms.deep_size_of_children(context) +
+            self.cache.deep_size_of_children(context)
+    }
 }
 
 impl InMemoryDao {
@@ -58,7 +72,12 @@ impl InMemoryDao {
 
         drop(cache_inner);
 
-        InMemoryDao { name, storage_path, ds_roots, cwms: cwms_map, cache }
+        InMemoryDao { name, storage_path, ds_roots, cwms: cwms_map, cache, temp_dir_option: None }
+    }
+
+    /// Make this DAO own a temporary directory its files reside in, so that it is removed along with the DAO.
+    pub fn own_temp_dir(&mut self, temp_dir: TempDir) {
+        self.temp_dir_option = Some(temp_dir);
     }
 
     fn chat_members(&self, chat: &Chat) -> Result<Vec<User>> {
@@ -79,6 +98,18 @@ impl InMemoryDao {
             .find(|cwm| cwm.chat.id == id)
     }
 
+    /// Chats within the given scope, in the order of datasets.
+    pub fn cwms_in_scope<'a>(&'a self, scope: SearchScope<'a>) -> Result<impl Iterator<Item = &'a ChatWithMessages> + 'a> {
+        Ok(self.datasets()?.into_iter()
+            .filter(move |ds| match scope {
+                SearchScope::Everywhere => true,
+                SearchScope::Dataset(ds_uuid) => *ds_uuid == ds.uuid,
+                SearchScope::Chat(chat) => chat.ds_uuid == ds.uuid,
+            })
+            .flat_map(move |ds| self.cwms[&ds.uuid].iter())
+            .filter(move |cwm| !matches!(scope, SearchScope::Chat(chat) if chat.id != cwm.chat.id)))
+    }
+
     fn messages_option(&self, ds_uuid: &PbUuid, chat_id: i64) -> Option<&Vec<Message>> {
         self.cwm_option(ds_uuid, chat_id).map(|cwm| &cwm.messages)
     }
@@ -244,6 +275,144 @@ impl ChatHistoryDao for InMemoryDao {
             .iter().find(|m| m.source_id_option.iter().contains(&*source_id)).cloned())
     }
 
+    fn filter_messages(&self, chat: &Chat, filter: &MessageFilter, offset: usize, limit: usize) -> Result<Vec<Message>> {
+        Ok(self.messages_option(&chat.ds_uuid, chat.id)
+            .map(|msgs| msgs.iter()
+                .filter(|m| filter.matches(m))
+                .skip(offset)
+                .take(limit)
+                .cloned()
+                .collect_vec())
+            .unwrap_or_default())
+    }
+
+    fn media_items_impl(&self, scope: SearchScope, kinds: &[ContentKind], offset: usize, limit: usize) -> Result<Vec<MediaItem>> {
+        use content::SealedValueOptional::*;
+        let mut result = vec![];
+        for cwm in self.cwms_in_scope(scope)? {
+            let ds_uuid = &cwm.chat.ds_uuid;
+            let ds_root = self.dataset_root(ds_uuid)?;
+            for m in cwm.messages.iter() {
+                let message::Typed::Regular(mr) = m.typed() else { continue };
+                for c in mr.contents().filter_map(|c| c.sealed_value_optional.as_ref()) {
+                    let kind = ContentKind::from(c);
+                    if !kinds.contains(&kind) { continue; }
+                    let (path_option, thumbnail_path_option) = match c {
+                        Sticker(v) => (&v.path_option, &v.thumbnail_path_option),
+                        Photo(v) => (&v.path_option, &None),
+                        VoiceMsg(v) => (&v.path_option, &None),
+                        Audio(v) => (&v.path_option, &None),
+                        VideoMsg(v) => (&v.path_option, &v.thumbnail_path_option),
+                        Video(v) => (&v.path_option, &v.thumbnail_path_option),
+                        File(v) => (&v.path_option, &v.thumbnail_path_option),
+                        _ => unreachable!("{kind:?} is not a media kind!"),
+                    };
+                    result.push(MediaItem {
+                        ds_uuid: ds_uuid.clone(),
+                        chat_id: cwm.chat.id,
+                        message_internal_id: m.internal_id,
+                        timestamp: m.timestamp,
+                        from_id: m.from_id,
+                        kind: kind as i32,
+                        path_option: path_option.clone(),
+                        thumbnail_path_option: thumbnail_path_option.clone(),
+                        file_exists: path_option.as_ref().is_some_and(|p| ds_root.to_absolute(p).exists()),
+                    });
+                }
+            }
+        }
+        // Latest messages go first, contents of a single message keep their order
+        let result = result.into_iter()
+            .enumerate()
+            .sorted_by_key(|(idx, item)| (cmp::Reverse((item.timestamp, item.ds_uuid.value.clone(),
+                                                        item.chat_id, item.message_internal_id)), *idx))
+            .map(|(_, item)| item)
+            .collect_vec();
+        Ok(cutout(&result, offset, offset.saturating_add(limit)))
+    }
+
+    fn links_impl(&self, scope: SearchScope, query: &str) -> Result<Vec<LinkItem>> {
+        let mut result = vec![];
+        for cwm in self.cwms_in_scope(scope)? {
+            for m in cwm.messages.iter() {
+                let items = m.text.iter()
+                    .flat_map(links::element_links)
+                    .filter(|url| links::link_matches(url, query))
+                    .map(|url| LinkItem {
+                        ds_uuid: cwm.chat.ds_uuid.clone(),
+                        chat_id: cwm.chat.id,
+                        message_internal_id: m.internal_id,
+                        timestamp: m.timestamp,
+                        from_id: m.from_id,
+                        domain: links::link_domain(&url),
+                        url,
+                    });
+                result.extend(items);
+            }
+        }
+        // Latest messages go first, links of a single message keep their order
+        Ok(result.into_iter()
+            .enumerate()
+            .sorted_by_key(|(idx, item)| (cmp::Reverse((item.timestamp, item.ds_uuid.value.clone(),
+                                                        item.chat_id, item.message_internal_id)), *idx))
+            .map(|(_, item)| item)
+            .collect_vec())
+    }
+
+    fn search_messages(&self, scope: SearchScope, query: &str, offset: usize, limit: usize) -> Result<Vec<MessageSearchHit>> {
+        // Naive scan mimicking full-text search, hits aren't ranked and go in the order of chats
+        let terms = query.split_whitespace().map(regex::escape).collect_vec();
+        if terms.is_empty() {
+            return Ok(vec![]);
+        }
+        let term_regexes: Vec<Regex> = terms.iter()
+            .map(|term| Regex::new(&format!(r"(?i)\b{term}")))
+            .try_collect()?;
+        let any_term_regex = Regex::new(&format!(r"(?i)\b(?:{})", terms.iter().join("|")))?;
+
+        let mut result = vec![];
+        for cwm in self.cwms_in_scope(scope)? {
+            let hits = cwm.messages.iter()
+                .filter(|m| term_regexes.iter().all(|r| r.is_match(&m.searchable_string)))
+                .map(|m| MessageSearchHit {
+                    ds_uuid: cwm.chat.ds_uuid.clone(),
+                    chat_id: ChatId(cwm.chat.id),
+                    message: m.clone(),
+                    snippet: any_term_regex.replace_all(&m.searchable_string, |c: &Captures| {
+                        format!("{SEARCH_HIGHLIGHT_START}{}{SEARCH_HIGHLIGHT_END}", &c[0])
+                    }).into_owned(),
+                });
+            result.extend(hits);
+        }
+        Ok(cutout(&result, offset, offset.saturating_add(limit)))
+    }
+
+    fn timeline(&self,
+                scope: SearchScope,
+                timestamp_from_option: Option<i64>,
+                timestamp_to_option: Option<i64>,
+                after_option: Option<&TimelinePosition>,
+                limit: usize) -> Result<Vec<TimelineEntry>> {
+        let after_key_option = after_option
+            .map(|p| (p.timestamp, p.ds_uuid.value.clone(), p.chat_id, p.message_internal_id));
+        let mut result = vec![];
+        for cwm in self.cwms_in_scope(scope)? {
+            let ds_uuid = &cwm.chat.ds_uuid;
+            let entries = cwm.messages.iter()
+                .filter(|m| timestamp_from_option.is_none_or(|ts| m.timestamp >= ts) &&
+                    timestamp_to_option.is_none_or(|ts| m.timestamp < ts))
+                .map(|m| ((m.timestamp, ds_uuid.value.clone(), cwm.chat.id, m.internal_id), TimelineEntry {
+                    ds_uuid: ds_uuid.clone(),
+                    chat_id: cwm.chat.id,
+                    message: m.clone(),
+                }))
+                .filter(|(k, _)| after_key_option.as_ref().is_none_or(|after_key| k > after_key));
+            result.extend(entries);
+        }
+        result.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        Ok(result.into_iter().map(|(_, e)| e).take(limit).collect_vec())
+    }
+
     fn as_mutable(&mut self) -> Result<&mut dyn MutableChatHistoryDao> {
         Ok(self)
     }
@@ -287,7 +456,7 @@ impl MutableChatHistoryDao for InMemoryDao {
         }
     }
 
-    fn insert_user(&mut self, _user: User, _is_myself: bool) -> Result<User> {
+    fn insert_user(&mut self, _user: User, _is_myself: bool, _src_ds_root: &DatasetRoot) -> Result<User> {
         err!("InMemoryDao does not implement inserting users")
     }
 
@@ -332,13 +501,27 @@ impl ShiftableChatHistoryDao for InMemoryDao {
         let timestamp_shift: i64 = (hours_shift * 60 * 60).into();
         let cwms = self.cwms.get_mut(uuid).unwrap();
         for cwm in cwms.iter_mut() {
+            cwm.chat.created_timestamp_option.iter_mut().for_each(|ts| *ts += timestamp_shift);
+            cwm.chat.member_details.iter_mut().for_each(|md| {
+                md.joined_timestamp_option.iter_mut().for_each(|ts| *ts += timestamp_shift);
+                md.left_timestamp_option.iter_mut().for_each(|ts| *ts += timestamp_shift);
+            });
             for m in cwm.messages.iter_mut() {
                 m.timestamp += timestamp_shift;
                 match m.typed_mut() {
-                    message::Typed::Regular(mr) =>
-                        mr.edit_timestamp_option.iter_mut().for_each(|ts| *ts += timestamp_shift),
+                    message::Typed::Regular(mr) => {
+                        mr.edit_timestamp_option.iter_mut().for_each(|ts| *ts += timestamp_shift);
+                        mr.forward_origin_option.iter_mut()
+                            .flat_map(|fo| fo.timestamp_option.iter_mut())
+                            .for_each(|ts| *ts += timestamp_shift);
+                    }
                     message::Typed::Service(_) => { /* NOOP */ }
                 }
+                m.reactions.iter_mut().for_each(|r| r.timestamp_option.iter_mut().for_each(|ts| *ts += timestamp_shift));
+                m.receipts.iter_mut().for_each(|r| {
+                    r.delivered_timestamp_option.iter_mut().for_each(|ts| *ts += timestamp_shift);
+                    r.read_timestamp_option.iter_mut().for_each(|ts| *ts += timestamp_shift);
+                });
             }
         }
         Ok(())
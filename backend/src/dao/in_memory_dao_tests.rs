@@ -172,6 +172,35 @@ fn messages_around() -> EmptyRes {
     Ok(())
 }
 
+#[test]
+fn search_messages() -> EmptyRes {
+    let dao_holder = create_specific_dao();
+    let dao = dao_holder.dao;
+    let ds_uuid = dao.datasets()?.remove(0).uuid;
+    let chat = dao.chats(&ds_uuid)?.remove(0).chat;
+    let msgs = &dao.cwms[&ds_uuid][0].messages;
+
+    let hits = dao.search_messages(SearchScope::Chat(&chat), "THERE 3", 0, 10)?;
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].ds_uuid, ds_uuid);
+    assert_eq!(hits[0].chat_id, ChatId(chat.id));
+    assert_eq!(hits[0].message, msgs[3]);
+    let highlight = |s: &str| format!("{SEARCH_HIGHLIGHT_START}{s}{SEARCH_HIGHLIGHT_END}");
+    assert_eq!(hits[0].snippet, msgs[3].searchable_string.replace("there", &highlight("there")).replace('3', &highlight("3")));
+
+    let msg_hits = |scope: SearchScope, offset: usize, limit: usize| -> Result<Vec<Message>> {
+        Ok(dao.search_messages(scope, "hello", offset, limit)?.into_iter().map(|h| h.message).collect_vec())
+    };
+    assert_eq!(msg_hits(SearchScope::Everywhere, 0, 1000)?, msgs.smart_slice(..));
+    assert_eq!(msg_hits(SearchScope::Dataset(&ds_uuid), 2, 3)?, msgs.smart_slice(2..5));
+    assert_eq!(msg_hits(SearchScope::Dataset(&PbUuid { value: "other".to_owned() }), 0, 1000)?, vec![]);
+
+    assert_eq!(dao.search_messages(SearchScope::Everywhere, "ello", 0, 1000)?, vec![]);
+    assert_eq!(dao.search_messages(SearchScope::Everywhere, "", 0, 1000)?, vec![]);
+
+    Ok(())
+}
+
 //
 // Helpers
 //
@@ -185,6 +214,10 @@ pub fn create_specific_dao() -> InMemoryDaoHolder {
             last_name_option: None,
             username_option: None,
             phone_number_option: None,
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: None,
+            img_path_option: None,
         },
         User {
             ds_uuid: ZERO_PB_UUID.clone(),
@@ -193,6 +226,10 @@ pub fn create_specific_dao() -> InMemoryDaoHolder {
             last_name_option: Some("Aaaaaaaaaaa".to_owned()),
             username_option: Some("myself".to_owned()),
             phone_number_option: Some("+998 91 1234567".to_owned()),
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: None,
+            img_path_option: None,
         },
     ];
 
@@ -210,6 +247,9 @@ pub fn create_specific_dao() -> InMemoryDaoHolder {
                 member_ids: users.iter().map(|u| u.id).collect_vec(),
                 msg_count: messages.len() as i32,
                 main_chat_id: None,
+                description_option: None,
+                created_timestamp_option: None,
+                member_details: vec![],
             },
             messages,
         }
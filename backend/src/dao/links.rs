@@ -0,0 +1,59 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::*;
+
+lazy_static! {
+    static ref URL_REGEX: Regex =
+        Regex::new(r#"(?i)\b(?:(?:https?|ftp)://|www\.)[^\s<>"'`{}|\\^\[\]]+"#).unwrap();
+}
+
+/// URLs referenced by a rich text element: link target for links, URLs detected in text for plain text.
+pub fn element_links(rte: &RichTextElement) -> Vec<String> {
+    match rte.val.as_ref() {
+        Some(rich_text_element::Val::Link(link)) => link_href(&link.href).into_iter().collect_vec(),
+        Some(rich_text_element::Val::Plain(plain)) => plain_text_links(&plain.text),
+        _ => vec![],
+    }
+}
+
+pub fn link_href(href: &str) -> Option<String> {
+    let href = href.trim();
+    (!href.is_empty()).then(|| href.to_owned())
+}
+
+pub fn plain_text_links(text: &str) -> Vec<String> {
+    URL_REGEX.find_iter(text).map(|m| trim_url(m.as_str()).to_owned()).collect_vec()
+}
+
+/// Host name of the URL without the `www.` prefix, with ASCII case lowered the same way [link_matches] does.
+pub fn link_domain(url: &str) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = without_scheme.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = host.split(':').next().unwrap_or_default().to_ascii_lowercase();
+    match host.strip_prefix("www.") {
+        Some(stripped) => stripped.to_owned(),
+        None => host,
+    }
+}
+
+/// Only ASCII case is ignored, same as SQLite `lower()` does.
+pub fn link_matches(url: &str, query: &str) -> bool {
+    query.is_empty() || url.to_ascii_lowercase().contains(&query.to_ascii_lowercase())
+}
+
+/// Strips punctuation that most likely belongs to the surrounding text rather than to the URL.
+fn trim_url(url: &str) -> &str {
+    let mut url = url;
+    loop {
+        let trim = match url.chars().last() {
+            Some('.' | ',' | ';' | ':' | '!' | '?') => true,
+            Some(')') => url.matches('(').count() < url.matches(')').count(),
+            _ => false,
+        };
+        if !trim { break; }
+        url = &url[..url.len() - 1];
+    }
+    url
+}
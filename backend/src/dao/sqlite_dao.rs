@@ -8,6 +8,7 @@ use chrono::Local;
 use diesel::{delete, insert_into, sql_query, sql_types, update};
 use diesel::migration::MigrationSource;
 use diesel::prelude::*;
+use diesel::query_builder::{BoxedSqlQuery, SqlQuery};
 use diesel::sqlite::Sqlite;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use itertools::{Either, Itertools};
@@ -24,6 +25,16 @@ mod utils;
 #[path = "sqlite_dao_tests.rs"]
 mod tests;
 
+/// Join clause for the main message content, extra contents (if any) are fetched separately.
+macro_rules! main_content {
+    () => {
+        schema::message_content::table.on(
+            schema::message_content::columns::message_internal_id.eq(schema::message::columns::internal_id.nullable())
+                .and(schema::message_content::columns::ordinal.eq(0))
+        )
+    };
+}
+
 pub struct SqliteDao {
     pub name: String,
     pub db_file: PathBuf,
@@ -36,6 +47,9 @@ impl SqliteDao {
 
     const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./resources/main/migrations");
 
+    /// Migration that introduced message links table, links for existing messages are to be extracted after it.
+    const MESSAGE_LINK_MIGRATION_VERSION: &'static str = "202610194";
+
     pub fn create(db_file: &Path) -> Result<Self> {
         ensure!(!db_file.exists(), "File {} already exists!", db_file.display());
         Self::create_load_inner(db_file)
@@ -47,6 +61,46 @@ impl SqliteDao {
         Self::create_load_inner(db_file)
     }
 
+    pub fn load_or_create(db_file: &Path) -> Result<Self> {
+        if db_file.exists() { Self::load(db_file) } else { Self::create(db_file) }
+    }
+
+    /// Fills message links table for messages that were inserted before links were extracted upon insertion.
+    fn extract_existing_links(conn: &mut SqliteConnection) -> EmptyRes {
+        use schema::*;
+        // Plain text is pre-filtered to only contain elements that might have URLs, the rest is done in code
+        let candidates: Vec<RawLinkCandidate> = sql_query(r"
+            SELECT mte.message_internal_id AS internal_id, mte.element_type, mte.text, mte.href
+            FROM message_text_element mte
+            WHERE mte.element_type = 'link'
+               OR (mte.element_type = 'plain' AND (mte.text LIKE '%://%' OR mte.text LIKE '%www.%'))
+            ORDER BY mte.id ASC").load(conn)?;
+
+        let raw_links = candidates.into_iter().flat_map(|raw| {
+            let urls = match (raw.element_type.as_str(), &raw.href, &raw.text) {
+                ("link", Some(href), _) => links::link_href(href).into_iter().collect_vec(),
+                ("plain", _, Some(text)) => links::plain_text_links(text),
+                _ => vec![],
+            };
+            urls.into_iter().map(move |url| RawLink {
+                id: None,
+                message_internal_id: Some(raw.internal_id),
+                domain: links::link_domain(&url),
+                url,
+            })
+        }).collect_vec();
+
+        const BATCH_SIZE: usize = 5_000;
+        conn.transaction(|conn| {
+            for batch in raw_links.chunks(BATCH_SIZE) {
+                insert_into(message_link::table).values(batch).execute(conn)?;
+            }
+            ok(())
+        })?;
+        log::info!("Extracted {} links from existing messages", raw_links.len());
+        Ok(())
+    }
+
     fn check_db_file_path(db_file: &Path) -> EmptyRes {
         ensure!(db_file.parent().is_some_and(|p| p.exists()),
                 "Parent directory for {} does not exist!", db_file.display());
@@ -70,7 +124,15 @@ impl SqliteDao {
             let migrations = conn.pending_migrations(SqliteDao::MIGRATIONS).normalize_error()?;
             for m in migrations.iter() {
                 log::info!("Applying migration: {}", m.name());
-                conn.run_migration(m).normalize_error()?;
+                if m.name().version().to_string() == Self::MESSAGE_LINK_MIGRATION_VERSION {
+                    // Extracting links along with the migration, so that an interrupted extraction is redone next time
+                    conn.transaction(|conn| {
+                        conn.run_migration(m).normalize_error()?;
+                        Self::extract_existing_links(conn)
+                    })?;
+                } else {
+                    conn.run_migration(m).normalize_error()?;
+                }
             }
         }
 
@@ -125,20 +187,26 @@ impl SqliteDao {
 
                     let raw_ds = utils::dataset::serialize(src_ds);
 
+                    let src_ds_root = src.dataset_root(ds_uuid)?;
+                    let dst_ds_root = self.dataset_root(ds_uuid)?;
+
                     self.conn.borrow_mut().transaction(|txn| {
                         insert_into(dataset::table).values(&raw_ds).execute(txn)?;
 
                         let raw_users: Vec<RawUser> = src.users(ds_uuid)?.iter().map(|u| {
                             ensure!(u.id > 0, "IDs should be positive!");
-                            Ok(utils::user::serialize(u, *u == src_myself, &raw_ds.uuid))
+                            let mut raw_user = utils::user::serialize(u, *u == src_myself, &raw_ds.uuid);
+                            if let Some(ref img) = u.img_path_option {
+                                raw_user.img_path =
+                                    copy_file_into(img, &None, &subpaths::AVATARS,
+                                                   USERS_ROOT_REL_PATH, &src_ds_root, &dst_ds_root)?;
+                            }
+                            Ok(raw_user)
                         }).try_collect()?;
                         insert_into(user::table).values(&raw_users).execute(txn)?;
                         ok(())
                     })?;
 
-                    let src_ds_root = src.dataset_root(ds_uuid)?;
-                    let dst_ds_root = self.dataset_root(ds_uuid)?;
-
                     for src_cwd in src.chats(ds_uuid)?.iter() {
                         ensure!(src_cwd.chat.id > 0, "IDs should be positive!");
                         ensure!(src_cwd.chat.member_ids.first() == Some(&src_myself.id),
@@ -153,16 +221,7 @@ impl SqliteDao {
                             }
                             insert_into(chat::table).values(raw_chat).execute(txn)?;
                             insert_into(chat_member::table)
-                                .values(src_cwd.chat.member_ids.iter()
-                                    .enumerate()
-                                    .map(|(order, &user_id)|
-                                        RawChatMember {
-                                            ds_uuid: raw_ds.uuid.clone(),
-                                            chat_id: src_cwd.chat.id,
-                                            user_id,
-                                            order: order as i32,
-                                        })
-                                    .collect_vec())
+                                .values(utils::chat::serialize_members(&src_cwd.chat, &raw_ds.uuid)?)
                                 .execute(txn)?;
                             ok(())
                         })?;
@@ -206,6 +265,39 @@ impl SqliteDao {
         utils::message::fetch(self.conn.borrow_mut().deref_mut(), get_raw_messages_with_content)
     }
 
+    /// Narrows down raw query over `message m` to the given scope.
+    fn with_scope_condition<'a>(raw_query: BoxedSqlQuery<'a, Sqlite, SqlQuery>,
+                                scope: SearchScope) -> Result<BoxedSqlQuery<'a, Sqlite, SqlQuery>> {
+        Ok(match scope {
+            SearchScope::Everywhere =>
+                raw_query,
+            SearchScope::Dataset(ds_uuid) =>
+                raw_query.sql(" AND m.ds_uuid = ?")
+                    .bind::<sql_types::Binary, _>(Uuid::parse_str(&ds_uuid.value)?.as_bytes().to_vec()),
+            SearchScope::Chat(chat) =>
+                raw_query.sql(" AND m.ds_uuid = ? AND m.chat_id = ?")
+                    .bind::<sql_types::Binary, _>(Uuid::parse_str(&chat.ds_uuid.value)?.as_bytes().to_vec())
+                    .bind::<sql_types::BigInt, _>(chat.id),
+        })
+    }
+
+    /// Narrows down raw query over `message_link ml` joined with `message m` to the given scope,
+    /// URLs containing the query (case-insensitive) and the domain, if any.
+    fn with_links_conditions<'a>(raw_query: BoxedSqlQuery<'a, Sqlite, SqlQuery>,
+                                 scope: SearchScope,
+                                 query: &str,
+                                 domain_option: Option<&str>) -> Result<BoxedSqlQuery<'a, Sqlite, SqlQuery>> {
+        let mut raw_query = Self::with_scope_condition(raw_query, scope)?;
+        if !query.is_empty() {
+            raw_query = raw_query.sql(" AND instr(lower(ml.url), ?) > 0")
+                .bind::<sql_types::Text, _>(query.to_ascii_lowercase());
+        }
+        if let Some(domain) = domain_option {
+            raw_query = raw_query.sql(" AND ml.domain = ?").bind::<sql_types::Text, _>(domain.to_owned());
+        }
+        Ok(raw_query)
+    }
+
     fn copy_messages(&self,
                      conn: &mut SqliteConnection,
                      src_msgs: &[Message],
@@ -235,18 +327,40 @@ impl SqliteDao {
 
         let mut raw_mcs = vec![];
         let mut raw_rtes = vec![];
-        for (mut raw, internal_id) in full_raw_msgs.into_iter().zip(internal_ids) {
+        let mut raw_reactions = vec![];
+        let mut raw_receipts = vec![];
+        let mut raw_links = vec![];
+        for ((mut raw, internal_id), src_msg) in full_raw_msgs.into_iter().zip(internal_ids).zip(src_msgs) {
             if let Some(mut mc) = raw.mc {
-                mc.message_internal_id = internal_id;
+                mc.message_internal_id = Some(internal_id);
                 raw_mcs.push(mc);
             }
 
+            raw.extra_mcs.iter_mut().for_each(|mc| mc.message_internal_id = Some(internal_id));
+            raw_mcs.extend(raw.extra_mcs);
+
             raw.rtes.iter_mut().for_each(|rte| rte.message_internal_id = Some(internal_id));
             raw_rtes.extend(raw.rtes.into_iter());
+
+            raw.reactions.iter_mut().for_each(|r| r.message_internal_id = Some(internal_id));
+            raw_reactions.extend(raw.reactions);
+
+            raw.receipts.iter_mut().for_each(|r| r.message_internal_id = Some(internal_id));
+            raw_receipts.extend(raw.receipts);
+
+            raw_links.extend(src_msg.text.iter().flat_map(links::element_links).map(|url| RawLink {
+                id: None,
+                message_internal_id: Some(internal_id),
+                domain: links::link_domain(&url),
+                url,
+            }));
         }
 
         insert_into(message_content::table).values(raw_mcs).execute(conn)?;
         insert_into(message_text_element::table).values(raw_rtes).execute(conn)?;
+        insert_into(message_reaction::table).values(raw_reactions).execute(conn)?;
+        insert_into(message_receipt::table).values(raw_receipts).execute(conn)?;
+        insert_into(message_link::table).values(raw_links).execute(conn)?;
         Ok(())
     }
 }
@@ -344,7 +458,7 @@ impl ChatHistoryDao for SqliteDao {
                 .filter(message::columns::ds_uuid.eq(uuid.as_bytes().as_slice()))
                 .filter(message::columns::chat_id.eq(chat.id))
                 .order_by(message::columns::internal_id.asc())
-                .left_join(message_content::table)
+                .left_join(main_content!())
                 .offset(offset as i64)
                 .limit(limit as i64)
                 .select((RawMessage::as_select(), Option::<RawMessageContent>::as_select()))
@@ -360,7 +474,7 @@ impl ChatHistoryDao for SqliteDao {
                 .filter(message::columns::ds_uuid.eq(uuid.as_bytes().as_slice()))
                 .filter(message::columns::chat_id.eq(chat.id))
                 .order_by(message::columns::internal_id.desc())
-                .left_join(message_content::table)
+                .left_join(main_content!())
                 .limit(limit as i64)
                 .select((RawMessage::as_select(), Option::<RawMessageContent>::as_select()))
                 .load(conn)?)
@@ -378,7 +492,7 @@ impl ChatHistoryDao for SqliteDao {
                 .filter(message::columns::chat_id.eq(chat.id))
                 .filter(message::columns::internal_id.lt(*msg_id))
                 .order_by(message::columns::internal_id.desc())
-                .left_join(message_content::table)
+                .left_join(main_content!())
                 .limit(limit as i64)
                 .select((RawMessage::as_select(), Option::<RawMessageContent>::as_select()))
                 .load(conn)?)
@@ -396,7 +510,7 @@ impl ChatHistoryDao for SqliteDao {
                 .filter(message::columns::chat_id.eq(chat.id))
                 .filter(message::columns::internal_id.gt(*msg_id))
                 .order_by(message::columns::internal_id.asc())
-                .left_join(message_content::table)
+                .left_join(main_content!())
                 .limit(limit as i64)
                 .select((RawMessage::as_select(), Option::<RawMessageContent>::as_select()))
                 .load(conn)?)
@@ -420,7 +534,7 @@ impl ChatHistoryDao for SqliteDao {
                     .filter(message::columns::internal_id.ge(*first_id))
                     .filter(message::columns::internal_id.le(*msg2_id))
                     .order_by(message::columns::internal_id.asc())
-                    .left_join(message_content::table)
+                    .left_join(main_content!())
                     .limit(BATCH_SIZE as i64)
                     .select((RawMessage::as_select(), Option::<RawMessageContent>::as_select()))
                     .load(conn)?)
@@ -454,7 +568,7 @@ impl ChatHistoryDao for SqliteDao {
                         .filter(message::columns::chat_id.eq(chat.id))
                         .filter($cond)
                         .order_by(message::columns::internal_id.$order())
-                        .left_join(message_content::table)
+                        .left_join(main_content!())
                         .limit($limit as i64)
                         .select((RawMessage::as_select(), Option::<RawMessageContent>::as_select()))
                         .load(conn)?)
@@ -509,13 +623,410 @@ impl ChatHistoryDao for SqliteDao {
             Ok(message::table
                 .filter(message::columns::chat_id.eq(chat.id))
                 .filter(message::columns::source_id.eq(Some(*source_id)))
-                .left_join(message_content::table)
+                .left_join(main_content!())
                 .limit(1)
                 .select((RawMessage::as_select(), Option::<RawMessageContent>::as_select()))
                 .load(conn)?)
         }).map(|mut v| v.pop())
     }
 
+    fn filter_messages(&self, chat: &Chat, filter: &MessageFilter, offset: usize, limit: usize) -> Result<Vec<Message>> {
+        let uuid = Uuid::parse_str(&chat.ds_uuid.value)?;
+        let placeholders = |n: usize| (0..n).map(|_| "?").join(", ");
+
+        // Conditions are appended along with their bindings, so that they stay in sync
+        let mut raw_query = sql_query("SELECT m.internal_id FROM message m WHERE m.ds_uuid = ? AND m.chat_id = ?")
+            .into_boxed::<Sqlite>()
+            .bind::<sql_types::Binary, _>(uuid.as_bytes().to_vec())
+            .bind::<sql_types::BigInt, _>(chat.id);
+        if !filter.from_ids.is_empty() {
+            raw_query = raw_query.sql(format!(" AND m.from_id IN ({})", placeholders(filter.from_ids.len())));
+            for from_id in filter.from_ids.iter() {
+                raw_query = raw_query.bind::<sql_types::BigInt, _>(*from_id);
+            }
+        }
+        if let Some(ts) = filter.timestamp_from_option {
+            raw_query = raw_query.sql(" AND m.time_sent >= ?").bind::<sql_types::BigInt, _>(ts);
+        }
+        if let Some(ts) = filter.timestamp_to_option {
+            raw_query = raw_query.sql(" AND m.time_sent < ?").bind::<sql_types::BigInt, _>(ts);
+        }
+        if !filter.content_kinds.is_empty() || !filter.service_kinds.is_empty() {
+            use utils::EnumSerialization;
+            let content_kinds: Vec<String> = filter.content_kinds.iter().map(|k| ContentKind::serialize(*k)).try_collect()?;
+            let service_kinds: Vec<String> = filter.service_kinds.iter().map(|k| ServiceKind::serialize(*k)).try_collect()?;
+            raw_query = raw_query.sql(format!(r"
+                AND (
+                  m.type = 'regular' AND EXISTS (
+                    SELECT 1 FROM message_content mc
+                    WHERE mc.message_internal_id = m.internal_id AND mc.element_type IN ({})
+                  )
+                  OR m.type = 'service' AND m.subtype IN ({})
+                )", placeholders(content_kinds.len()), placeholders(service_kinds.len())));
+            for kind in content_kinds.into_iter().chain(service_kinds) {
+                raw_query = raw_query.bind::<sql_types::Text, _>(kind);
+            }
+        }
+        if let Some(has_reply) = filter.has_reply_option {
+            raw_query = raw_query.sql(if has_reply {
+                " AND m.reply_to_message_id IS NOT NULL"
+            } else {
+                " AND m.reply_to_message_id IS NULL"
+            });
+        }
+        if let Some(is_deleted) = filter.is_deleted_option {
+            raw_query = raw_query.sql(" AND m.is_deleted = ?").bind::<sql_types::Integer, _>(is_deleted as i32);
+        }
+        if let Some(is_edited) = filter.is_edited_option {
+            raw_query = raw_query.sql(if is_edited {
+                " AND m.time_edited IS NOT NULL"
+            } else {
+                " AND m.time_edited IS NULL"
+            });
+        }
+        raw_query = raw_query.sql(" ORDER BY m.internal_id ASC LIMIT ? OFFSET ?")
+            .bind::<sql_types::BigInt, _>(limit as i64)
+            .bind::<sql_types::BigInt, _>(offset as i64);
+
+        let internal_ids: Vec<i64> = raw_query
+            .load::<InternalIdWrapper>(self.conn.borrow_mut().deref_mut())?
+            .into_iter().map(|w| w.internal_id).collect_vec();
+        if internal_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        self.fetch_messages(|conn| {
+            use schema::*;
+            Ok(message::table
+                .filter(message::columns::internal_id.eq_any(&internal_ids))
+                .order_by(message::columns::internal_id.asc())
+                .left_join(main_content!())
+                .select((RawMessage::as_select(), Option::<RawMessageContent>::as_select()))
+                .load(conn)?)
+        })
+    }
+
+    fn media_items_impl(&self, scope: SearchScope, kinds: &[ContentKind], offset: usize, limit: usize) -> Result<Vec<MediaItem>> {
+        use utils::EnumSerialization;
+        let mut raw_query = sql_query(format!(r"
+            SELECT m.ds_uuid, m.chat_id, m.internal_id, m.time_sent, m.from_id, mc.element_type, mc.path, mc.thumbnail_path
+            FROM message_content mc
+            INNER JOIN message m ON m.internal_id = mc.message_internal_id
+            WHERE m.type = 'regular' AND mc.element_type IN ({})", kinds.iter().map(|_| "?").join(", ")))
+            .into_boxed::<Sqlite>();
+        for kind in kinds {
+            raw_query = raw_query.bind::<sql_types::Text, _>(ContentKind::serialize(*kind as i32)?);
+        }
+        let raw_items: Vec<RawMediaItem> = Self::with_scope_condition(raw_query, scope)?
+            .sql(" ORDER BY m.time_sent DESC, m.ds_uuid DESC, m.chat_id DESC, m.internal_id DESC, mc.ordinal ASC")
+            .sql(" LIMIT ? OFFSET ?")
+            .bind::<sql_types::BigInt, _>(limit as i64)
+            .bind::<sql_types::BigInt, _>(offset as i64)
+            .load(self.conn.borrow_mut().deref_mut())?;
+
+        let mut ds_roots: HashMap<Vec<u8>, (PbUuid, DatasetRoot)> = HashMap::new();
+        raw_items.into_iter().map(|raw| -> Result<MediaItem> {
+            let (ds_uuid, ds_root) = match ds_roots.get(&raw.ds_uuid) {
+                Some(v) => v.clone(),
+                None => {
+                    let ds_uuid = PbUuid { value: Uuid::from_slice(&raw.ds_uuid)?.to_string() };
+                    let ds_root = self.dataset_root(&ds_uuid)?;
+                    ds_roots.insert(raw.ds_uuid.clone(), (ds_uuid.clone(), ds_root.clone()));
+                    (ds_uuid, ds_root)
+                }
+            };
+            Ok(MediaItem {
+                file_exists: raw.path.as_ref().is_some_and(|p| ds_root.to_absolute(p).exists()),
+                ds_uuid,
+                chat_id: raw.chat_id,
+                message_internal_id: raw.internal_id,
+                timestamp: raw.time_sent,
+                from_id: raw.from_id,
+                kind: ContentKind::deserialize(&raw.element_type)?,
+                path_option: raw.path,
+                thumbnail_path_option: raw.thumbnail_path,
+            })
+        }).try_collect()
+    }
+
+    fn links(&self, scope: SearchScope, query: &str, domain_option: Option<&str>, offset: usize, limit: usize) -> Result<Vec<LinkItem>> {
+        let raw_query = Self::with_links_conditions(sql_query(r"
+            SELECT m.ds_uuid, m.chat_id, m.internal_id, m.time_sent, m.from_id, ml.url, ml.domain
+            FROM message_link ml
+            INNER JOIN message m ON m.internal_id = ml.message_internal_id
+            WHERE TRUE").into_boxed(), scope, query, domain_option)?;
+        let raw_items: Vec<RawLinkItem> = raw_query
+            .sql(" ORDER BY m.time_sent DESC, m.ds_uuid DESC, m.chat_id DESC, m.internal_id DESC, ml.id ASC")
+            .sql(" LIMIT ? OFFSET ?")
+            .bind::<sql_types::BigInt, _>(limit as i64)
+            .bind::<sql_types::BigInt, _>(offset as i64)
+            .load(self.conn.borrow_mut().deref_mut())?;
+
+        let mut ds_uuids: HashMap<Vec<u8>, PbUuid> = HashMap::new();
+        raw_items.into_iter().map(|raw| -> Result<LinkItem> {
+            let ds_uuid = match ds_uuids.get(&raw.ds_uuid) {
+                Some(v) => v.clone(),
+                None => {
+                    let ds_uuid = PbUuid { value: Uuid::from_slice(&raw.ds_uuid)?.to_string() };
+                    ds_uuids.insert(raw.ds_uuid.clone(), ds_uuid.clone());
+                    ds_uuid
+                }
+            };
+            Ok(LinkItem {
+                ds_uuid,
+                chat_id: raw.chat_id,
+                message_internal_id: raw.internal_id,
+                timestamp: raw.time_sent,
+                from_id: raw.from_id,
+                url: raw.url,
+                domain: raw.domain,
+            })
+        }).try_collect()
+    }
+
+    fn link_domains(&self, scope: SearchScope, query: &str) -> Result<Vec<DomainCount>> {
+        let raw_query = Self::with_links_conditions(sql_query(r"
+            SELECT ml.domain, COUNT(*) AS count
+            FROM message_link ml
+            INNER JOIN message m ON m.internal_id = ml.message_internal_id
+            WHERE TRUE").into_boxed(), scope, query, None)?;
+        let raw_counts: Vec<RawDomainCount> = raw_query
+            .sql(" GROUP BY ml.domain ORDER BY count DESC, ml.domain ASC")
+            .load(self.conn.borrow_mut().deref_mut())?;
+        Ok(raw_counts.into_iter().map(|raw| DomainCount { domain: raw.domain, count: raw.count }).collect_vec())
+    }
+
+    fn links_impl(&self, scope: SearchScope, query: &str) -> Result<Vec<LinkItem>> {
+        self.links(scope, query, None, 0, usize::MAX)
+    }
+
+    fn search_messages(&self, scope: SearchScope, query: &str, offset: usize, limit: usize) -> Result<Vec<MessageSearchHit>> {
+        // Every term is quoted so that FTS5 syntax characters in it are treated literally
+        let fts_query = query.split_whitespace()
+            .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+            .join(" ");
+        if fts_query.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let raw_query = sql_query(r"
+            SELECT m.internal_id, m.ds_uuid, m.chat_id, snippet(message_fts, 0, ?, ?, '…', 16) AS snippet
+            FROM message_fts
+            INNER JOIN message m ON m.internal_id = message_fts.rowid
+            WHERE message_fts MATCH ?").into_boxed::<Sqlite>()
+            .bind::<sql_types::Text, _>(SEARCH_HIGHLIGHT_START)
+            .bind::<sql_types::Text, _>(SEARCH_HIGHLIGHT_END)
+            .bind::<sql_types::Text, _>(fts_query);
+        let raw_hits: Vec<RawSearchHit> = Self::with_scope_condition(raw_query, scope)?
+            .sql(" ORDER BY bm25(message_fts), m.internal_id LIMIT ? OFFSET ?")
+            .bind::<sql_types::BigInt, _>(limit as i64)
+            .bind::<sql_types::BigInt, _>(offset as i64)
+            .load(self.conn.borrow_mut().deref_mut())?;
+        if raw_hits.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let internal_ids = raw_hits.iter().map(|h| h.internal_id).collect_vec();
+        let mut msgs_by_id: HashMap<i64, Message> = self.fetch_messages(|conn| {
+            use schema::*;
+            Ok(message::table
+                .filter(message::columns::internal_id.eq_any(&internal_ids))
+                .left_join(main_content!())
+                .select((RawMessage::as_select(), Option::<RawMessageContent>::as_select()))
+                .load(conn)?)
+        })?.into_iter().map(|m| (m.internal_id, m)).collect();
+
+        raw_hits.into_iter().map(|h| -> Result<MessageSearchHit> {
+            Ok(MessageSearchHit {
+                ds_uuid: PbUuid { value: Uuid::from_slice(&h.ds_uuid)?.to_string() },
+                chat_id: ChatId(h.chat_id),
+                message: msgs_by_id.remove(&h.internal_id)
+                    .with_context(|| format!("Message {} not found!", h.internal_id))?,
+                snippet: h.snippet,
+            })
+        }).try_collect()
+    }
+
+    fn timeline(&self,
+                scope: SearchScope,
+                timestamp_from_option: Option<i64>,
+                timestamp_to_option: Option<i64>,
+                after_option: Option<&TimelinePosition>,
+                limit: usize) -> Result<Vec<TimelineEntry>> {
+        let mut raw_query = Self::with_scope_condition(
+            sql_query("SELECT m.internal_id, m.ds_uuid, m.chat_id FROM message m WHERE TRUE").into_boxed(), scope)?;
+        if let Some(ts) = timestamp_from_option {
+            raw_query = raw_query.sql(" AND m.time_sent >= ?").bind::<sql_types::BigInt, _>(ts);
+        }
+        if let Some(ts) = timestamp_to_option {
+            raw_query = raw_query.sql(" AND m.time_sent < ?").bind::<sql_types::BigInt, _>(ts);
+        }
+        if let Some(after) = after_option {
+            // Within a dataset, keyset matches message_timeline_idx, otherwise - message_time_sent_global_idx
+            raw_query = match scope {
+                SearchScope::Everywhere =>
+                    raw_query.sql(" AND (m.time_sent, m.ds_uuid, m.chat_id, m.internal_id) > (?, ?, ?, ?)")
+                        .bind::<sql_types::BigInt, _>(after.timestamp)
+                        .bind::<sql_types::Binary, _>(Uuid::parse_str(&after.ds_uuid.value)?.as_bytes().to_vec()),
+                SearchScope::Dataset(_) | SearchScope::Chat(_) =>
+                    raw_query.sql(" AND (m.time_sent, m.chat_id, m.internal_id) > (?, ?, ?)")
+                        .bind::<sql_types::BigInt, _>(after.timestamp),
+            }.bind::<sql_types::BigInt, _>(after.chat_id)
+                .bind::<sql_types::BigInt, _>(after.message_internal_id);
+        }
+        let refs: Vec<MessageRefWrapper> = raw_query
+            .sql(" ORDER BY m.time_sent, m.ds_uuid, m.chat_id, m.internal_id LIMIT ?")
+            .bind::<sql_types::BigInt, _>(limit as i64)
+            .load(self.conn.borrow_mut().deref_mut())?;
+        if refs.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let internal_ids = refs.iter().map(|r| r.internal_id).collect_vec();
+        let mut msgs_by_id: HashMap<i64, Message> = self.fetch_messages(|conn| {
+            use schema::*;
+            Ok(message::table
+                .filter(message::columns::internal_id.eq_any(&internal_ids))
+                .left_join(main_content!())
+                .select((RawMessage::as_select(), Option::<RawMessageContent>::as_select()))
+                .load(conn)?)
+        })?.into_iter().map(|m| (m.internal_id, m)).collect();
+
+        refs.into_iter().map(|r| -> Result<TimelineEntry> {
+            Ok(TimelineEntry {
+                ds_uuid: PbUuid { value: Uuid::from_slice(&r.ds_uuid)?.to_string() },
+                chat_id: r.chat_id,
+                message: msgs_by_id.remove(&r.internal_id)
+                    .with_context(|| format!("Message {} not found!", r.internal_id))?,
+            })
+        }).try_collect()
+    }
+
+    fn statistics(&self, scope: SearchScope, top_words_limit: usize) -> Result<Statistics> {
+        use statistics::{StatisticsAccumulator, MAX_RESPONSE_TIME_SEC};
+        use utils::EnumSerialization;
+        ensure!(!matches!(scope, SearchScope::Everywhere), "Statistics can only be computed for a chat or a dataset!");
+
+        let mut conn = self.conn.borrow_mut();
+        let conn = conn.deref_mut();
+
+        // Selects from `message m` narrowed down to scope, with the given tail appended
+        let scoped = |select: &str, tail: &str| -> Result<BoxedSqlQuery<'static, Sqlite, SqlQuery>> {
+            Ok(Self::with_scope_condition(sql_query(select).into_boxed(), scope)?.sql(tail))
+        };
+        const LOCAL_TIME_SENT: &str = "m.time_sent, 'unixepoch', 'localtime'";
+
+        let mut acc = StatisticsAccumulator::default();
+
+        let totals: RawStatisticsTotals = scoped(r"
+            SELECT COUNT(*) AS messages_count, MIN(m.time_sent) AS first_time_sent, MAX(m.time_sent) AS last_time_sent
+            FROM message m WHERE TRUE", "")?.get_result(conn)?;
+        acc.messages_count = totals.messages_count;
+        acc.first_timestamp_option = totals.first_time_sent;
+        acc.last_timestamp_option = totals.last_time_sent;
+
+        let per_user: Vec<RawIntCount> = scoped(r"
+            SELECT m.from_id AS key, COUNT(*) AS count
+            FROM message m WHERE TRUE", " GROUP BY m.from_id")?.load(conn)?;
+        acc.per_user = per_user.into_iter().map(|r| (r.key, r.count)).collect();
+
+        let per_day: Vec<RawTextCount> = scoped(&format!(r"
+            SELECT strftime('%Y-%m-%d', {LOCAL_TIME_SENT}) AS key, COUNT(*) AS count
+            FROM message m WHERE TRUE"), " GROUP BY key")?.load(conn)?;
+        acc.per_day = per_day.into_iter().map(|r| (r.key, r.count)).collect();
+
+        let per_hour: Vec<RawIntCount> = scoped(&format!(r"
+            SELECT CAST(strftime('%H', {LOCAL_TIME_SENT}) AS INTEGER) AS key, COUNT(*) AS count
+            FROM message m WHERE TRUE"), " GROUP BY key")?.load(conn)?;
+        for r in per_hour {
+            acc.per_hour[r.key as usize] = r.count;
+        }
+
+        // SQLite weekdays start from Sunday
+        let per_weekday: Vec<RawIntCount> = scoped(&format!(r"
+            SELECT (CAST(strftime('%w', {LOCAL_TIME_SENT}) AS INTEGER) + 6) % 7 AS key, COUNT(*) AS count
+            FROM message m WHERE TRUE"), " GROUP BY key")?.load(conn)?;
+        for r in per_weekday {
+            acc.per_weekday[r.key as usize] = r.count;
+        }
+
+        let response_times: Vec<RawIntSumCount> = scoped(r"
+            SELECT from_id AS key, SUM(time_sent - prev_time_sent) AS sum, COUNT(*) AS count FROM (
+              SELECT m.from_id, m.time_sent,
+                     LAG(m.from_id) OVER w AS prev_from_id,
+                     LAG(m.time_sent) OVER w AS prev_time_sent
+              FROM message m WHERE m.type = 'regular'", r"
+              WINDOW w AS (PARTITION BY m.ds_uuid, m.chat_id ORDER BY m.internal_id)
+            )
+            WHERE prev_from_id != from_id AND time_sent - prev_time_sent BETWEEN 0 AND ?
+            GROUP BY from_id")?
+            .bind::<sql_types::BigInt, _>(MAX_RESPONSE_TIME_SEC)
+            .load(conn)?;
+        acc.response_times = response_times.into_iter().map(|r| (r.key, (r.sum, r.count))).collect();
+
+        let calls: RawSumCount = scoped(r"
+            SELECT COALESCE(SUM(mc.duration_sec), 0) AS sum, COUNT(*) AS count
+            FROM message m
+            LEFT JOIN message_content mc ON mc.message_internal_id = m.internal_id AND mc.ordinal = 0
+            WHERE m.type = 'service' AND m.subtype = 'phone_call'", "")?.get_result(conn)?;
+        acc.calls_count = calls.count;
+        acc.calls_duration_sec = calls.sum;
+
+        let mut media_query = sql_query(format!(r"
+            SELECT mc.element_type AS key, COUNT(*) AS count
+            FROM message_content mc
+            INNER JOIN message m ON m.internal_id = mc.message_internal_id
+            WHERE m.type = 'regular' AND mc.element_type IN ({})", MEDIA_CONTENT_KINDS.iter().map(|_| "?").join(", ")))
+            .into_boxed::<Sqlite>();
+        for kind in MEDIA_CONTENT_KINDS {
+            media_query = media_query.bind::<sql_types::Text, _>(ContentKind::serialize(kind as i32)?);
+        }
+        let media_counts: Vec<RawTextCount> = Self::with_scope_condition(media_query, scope)?
+            .sql(" GROUP BY mc.element_type")
+            .load(conn)?;
+        acc.media_counts = media_counts.into_iter()
+            .map(|r| -> Result<(i32, i64)> { Ok((ContentKind::deserialize(&r.key)?, r.count)) })
+            .try_collect()?;
+
+        // Words are counted in batches to avoid loading all the strings at once
+        const BATCH_SIZE: i64 = 10_000;
+        let mut last_internal_id = -1;
+        loop {
+            let batch: Vec<SearchableStringWrapper> = scoped(r"
+                SELECT m.internal_id, m.searchable_string
+                FROM message m WHERE TRUE", " AND m.internal_id > ? ORDER BY m.internal_id LIMIT ?")?
+                .bind::<sql_types::BigInt, _>(last_internal_id)
+                .bind::<sql_types::BigInt, _>(BATCH_SIZE)
+                .load(conn)?;
+            batch.iter().for_each(|w| acc.add_words(&w.searchable_string));
+            match batch.last() {
+                Some(w) if batch.len() as i64 == BATCH_SIZE => last_internal_id = w.internal_id,
+                _ => break,
+            }
+        }
+
+        Ok(acc.finish(top_words_limit))
+    }
+
+    fn activity_counts(&self,
+                       chat: &Chat,
+                       timestamp_from_option: Option<i64>,
+                       timestamp_to_option: Option<i64>,
+                       granularity: ActivityGranularity) -> Result<Vec<DateCount>> {
+        let mut raw_query = Self::with_scope_condition(sql_query(format!(r"
+            SELECT strftime('{}', m.time_sent, 'unixepoch', 'localtime') AS key, COUNT(*) AS count
+            FROM message m WHERE TRUE", granularity.date_format())).into_boxed(), SearchScope::Chat(chat))?;
+        if let Some(ts) = timestamp_from_option {
+            raw_query = raw_query.sql(" AND m.time_sent >= ?").bind::<sql_types::BigInt, _>(ts);
+        }
+        if let Some(ts) = timestamp_to_option {
+            raw_query = raw_query.sql(" AND m.time_sent < ?").bind::<sql_types::BigInt, _>(ts);
+        }
+        let counts: Vec<RawTextCount> = raw_query
+            .sql(" GROUP BY key ORDER BY key")
+            .load(self.conn.borrow_mut().deref_mut())?;
+        Ok(counts.into_iter().map(|r| DateCount { date: r.key, count: r.count }).collect_vec())
+    }
+
     fn as_mutable(&mut self) -> Result<&mut dyn MutableChatHistoryDao> {
         Ok(self)
     }
@@ -672,6 +1183,27 @@ impl MutableChatHistoryDao for SqliteDao {
                     WHERE ds_uuid = ?
                 )
             ")?;
+            delete_by_ds_uuid(r"
+                DELETE FROM message_reaction
+                WHERE message_internal_id IN (
+                    SELECT internal_id FROM message
+                    WHERE ds_uuid = ?
+                )
+            ")?;
+            delete_by_ds_uuid(r"
+                DELETE FROM message_receipt
+                WHERE message_internal_id IN (
+                    SELECT internal_id FROM message
+                    WHERE ds_uuid = ?
+                )
+            ")?;
+            delete_by_ds_uuid(r"
+                DELETE FROM message_link
+                WHERE message_internal_id IN (
+                    SELECT internal_id FROM message
+                    WHERE ds_uuid = ?
+                )
+            ")?;
             delete(message::dsl::message)
                 .filter(message::columns::ds_uuid.eq(uuid.as_bytes().as_slice()))
                 .execute(conn)?;
@@ -706,7 +1238,13 @@ impl MutableChatHistoryDao for SqliteDao {
         })
     }
 
-    fn insert_user(&mut self, user: User, is_myself: bool) -> Result<User> {
+    fn insert_user(&mut self, mut user: User, is_myself: bool, src_ds_root: &DatasetRoot) -> Result<User> {
+        if let Some(ref img) = user.img_path_option {
+            let dst_ds_root = self.dataset_root(&user.ds_uuid)?;
+            user.img_path_option = copy_file_into(img, &None, &subpaths::AVATARS,
+                                                  USERS_ROOT_REL_PATH, src_ds_root, &dst_ds_root)?;
+        }
+
         self.invalidate_cache()?;
         let mut conn = self.conn.borrow_mut();
         let conn = conn.deref_mut();
@@ -771,17 +1309,96 @@ impl MutableChatHistoryDao for SqliteDao {
                     .set(message::columns::from_id.eq(user.id))
                     .execute(conn)?;
 
+                update(message::dsl::message)
+                    .filter(message::columns::ds_uuid.eq(uuid.as_bytes().as_slice()))
+                    .filter(message::columns::forward_from_id.eq(*old_id))
+                    .set(message::columns::forward_from_id.eq(user.id))
+                    .execute(conn)?;
+
+                sql_query(r"
+                    UPDATE message_reaction
+                    SET from_id = ?
+                    WHERE from_id = ? AND message_internal_id IN (
+                        SELECT internal_id FROM message
+                        WHERE ds_uuid = ?
+                    )
+                ")
+                    .bind::<sql_types::BigInt, _>(user.id)
+                    .bind::<sql_types::BigInt, _>(*old_id)
+                    .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
+                    .execute(conn)?;
+
+                sql_query(r"
+                    UPDATE message_receipt
+                    SET user_id = ?
+                    WHERE user_id = ? AND message_internal_id IN (
+                        SELECT internal_id FROM message
+                        WHERE ds_uuid = ?
+                    )
+                ")
+                    .bind::<sql_types::BigInt, _>(user.id)
+                    .bind::<sql_types::BigInt, _>(*old_id)
+                    .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
+                    .execute(conn)?;
+
+                sql_query(r"
+                    UPDATE message_text_element
+                    SET user_id = ?
+                    WHERE element_type = 'mention' AND user_id = ? AND message_internal_id IN (
+                        SELECT internal_id FROM message
+                        WHERE ds_uuid = ?
+                    )
+                ")
+                    .bind::<sql_types::BigInt, _>(user.id)
+                    .bind::<sql_types::BigInt, _>(*old_id)
+                    .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
+                    .execute(conn)?;
+
                 update(chat_member::dsl::chat_member)
                     .filter(chat_member::columns::user_id.eq(*old_id))
                     .set(chat_member::columns::user_id.eq(user.id))
                     .execute(conn)?;
             }
 
+            // Messages which searchable string depends on the user name
+            let mut renamed_msg_ids: Vec<i64> = vec![];
+
+            // Update displayed text of mentions of this user.
+            // Only mentions showing the old name are touched, others (e.g. WhatsApp phone numbers) are kept as-is.
+            if let (Some(old_name), Some(new_name)) = (old_name.as_ref(), user.pretty_name_option()) {
+                let mention_msg_ids: Vec<Option<i64>> = message_text_element::table
+                    .filter(message_text_element::columns::element_type.eq("mention"))
+                    .filter(message_text_element::columns::user_id.eq(user.id))
+                    .filter(message_text_element::columns::text.eq(old_name))
+                    .filter(message_text_element::columns::message_internal_id.eq_any(
+                        message::table
+                            .filter(message::columns::ds_uuid.eq(uuid.as_bytes().as_slice()))
+                            .select(message::columns::internal_id.nullable())
+                    ))
+                    .select(message_text_element::columns::message_internal_id)
+                    .load(conn)?;
+                renamed_msg_ids.extend(mention_msg_ids.into_iter().flatten());
+
+                sql_query(r"
+                    UPDATE message_text_element
+                    SET text = ?
+                    WHERE element_type = 'mention' AND user_id = ? AND text = ? AND message_internal_id IN (
+                        SELECT internal_id FROM message
+                        WHERE ds_uuid = ?
+                    )
+                ")
+                    .bind::<sql_types::Text, _>(new_name)
+                    .bind::<sql_types::BigInt, _>(user.id)
+                    .bind::<sql_types::Text, _>(old_name)
+                    .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
+                    .execute(conn)?;
+            }
+
             // Update user name in "members" string field
             if let Some(old_name) = old_name {
                 let new_name = user.pretty_name();
 
-                let old_mc_members: Vec<(i64, Option<String>)> = message_content::table
+                let old_mc_members: Vec<(i64, i64, Option<String>)> = message_content::table
                     .inner_join(message::table)
                     .inner_join(chat::table
                         .on(chat::columns::ds_uuid.eq(message::columns::ds_uuid)
@@ -792,10 +1409,12 @@ impl MutableChatHistoryDao for SqliteDao {
                     .filter(chat::columns::ds_uuid.eq(uuid.as_bytes().as_slice()))
                     .filter(chat_member::columns::user_id.eq(user.id))
                     .filter(message_content::columns::members.like(format!("%{old_name}%")))
-                    .select((message_content::columns::id, message_content::columns::members))
+                    .select((message_content::columns::id,
+                             message_content::columns::message_internal_id.assume_not_null(),
+                             message_content::columns::members))
                     .load(conn)?;
 
-                for (id, members_string) in old_mc_members {
+                for (id, msg_internal_id, members_string) in old_mc_members {
                     let new_members_string = utils::serialize_arr(&utils::deserialize_arr(members_string)
                         .into_iter()
                         .map(|s| if s == old_name { new_name.clone() } else { s })
@@ -805,6 +1424,27 @@ impl MutableChatHistoryDao for SqliteDao {
                         .filter(message_content::columns::id.eq(id))
                         .set(message_content::columns::members.eq(new_members_string))
                         .execute(conn)?;
+                    renamed_msg_ids.push(msg_internal_id);
+                }
+            }
+
+            // Recompute searchable strings, which also re-indexes these messages for full-text search
+            const BATCH_SIZE: usize = 5_000;
+            renamed_msg_ids.sort_unstable();
+            renamed_msg_ids.dedup();
+            for msg_ids in renamed_msg_ids.chunks(BATCH_SIZE) {
+                let msgs = utils::message::fetch(conn, |conn| {
+                    Ok(message::table
+                        .filter(message::columns::internal_id.eq_any(msg_ids))
+                        .left_join(main_content!())
+                        .select((RawMessage::as_select(), Option::<RawMessageContent>::as_select()))
+                        .load(conn)?)
+                })?;
+                for msg in msgs {
+                    update(message::table)
+                        .filter(message::columns::internal_id.eq(msg.internal_id))
+                        .set(message::columns::searchable_string.eq(make_searchable_string(&msg.text, msg.typed())))
+                        .execute(conn)?;
                 }
             }
 
@@ -824,6 +1464,7 @@ impl MutableChatHistoryDao for SqliteDao {
         let uuid = Uuid::parse_str(&chat.ds_uuid.value).expect("Invalid UUID!");
         let uuid_bytes = Vec::from(uuid.as_bytes().as_slice());
         let raw_chat = utils::chat::serialize(&chat, &uuid_bytes)?;
+        let chat_members = utils::chat::serialize_members(&chat, &uuid_bytes)?;
 
         let myself = self.myself(&chat.ds_uuid)?;
         ensure!(chat.member_ids.first() == Some(&myself.id),
@@ -835,13 +1476,6 @@ impl MutableChatHistoryDao for SqliteDao {
             .values(raw_chat)
             .execute(conn)?;
 
-        let chat_members = chat.member_ids.iter().enumerate().map(|(order, &user_id)| RawChatMember {
-            ds_uuid: uuid_bytes.clone(),
-            chat_id: chat.id,
-            user_id,
-            order: order as i32,
-        }).collect_vec();
-
         insert_into(schema::chat_member::dsl::chat_member)
             .values(chat_members)
             .execute(conn)?;
@@ -927,6 +1561,34 @@ impl MutableChatHistoryDao for SqliteDao {
                         .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
                         .bind::<sql_types::BigInt, _>(chat.id)
                         .execute(conn)?;
+
+                    sql_query(r"
+                        UPDATE message_reaction
+                        SET custom_sticker_path = REPLACE(custom_sticker_path, ?, ?)
+                        WHERE message_internal_id IN (
+                            SELECT internal_id FROM message
+                            WHERE ds_uuid = ? AND chat_id = ?
+                        )
+                    ")
+                        .bind::<sql_types::Text, _>(&old_rel_path)
+                        .bind::<sql_types::Text, _>(&new_rel_path)
+                        .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
+                        .bind::<sql_types::BigInt, _>(chat.id)
+                        .execute(conn)?;
+
+                    sql_query(r"
+                        UPDATE message_text_element
+                        SET path = REPLACE(path, ?, ?)
+                        WHERE message_internal_id IN (
+                            SELECT internal_id FROM message
+                            WHERE ds_uuid = ? AND chat_id = ?
+                        )
+                    ")
+                        .bind::<sql_types::Text, _>(&old_rel_path)
+                        .bind::<sql_types::Text, _>(&new_rel_path)
+                        .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
+                        .bind::<sql_types::BigInt, _>(chat.id)
+                        .execute(conn)?;
                 }
             }
             ok(())
@@ -970,6 +1632,32 @@ impl MutableChatHistoryDao for SqliteDao {
                 .flatten()
                 .collect_vec();
 
+            relative_paths.extend(sql_query(r"
+                SELECT mr.custom_sticker_path AS path, NULL AS thumbnail_path FROM message_reaction mr
+                WHERE mr.message_internal_id IN (
+                    SELECT internal_id FROM message
+                    WHERE ds_uuid = ? AND chat_id = ?
+                )
+            ")
+                .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
+                .bind::<sql_types::BigInt, _>(chat.id)
+                .load::<PathsWrapper>(conn)?
+                .into_iter()
+                .filter_map(|p| p.path));
+
+            relative_paths.extend(sql_query(r"
+                SELECT mte.path, NULL AS thumbnail_path FROM message_text_element mte
+                WHERE mte.message_internal_id IN (
+                    SELECT internal_id FROM message
+                    WHERE ds_uuid = ? AND chat_id = ?
+                )
+            ")
+                .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
+                .bind::<sql_types::BigInt, _>(chat.id)
+                .load::<PathsWrapper>(conn)?
+                .into_iter()
+                .filter_map(|p| p.path));
+
             if let Some(ref img_path) = chat.img_path_option {
                 relative_paths.push(img_path.clone());
             }
@@ -989,6 +1677,27 @@ impl MutableChatHistoryDao for SqliteDao {
                     WHERE ds_uuid = ? AND chat_id = ?
                 )
             ", conn)?;
+            delete_by_ds_and_chat(r"
+                DELETE FROM message_reaction
+                WHERE message_internal_id IN (
+                    SELECT internal_id FROM message
+                    WHERE ds_uuid = ? AND chat_id = ?
+                )
+            ", conn)?;
+            delete_by_ds_and_chat(r"
+                DELETE FROM message_receipt
+                WHERE message_internal_id IN (
+                    SELECT internal_id FROM message
+                    WHERE ds_uuid = ? AND chat_id = ?
+                )
+            ", conn)?;
+            delete_by_ds_and_chat(r"
+                DELETE FROM message_link
+                WHERE message_internal_id IN (
+                    SELECT internal_id FROM message
+                    WHERE ds_uuid = ? AND chat_id = ?
+                )
+            ", conn)?;
             delete(message::dsl::message)
                 .filter(message::columns::ds_uuid.eq(uuid.as_bytes().as_slice()))
                 .filter(message::columns::chat_id.eq(chat.id))
@@ -1083,7 +1792,7 @@ impl MutableChatHistoryDao for SqliteDao {
 
 impl ShiftableChatHistoryDao for SqliteDao {
     fn shift_dataset_time(&mut self, uuid: &PbUuid, hours_shift: i32) -> EmptyRes {
-        // Messages aren't cached so no need to invalidate cache
+        // Chats and messages aren't cached so no need to invalidate cache
         let mut conn = self.conn.borrow_mut();
         let conn = conn.deref_mut();
 
@@ -1091,8 +1800,52 @@ impl ShiftableChatHistoryDao for SqliteDao {
         let timestamp_shift = hours_shift * 60 * 60;
         sql_query(r"
             UPDATE message SET
-              time_sent   = time_sent + ?,
-              time_edited = time_edited + ?
+              time_sent    = time_sent + ?,
+              time_edited  = time_edited + ?,
+              forward_time = forward_time + ?
+            WHERE ds_uuid = ?
+        ")
+            .bind::<sql_types::Integer, _>(timestamp_shift)
+            .bind::<sql_types::Integer, _>(timestamp_shift)
+            .bind::<sql_types::Integer, _>(timestamp_shift)
+            .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
+            .execute(conn)?;
+        sql_query(r"
+            UPDATE message_reaction SET
+              time_sent = time_sent + ?
+            WHERE message_internal_id IN (
+              SELECT internal_id FROM message
+              WHERE ds_uuid = ?
+            )
+        ")
+            .bind::<sql_types::Integer, _>(timestamp_shift)
+            .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
+            .execute(conn)?;
+        sql_query(r"
+            UPDATE message_receipt SET
+              time_delivered = time_delivered + ?,
+              time_read      = time_read + ?
+            WHERE message_internal_id IN (
+              SELECT internal_id FROM message
+              WHERE ds_uuid = ?
+            )
+        ")
+            .bind::<sql_types::Integer, _>(timestamp_shift)
+            .bind::<sql_types::Integer, _>(timestamp_shift)
+            .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
+            .execute(conn)?;
+        sql_query(r"
+            UPDATE chat SET
+              time_created = time_created + ?
+            WHERE ds_uuid = ?
+        ")
+            .bind::<sql_types::Integer, _>(timestamp_shift)
+            .bind::<sql_types::Binary, _>(uuid.as_bytes().as_slice())
+            .execute(conn)?;
+        sql_query(r"
+            UPDATE chat_member SET
+              time_joined = time_joined + ?,
+              time_left   = time_left + ?
             WHERE ds_uuid = ?
         ")
             .bind::<sql_types::Integer, _>(timestamp_shift)
@@ -1114,6 +1867,9 @@ fn chat_root_rel_path(chat_id: i64) -> String {
     format!("chat_{chat_id}")
 }
 
+/// Root for files that belong to users rather than to any specific chat.
+const USERS_ROOT_REL_PATH: &str = "users";
+
 /// Subpath inside a directory, suffixed by " / " to be concatenated.
 struct Subpath {
     path_fragment: &'static str,
@@ -1131,6 +1887,7 @@ mod subpaths {
     pub(super) static VIDEO_MESSAGES: Subpath = Subpath { path_fragment: "video_messages", use_hashing: true };
     pub(super) static VIDEOS: Subpath = Subpath { path_fragment: "videos", use_hashing: true };
     pub(super) static FILES: Subpath = Subpath { path_fragment: "files", use_hashing: false };
+    pub(super) static AVATARS: Subpath = Subpath { path_fragment: "avatars", use_hashing: true };
 }
 
 fn copy_file(src_rel_path: &str,
@@ -1139,6 +1896,16 @@ fn copy_file(src_rel_path: &str,
              chat_id: i64,
              src_ds_root: &DatasetRoot,
              dst_ds_root: &DatasetRoot) -> Result<Option<String>> {
+    copy_file_into(src_rel_path, thumbnail_dst_main_path, subpath,
+                   &chat_root_rel_path(chat_id), src_ds_root, dst_ds_root)
+}
+
+fn copy_file_into(src_rel_path: &str,
+                  thumbnail_dst_main_path: &Option<String>,
+                  subpath: &Subpath,
+                  root_rel_path: &str,
+                  src_ds_root: &DatasetRoot,
+                  dst_ds_root: &DatasetRoot) -> Result<Option<String>> {
     let src_file = src_ds_root.to_absolute(src_rel_path);
     let src_absolute_path = path_to_str(&src_file)?;
     let src_meta = fs::metadata(&src_file);
@@ -1159,7 +1926,7 @@ fn copy_file(src_rel_path: &str,
                 } else {
                     src_file.file_name().unwrap().to_str().unwrap().to_owned()
                 };
-                format!("{}/{}/{inner_path}", chat_root_rel_path(chat_id), subpath.path_fragment)
+                format!("{root_rel_path}/{}/{inner_path}", subpath.path_fragment)
             };
         let dst_file = dst_ds_root.to_absolute(&dst_rel_path);
         fs::create_dir_all(dst_file.parent().unwrap()).context("Can't create dataset root path")?;
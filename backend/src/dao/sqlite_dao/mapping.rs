@@ -18,6 +18,9 @@ pub mod schema {
             username -> Nullable<Text>,
             phone_numbers -> Nullable<Text>,
             is_myself -> Integer,
+            emails -> Nullable<Text>,
+            bio -> Nullable<Text>,
+            img_path -> Nullable<Text>,
         }
     }
 
@@ -32,6 +35,8 @@ pub mod schema {
             img_path -> Nullable<Text>,
             msg_count -> Integer,
             main_chat_id -> Nullable<BigInt>,
+            description -> Nullable<Text>,
+            time_created -> Nullable<BigInt>,
         }
     }
 
@@ -41,6 +46,9 @@ pub mod schema {
             chat_id -> BigInt,
             user_id -> BigInt,
             order -> Integer,
+            role -> Nullable<Text>,
+            time_joined -> Nullable<BigInt>,
+            time_left -> Nullable<BigInt>,
         }
     }
 
@@ -60,13 +68,21 @@ pub mod schema {
             forward_from_name -> Nullable<Text>,
             reply_to_message_id -> Nullable<BigInt>,
             searchable_string -> Text,
+            delivery_status -> Nullable<Text>,
+            forward_from_id -> Nullable<BigInt>,
+            forward_chat_name -> Nullable<Text>,
+            forward_time -> Nullable<BigInt>,
+            forward_source_id -> Nullable<BigInt>,
+            forward_author_name -> Nullable<Text>,
+            ttl_sec -> Nullable<Integer>,
         }
     }
 
     diesel::table! {
         message_content (id) {
             id -> BigInt,
-            message_internal_id -> BigInt,
+            message_internal_id -> Nullable<BigInt>,
+            ordinal -> Integer,
             element_type -> Text,
             path -> Nullable<Text>,
             thumbnail_path -> Nullable<Text>,
@@ -89,6 +105,9 @@ pub mod schema {
             discard_reason -> Nullable<Text>,
             pinned_message_id -> Nullable<BigInt>,
             is_blocked -> Nullable<Integer>,
+            url -> Nullable<Text>,
+            description -> Nullable<Text>,
+            site_name -> Nullable<Text>,
         }
     }
 
@@ -101,6 +120,38 @@ pub mod schema {
             href -> Nullable<Text>,
             hidden -> Nullable<Integer>,
             language -> Nullable<Text>,
+            user_id -> Nullable<BigInt>,
+            path -> Nullable<Text>,
+        }
+    }
+
+    diesel::table! {
+        message_reaction (id) {
+            id -> BigInt,
+            message_internal_id -> Nullable<BigInt>,
+            emoji -> Nullable<Text>,
+            custom_sticker_path -> Nullable<Text>,
+            from_id -> BigInt,
+            time_sent -> Nullable<BigInt>,
+        }
+    }
+
+    diesel::table! {
+        message_receipt (id) {
+            id -> BigInt,
+            message_internal_id -> Nullable<BigInt>,
+            user_id -> BigInt,
+            time_delivered -> Nullable<BigInt>,
+            time_read -> Nullable<BigInt>,
+        }
+    }
+
+    diesel::table! {
+        message_link (id) {
+            id -> BigInt,
+            message_internal_id -> Nullable<BigInt>,
+            url -> Text,
+            domain -> Text,
         }
     }
 
@@ -117,6 +168,9 @@ pub mod schema {
     diesel::joinable!(message -> dataset (ds_uuid));
     diesel::joinable!(message_content -> message (message_internal_id));
     diesel::joinable!(message_text_element -> message (message_internal_id));
+    diesel::joinable!(message_reaction -> message (message_internal_id));
+    diesel::joinable!(message_receipt -> message (message_internal_id));
+    diesel::joinable!(message_link -> message (message_internal_id));
     diesel::joinable!(user -> dataset (ds_uuid));
 
     diesel::allow_tables_to_appear_in_same_query!(
@@ -126,6 +180,9 @@ pub mod schema {
         message,
         message_content,
         message_text_element,
+        message_reaction,
+        message_receipt,
+        message_link,
         refinery_schema_history,
         user,
     );
@@ -155,6 +212,9 @@ pub struct RawUser {
     pub username: Option<String>,
     pub phone_numbers: Option<String>,
     pub is_myself: i32,
+    pub emails: Option<String>,
+    pub bio: Option<String>,
+    pub img_path: Option<String>,
 }
 
 #[derive(Debug, PartialEq, QueryableByName, Insertable, AsChangeset)]
@@ -170,6 +230,8 @@ pub struct RawChat {
     pub img_path: Option<String>,
     pub msg_count: i32,
     pub main_chat_id: Option<i64>,
+    pub description: Option<String>,
+    pub time_created: Option<i64>,
 }
 
 // We cannot use #[diesel(belongs_to(...))] because Diesel doesn't support multi-column foreign keys.
@@ -179,11 +241,15 @@ pub struct RawChat {
 // #[diesel(belongs_to(RawUser, foreign_key = (ds_uuid, user_id)))]
 #[diesel(table_name = schema::chat_member)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(treat_none_as_null = true)]
 pub struct RawChatMember {
     pub ds_uuid: Vec<u8>,
     pub chat_id: i64,
     pub user_id: i64,
     pub order: i32,
+    pub role: Option<String>,
+    pub time_joined: Option<i64>,
+    pub time_left: Option<i64>,
 }
 
 #[derive(Debug, PartialEq, QueryableByName)]
@@ -223,6 +289,13 @@ pub struct RawMessage {
     pub forward_from_name: Option<String>,
     pub reply_to_message_id: Option<i64>,
     pub searchable_string: String,
+    pub delivery_status: Option<String>,
+    pub forward_from_id: Option<i64>,
+    pub forward_chat_name: Option<String>,
+    pub forward_time: Option<i64>,
+    pub forward_source_id: Option<i64>,
+    pub forward_author_name: Option<String>,
+    pub ttl_sec: Option<i32>,
 }
 
 #[derive(Debug, PartialEq, Default, Identifiable, Selectable, Queryable, Insertable, Associations)]
@@ -233,7 +306,10 @@ pub struct RawMessage {
 pub struct RawMessageContent {
     #[diesel(deserialize_as = i64)]
     pub id: Option<i64>,
-    pub message_internal_id: i64,
+    // Same as in RawRichTextElement
+    pub message_internal_id: Option<i64>,
+    /// Position among message contents, 0 is the main one
+    pub ordinal: i32,
 
     pub element_type: String,
 
@@ -259,6 +335,9 @@ pub struct RawMessageContent {
     pub pinned_message_id: Option<i64>,
     /// Boolean value
     pub is_blocked: Option<i32>,
+    pub url: Option<String>,
+    pub description: Option<String>,
+    pub site_name: Option<String>,
 }
 
 /// Needed specifically for selecting paths through sql_query.
@@ -270,6 +349,165 @@ pub struct PathsWrapper {
     pub thumbnail_path: Option<String>,
 }
 
+/// Needed specifically for selecting message IDs through sql_query.
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(table_name = schema::message)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct InternalIdWrapper {
+    pub internal_id: i64,
+}
+
+/// Needed specifically for selecting message IDs along with their chats through sql_query.
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(table_name = schema::message)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct MessageRefWrapper {
+    pub internal_id: i64,
+    pub ds_uuid: Vec<u8>,
+    pub chat_id: i64,
+}
+
+//
+// Statistics aggregation results
+//
+
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct RawStatisticsTotals {
+    #[diesel(sql_type = BigInt)]
+    pub messages_count: i64,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    pub first_time_sent: Option<i64>,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    pub last_time_sent: Option<i64>,
+}
+
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct RawIntCount {
+    #[diesel(sql_type = BigInt)]
+    pub key: i64,
+    #[diesel(sql_type = BigInt)]
+    pub count: i64,
+}
+
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct RawTextCount {
+    #[diesel(sql_type = Text)]
+    pub key: String,
+    #[diesel(sql_type = BigInt)]
+    pub count: i64,
+}
+
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct RawSumCount {
+    #[diesel(sql_type = BigInt)]
+    pub sum: i64,
+    #[diesel(sql_type = BigInt)]
+    pub count: i64,
+}
+
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct RawIntSumCount {
+    #[diesel(sql_type = BigInt)]
+    pub key: i64,
+    #[diesel(sql_type = BigInt)]
+    pub sum: i64,
+    #[diesel(sql_type = BigInt)]
+    pub count: i64,
+}
+
+/// Needed specifically for selecting searchable strings through sql_query.
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(table_name = schema::message)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SearchableStringWrapper {
+    pub internal_id: i64,
+    pub searchable_string: String,
+}
+
+/// Media content along with the details of its message.
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct RawMediaItem {
+    #[diesel(sql_type = Binary)]
+    pub ds_uuid: Vec<u8>,
+    #[diesel(sql_type = BigInt)]
+    pub chat_id: i64,
+    #[diesel(sql_type = BigInt)]
+    pub internal_id: i64,
+    #[diesel(sql_type = BigInt)]
+    pub time_sent: i64,
+    #[diesel(sql_type = BigInt)]
+    pub from_id: i64,
+    #[diesel(sql_type = Text)]
+    pub element_type: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub path: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub thumbnail_path: Option<String>,
+}
+
+/// Text element that might contain URLs.
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct RawLinkCandidate {
+    #[diesel(sql_type = BigInt)]
+    pub internal_id: i64,
+    #[diesel(sql_type = Text)]
+    pub element_type: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub text: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub href: Option<String>,
+}
+
+/// Extracted link along with the details of its message.
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct RawLinkItem {
+    #[diesel(sql_type = Binary)]
+    pub ds_uuid: Vec<u8>,
+    #[diesel(sql_type = BigInt)]
+    pub chat_id: i64,
+    #[diesel(sql_type = BigInt)]
+    pub internal_id: i64,
+    #[diesel(sql_type = BigInt)]
+    pub time_sent: i64,
+    #[diesel(sql_type = BigInt)]
+    pub from_id: i64,
+    #[diesel(sql_type = Text)]
+    pub url: String,
+    #[diesel(sql_type = Text)]
+    pub domain: String,
+}
+
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct RawDomainCount {
+    #[diesel(sql_type = Text)]
+    pub domain: String,
+    #[diesel(sql_type = BigInt)]
+    pub count: i64,
+}
+
+/// Result of a full-text search query, message itself is fetched separately.
+#[derive(Debug, PartialEq, QueryableByName)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct RawSearchHit {
+    #[diesel(sql_type = BigInt)]
+    pub internal_id: i64,
+    #[diesel(sql_type = Binary)]
+    pub ds_uuid: Vec<u8>,
+    #[diesel(sql_type = BigInt)]
+    pub chat_id: i64,
+    #[diesel(sql_type = Text)]
+    pub snippet: String,
+}
+
 #[derive(Debug, PartialEq, Identifiable, Selectable, Queryable, Insertable, Associations)]
 #[diesel(belongs_to(RawMessage, foreign_key = message_internal_id))]
 #[diesel(table_name = schema::message_text_element)]
@@ -286,10 +524,60 @@ pub struct RawRichTextElement {
     /// Boolean value
     pub hidden: Option<i32>,
     pub language: Option<String>,
+    pub user_id: Option<i64>,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Identifiable, Selectable, Queryable, Insertable, Associations)]
+#[diesel(belongs_to(RawMessage, foreign_key = message_internal_id))]
+#[diesel(table_name = schema::message_reaction)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(treat_none_as_null = true)]
+pub struct RawReaction {
+    #[diesel(deserialize_as = i64)]
+    pub id: Option<i64>,
+    // Same as in RawRichTextElement
+    pub message_internal_id: Option<i64>,
+    pub emoji: Option<String>,
+    pub custom_sticker_path: Option<String>,
+    pub from_id: i64,
+    pub time_sent: Option<i64>,
+}
+
+#[derive(Debug, PartialEq, Identifiable, Selectable, Queryable, Insertable, Associations)]
+#[diesel(belongs_to(RawMessage, foreign_key = message_internal_id))]
+#[diesel(table_name = schema::message_receipt)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(treat_none_as_null = true)]
+pub struct RawReceipt {
+    #[diesel(deserialize_as = i64)]
+    pub id: Option<i64>,
+    // Same as in RawRichTextElement
+    pub message_internal_id: Option<i64>,
+    pub user_id: i64,
+    pub time_delivered: Option<i64>,
+    pub time_read: Option<i64>,
+}
+
+#[derive(Debug, PartialEq, Identifiable, Selectable, Queryable, Insertable, Associations)]
+#[diesel(belongs_to(RawMessage, foreign_key = message_internal_id))]
+#[diesel(table_name = schema::message_link)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(treat_none_as_null = true)]
+pub struct RawLink {
+    #[diesel(deserialize_as = i64)]
+    pub id: Option<i64>,
+    // Same as in RawRichTextElement
+    pub message_internal_id: Option<i64>,
+    pub url: String,
+    pub domain: String,
 }
 
 pub struct FullRawMessage {
     pub m: RawMessage,
     pub mc: Option<RawMessageContent>,
+    pub extra_mcs: Vec<RawMessageContent>,
     pub rtes: Vec<RawRichTextElement>,
+    pub reactions: Vec<RawReaction>,
+    pub receipts: Vec<RawReceipt>,
 }
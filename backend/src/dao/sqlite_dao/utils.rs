@@ -76,6 +76,53 @@ impl_enum_serialization!(ChatType, {
     PrivateGroup => "private_group"
 });
 
+impl_enum_serialization!(ContentKind, {
+    Sticker       => "sticker",
+    Photo         => "photo",
+    VoiceMsg      => "voice_message",
+    Audio         => "audio",
+    VideoMsg      => "video_message",
+    Video         => "video",
+    File          => "file",
+    Location      => "location",
+    Poll          => "poll",
+    SharedContact => "shared_contact",
+    WebPage       => "web_page"
+});
+
+impl_enum_serialization!(ServiceKind, {
+    PhoneCall           => "phone_call",
+    SuggestProfilePhoto => "suggest_profile_photo",
+    PinMessage          => "pin_message",
+    ClearHistory        => "clear_history",
+    BlockUser           => "block_user",
+    StatusTextChanged   => "status_text_changed",
+    Notice              => "notice",
+    SetMessagesTtl      => "set_messages_ttl",
+    GroupCreate         => "group_create",
+    GroupEditTitle      => "group_edit_title",
+    GroupEditPhoto      => "group_edit_photo",
+    GroupDeletePhoto    => "group_delete_photo",
+    GroupInviteMembers  => "group_invite_members",
+    GroupRemoveMembers  => "group_remove_members",
+    GroupMigrateFrom    => "group_migrate_from",
+    GroupMigrateTo      => "group_migrate_to"
+});
+
+impl_enum_serialization!(ChatMemberRole, {
+    Member => "member",
+    Admin  => "admin",
+    Owner  => "owner"
+});
+
+impl_enum_serialization!(DeliveryStatus, {
+    Pending   => "pending",
+    Sent      => "sent",
+    Delivered => "delivered",
+    Read      => "read",
+    Failed    => "failed"
+});
+
 //
 // Per-entity serialization
 //
@@ -103,13 +150,18 @@ pub mod user {
     use super::*;
 
     pub fn deserialize(raw: RawUser) -> Result<(User, bool)> {
+        let mut phone_numbers = deserialize_arr(raw.phone_numbers).into_iter();
         Ok((User {
             ds_uuid: PbUuid { value: Uuid::from_slice(&raw.ds_uuid)?.to_string() },
             id: raw.id,
             first_name_option: raw.first_name,
             last_name_option: raw.last_name,
             username_option: raw.username,
-            phone_number_option: raw.phone_numbers,
+            phone_number_option: phone_numbers.next(),
+            extra_phone_numbers: phone_numbers.collect_vec(),
+            emails: deserialize_arr(raw.emails),
+            bio_option: raw.bio,
+            img_path_option: raw.img_path,
         }, deserialize_bool(raw.is_myself)))
     }
 
@@ -120,8 +172,14 @@ pub mod user {
             first_name: user.first_name_option.clone(),
             last_name: user.last_name_option.clone(),
             username: user.username_option.clone(),
-            phone_numbers: user.phone_number_option.clone(),
+            phone_numbers: serialize_arr(&user.phone_number_option.iter()
+                .chain(user.extra_phone_numbers.iter())
+                .cloned()
+                .collect_vec()),
             is_myself: serialize_bool(is_myself),
+            emails: serialize_arr(&user.emails),
+            bio: user.bio_option.clone(),
+            img_path: user.img_path_option.clone(),
         }
     }
 }
@@ -178,9 +236,33 @@ pub mod chat {
             img_path: chat.img_path_option.clone(),
             msg_count: chat.msg_count,
             main_chat_id: chat.main_chat_id,
+            description: chat.description_option.clone(),
+            time_created: chat.created_timestamp_option,
         })
     }
 
+    /// Member details are stored alongside members themselves.
+    pub fn serialize_members(chat: &Chat, raw_uuid: &[u8]) -> Result<Vec<RawChatMember>> {
+        let mut details_by_user_id: HashMap<i64, &ChatMemberDetails> =
+            chat.member_details.iter().map(|md| (md.user_id, md)).collect();
+        let result = chat.member_ids.iter().enumerate().map(|(order, &user_id)| {
+            let details = details_by_user_id.remove(&user_id);
+            ok(RawChatMember {
+                ds_uuid: raw_uuid.to_vec(),
+                chat_id: chat.id,
+                user_id,
+                order: order as i32,
+                role: details.and_then(|md| md.role_option).map(ChatMemberRole::serialize).transpose()?,
+                time_joined: details.and_then(|md| md.joined_timestamp_option),
+                time_left: details.and_then(|md| md.left_timestamp_option),
+            })
+        }).try_collect()?;
+        ensure!(details_by_user_id.is_empty(),
+                "Chat {} has details for users that aren't its members: {:?}",
+                chat.qualified_name(), details_by_user_id.keys().sorted().collect_vec());
+        Ok(result)
+    }
+
     pub fn deserialize(raw: RawChatQ,
                        conn: &mut SqliteConnection,
                        ds_uuid: &PbUuid,
@@ -208,14 +290,35 @@ pub mod chat {
                     .unwrap_or(Ok(vec![]))?,
                 msg_count: raw.chat.msg_count,
                 main_chat_id: raw.chat.main_chat_id,
+                description_option: raw.chat.description,
+                created_timestamp_option: raw.chat.time_created,
+                member_details: vec![] /* Will be set right next */,
             },
             last_msg_option,
             members: vec![] /* Will be set right next */,
         };
         cwd.members = resolve_users(&cache.users[ds_uuid], cwd.chat.member_ids())?;
+        cwd.chat.member_details = fetch_member_details(conn, &raw.chat.ds_uuid, cwd.chat.id)?;
         Ok(cwd)
     }
 
+    fn fetch_member_details(conn: &mut SqliteConnection, raw_ds_uuid: &[u8], id: i64) -> Result<Vec<ChatMemberDetails>> {
+        use schema::chat_member::columns::*;
+        let raw_members: Vec<RawChatMember> = schema::chat_member::table
+            .filter(ds_uuid.eq(raw_ds_uuid))
+            .filter(chat_id.eq(id))
+            .filter(role.is_not_null().or(time_joined.is_not_null()).or(time_left.is_not_null()))
+            .order_by(order)
+            .select(RawChatMember::as_select())
+            .load(conn)?;
+        raw_members.into_iter().map(|raw| ok(ChatMemberDetails {
+            user_id: raw.user_id,
+            role_option: raw.role.as_deref().map(ChatMemberRole::deserialize).transpose()?,
+            joined_timestamp_option: raw.time_joined,
+            left_timestamp_option: raw.time_left,
+        })).try_collect()
+    }
+
     fn resolve_users(cache: &UserCacheForDataset, user_ids: impl Iterator<Item=UserId>) -> Result<Vec<User>> {
         Ok(user_ids
             .map(|id|
@@ -251,15 +354,50 @@ pub mod message {
                 .select(RawRichTextElement::as_select())
                 .load(conn)?;
 
+        let raw_message_extra_mcs: Vec<RawMessageContent> =
+            RawMessageContent::belonging_to(&raw_messages)
+                .filter(schema::message_content::columns::ordinal.gt(0))
+                .select(RawMessageContent::as_select())
+                .load(conn)?;
+
+        let raw_message_reactions: Vec<RawReaction> =
+            RawReaction::belonging_to(&raw_messages)
+                .select(RawReaction::as_select())
+                .load(conn)?;
+
+        let raw_message_receipts: Vec<RawReceipt> =
+            RawReceipt::belonging_to(&raw_messages)
+                .select(RawReceipt::as_select())
+                .load(conn)?;
+
         let mut grouped = raw_message_rtes.grouped_by(&raw_messages);
         for group in grouped.iter_mut() {
             // TODO: This may be redundant
             group.sort_by_key(|rte| rte.id)
         }
 
+        let mut grouped_extra_mcs = raw_message_extra_mcs.grouped_by(&raw_messages);
+        for group in grouped_extra_mcs.iter_mut() {
+            group.sort_by_key(|mc| mc.ordinal)
+        }
+
+        let mut grouped_reactions = raw_message_reactions.grouped_by(&raw_messages);
+        for group in grouped_reactions.iter_mut() {
+            group.sort_by_key(|reaction| reaction.id)
+        }
+
+        let mut grouped_receipts = raw_message_receipts.grouped_by(&raw_messages);
+        for group in grouped_receipts.iter_mut() {
+            group.sort_by_key(|receipt| receipt.id)
+        }
+
         let messages: Vec<Message> = grouped.into_iter()
+            .zip(grouped_extra_mcs)
+            .zip(grouped_reactions)
+            .zip(grouped_receipts)
             .zip(raw_messages_with_content)
-            .map(|(rtes, (m, mc))| FullRawMessage { m, mc, rtes })
+            .map(|((((rtes, extra_mcs), reactions), receipts), (m, mc))|
+                FullRawMessage { m, mc, extra_mcs, rtes, reactions, receipts })
             .map(deserialize)
             .try_collect()?;
 
@@ -272,23 +410,32 @@ pub mod message {
                                     raw_uuid: &[u8],
                                     src_ds_root: &DatasetRoot,
                                     dst_ds_root: &DatasetRoot) -> Result<FullRawMessage> {
-        let (tpe, subtype, mc, time_edited, is_deleted, forward_from_name, reply_to_message_id) =
+        let (tpe, subtype, mc, extra_mcs, time_edited, is_deleted, forward_from_name, forward_origin, reply_to_message_id,
+            ttl_sec) =
             match m.typed.as_ref().unwrap() {
                 crate::message::Typed::Regular(mr) => {
-                    let content = mr.content_option.as_ref()
-                        .map(|mc| serialize_content_and_copy_files(mc.sealed_value_optional.as_ref().unwrap(),
-                                                                   chat_id, src_ds_root, dst_ds_root)).transpose()?;
+                    let serialize = |c: &Content|
+                        serialize_content_and_copy_files(c.sealed_value_optional.as_ref().unwrap(),
+                                                         chat_id, src_ds_root, dst_ds_root);
+                    let content = mr.content_option.as_ref().map(serialize).transpose()?;
+                    let extra_contents: Vec<RawMessageContent> = mr.extra_contents.iter()
+                        .enumerate()
+                        .map(|(idx, c)| serialize(c).map(|raw| RawMessageContent { ordinal: idx as i32 + 1, ..raw }))
+                        .try_collect()?;
                     ("regular",
                      None,
                      content,
+                     extra_contents,
                      mr.edit_timestamp_option,
                      serialize_bool(mr.is_deleted),
                      mr.forward_from_name_option.clone(),
-                     mr.reply_to_message_id_option)
+                     mr.forward_origin_option.clone().unwrap_or_default(),
+                     mr.reply_to_message_id_option,
+                     mr.ttl_sec_option)
                 }
                 message_service_pat!(ms) => {
                     let (subtype, mc) = serialize_service_and_copy_files(ms, chat_id, src_ds_root, dst_ds_root)?;
-                    ("service", Some(subtype), mc, None, serialize_bool(false), None, None)
+                    ("service", Some(subtype), mc, vec![], None, serialize_bool(false), None, Default::default(), None, None)
                 }
                 message_service_pat_unreachable!() => { unreachable!() }
             };
@@ -307,9 +454,46 @@ pub mod message {
                 forward_from_name,
                 reply_to_message_id,
                 searchable_string: m.searchable_string.clone(),
+                delivery_status: m.delivery_status_option.map(DeliveryStatus::serialize).transpose()?,
+                forward_from_id: forward_origin.from_id_option,
+                forward_chat_name: forward_origin.chat_name_option,
+                forward_time: forward_origin.timestamp_option,
+                forward_source_id: forward_origin.source_id_option,
+                forward_author_name: forward_origin.from_name_option,
+                ttl_sec,
             },
             mc,
-            rtes: m.text.iter().map(serialize_rte).try_collect()?,
+            extra_mcs,
+            rtes: m.text.iter()
+                .map(|rte| serialize_rte_and_copy_files(rte, chat_id, src_ds_root, dst_ds_root))
+                .try_collect()?,
+            reactions: m.reactions.iter()
+                .map(|r| serialize_reaction_and_copy_files(r, chat_id, src_ds_root, dst_ds_root))
+                .try_collect()?,
+            receipts: m.receipts.iter().map(|r| RawReceipt {
+                id: None,
+                message_internal_id: None, // Discarded
+                user_id: r.user_id,
+                time_delivered: r.delivered_timestamp_option,
+                time_read: r.read_timestamp_option,
+            }).collect_vec(),
+        })
+    }
+
+    fn serialize_reaction_and_copy_files(r: &Reaction,
+                                         chat_id: i64,
+                                         src_ds_root: &DatasetRoot,
+                                         dst_ds_root: &DatasetRoot) -> Result<RawReaction> {
+        let custom_sticker_path = r.custom_sticker_path_option.as_ref().map(|p|
+            sqlite_dao::copy_file(p, &None, &subpaths::STICKERS, chat_id, src_ds_root, dst_ds_root)
+        ).transpose()?.flatten();
+        Ok(RawReaction {
+            id: None,
+            message_internal_id: None, // Discarded
+            emoji: r.emoji_option.clone(),
+            custom_sticker_path,
+            from_id: r.from_id,
+            time_sent: r.timestamp_option,
         })
     }
 
@@ -433,6 +617,18 @@ pub mod message {
                     ..Default::default()
                 }
             }
+            WebPage(v) => {
+                let path = copy_path!(v.image_path_option, &None, &subpaths::PHOTOS);
+                RawMessageContent {
+                    element_type: "web_page".to_owned(),
+                    path,
+                    url: Some(v.url.clone()),
+                    title: v.title_option.clone(),
+                    description: v.description_option.clone(),
+                    site_name: v.site_name_option.clone(),
+                    ..Default::default()
+                }
+            }
         })
     }
 
@@ -486,6 +682,11 @@ pub mod message {
                 ("status_text_changed", None),
             Notice(_) =>
                 ("notice", None),
+            SetMessagesTtl(v) =>
+                ("set_messages_ttl", Some(RawMessageContent {
+                    duration_sec: v.ttl_sec_option,
+                    ..Default::default()
+                })),
             GroupCreate(v) =>
                 ("group_create", Some(RawMessageContent {
                     title: Some(v.title.clone()),
@@ -530,9 +731,12 @@ pub mod message {
 
 
     /// Ignores message internal ID.
-    fn serialize_rte(rte: &RichTextElement) -> Result<RawRichTextElement> {
+    fn serialize_rte_and_copy_files(rte: &RichTextElement,
+                                    chat_id: i64,
+                                    src_ds_root: &DatasetRoot,
+                                    dst_ds_root: &DatasetRoot) -> Result<RawRichTextElement> {
         use rich_text_element::Val::*;
-        let (mut language, mut hidden, mut href) = (None, None, None);
+        let (mut language, mut hidden, mut href, mut user_id, mut path) = (None, None, None, None, None);
         let (text, tpe): (Option<String>, &str) = match rte.val.as_ref().unwrap() {
             Plain(v) =>
                 (Some(v.text.clone()), "plain"),
@@ -559,6 +763,28 @@ pub mod message {
                 (Some(v.text.clone()), "blockquote"),
             Spoiler(v) =>
                 (Some(v.text.clone()), "spoiler"),
+            Mention(v) => {
+                user_id = v.user_id_option;
+                (Some(v.text.clone()), "mention")
+            }
+            CustomEmoji(v) => {
+                path = v.path_option.as_ref().map(|p|
+                    sqlite_dao::copy_file(p, &None, &subpaths::STICKERS, chat_id, src_ds_root, dst_ds_root)
+                ).transpose()?.flatten();
+                (Some(v.text.clone()), "custom_emoji")
+            }
+            Hashtag(v) =>
+                (Some(v.text.clone()), "hashtag"),
+            Cashtag(v) =>
+                (Some(v.text.clone()), "cashtag"),
+            BotCommand(v) =>
+                (Some(v.text.clone()), "bot_command"),
+            Email(v) =>
+                (Some(v.text.clone()), "email"),
+            Phone(v) =>
+                (Some(v.text.clone()), "phone"),
+            BankCard(v) =>
+                (Some(v.text.clone()), "bank_card"),
         };
         Ok(RawRichTextElement {
             id: None,
@@ -568,6 +794,8 @@ pub mod message {
             href,
             hidden,
             language,
+            user_id,
+            path,
         })
     }
 
@@ -582,20 +810,44 @@ pub mod message {
                 content_option: raw.mc.map(|mc| ok(Content {
                     sealed_value_optional: Some(deserialize_content(mc)?)
                 })).transpose()?,
+                extra_contents: raw.extra_mcs.into_iter().map(|mc| ok(Content {
+                    sealed_value_optional: Some(deserialize_content(mc)?)
+                })).try_collect()?,
+                forward_origin_option: Some(ForwardOrigin {
+                    from_id_option: raw.m.forward_from_id,
+                    chat_name_option: raw.m.forward_chat_name,
+                    timestamp_option: raw.m.forward_time,
+                    source_id_option: raw.m.forward_source_id,
+                    from_name_option: raw.m.forward_author_name,
+                }).filter(|fo| fo != &ForwardOrigin::default()),
+                ttl_sec_option: raw.m.ttl_sec,
             },
             "service" => message_service!(deserialize_service(
                     raw.m.subtype.as_deref().expect("Service message subtype is empty!"),
                     raw.mc)?),
             tpe => bail!("Unknown message type {}!", tpe)
         };
-        Ok(Message::new(
+        let mut result = Message::new(
             raw.m.internal_id.expect("Message has no internal ID!"),
             raw.m.source_id,
             raw.m.time_sent,
             UserId(raw.m.from_id),
             text,
             typed,
-        ))
+        );
+        result.reactions = raw.reactions.into_iter().map(|r| Reaction {
+            emoji_option: r.emoji,
+            custom_sticker_path_option: r.custom_sticker_path,
+            from_id: r.from_id,
+            timestamp_option: r.time_sent,
+        }).collect_vec();
+        result.delivery_status_option = raw.m.delivery_status.as_deref().map(DeliveryStatus::deserialize).transpose()?;
+        result.receipts = raw.receipts.into_iter().map(|r| Receipt {
+            user_id: r.user_id,
+            delivered_timestamp_option: r.time_delivered,
+            read_timestamp_option: r.time_read,
+        }).collect_vec();
+        Ok(result)
     }
 
     fn deserialize_content(raw: RawMessageContent) -> Result<content::SealedValueOptional> {
@@ -669,6 +921,13 @@ pub mod message {
                 phone_number_option: raw.phone_number,
                 vcard_path_option: raw.path,
             }),
+            "web_page" => WebPage(ContentWebPage {
+                url: get_or_bail!(raw.url),
+                title_option: raw.title,
+                description_option: raw.description,
+                site_name_option: raw.site_name,
+                image_path_option: raw.path,
+            }),
             tpe => bail!("Unknown content type {}!", tpe)
         })
     }
@@ -731,6 +990,12 @@ pub mod message {
                 StatusTextChanged(MessageServiceStatusTextChanged {}),
             "notice" =>
                 Notice(MessageServiceNotice {}),
+            "set_messages_ttl" => {
+                let raw = raw_or_bail!();
+                SetMessagesTtl(MessageServiceSetMessagesTtl {
+                    ttl_sec_option: raw.duration_sec,
+                })
+            }
             "group_create" => {
                 let raw = raw_or_bail!();
                 GroupCreate(MessageServiceGroupCreate {
@@ -794,6 +1059,14 @@ pub mod message {
             "prefmt_block" => RichText::make_prefmt_block(text_or_bail!(), raw.language),
             "blockquote" => RichText::make_blockquote(text_or_bail!()),
             "spoiler" => RichText::make_spoiler(text_or_bail!()),
+            "mention" => RichText::make_mention(raw.user_id, text_or_bail!()),
+            "custom_emoji" => RichText::make_custom_emoji(text_or_bail!(), raw.path),
+            "hashtag" => RichText::make_hashtag(text_or_bail!()),
+            "cashtag" => RichText::make_cashtag(text_or_bail!()),
+            "bot_command" => RichText::make_bot_command(text_or_bail!()),
+            "email" => RichText::make_email(text_or_bail!()),
+            "phone" => RichText::make_phone(text_or_bail!()),
+            "bank_card" => RichText::make_bank_card(text_or_bail!()),
             x => bail!("Unknown rich text element {x}!")
         })
     }
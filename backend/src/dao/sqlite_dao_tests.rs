@@ -318,7 +318,7 @@ fn inserts() -> EmptyRes {
     dst_dao.insert_dataset(src_dao.dataset())?;
     for u in src_dao.users_single_ds() {
         let is_myself = u.id == src_dao.myself_single_ds().id;
-        dst_dao.insert_user(u, is_myself)?;
+        dst_dao.insert_user(u, is_myself, &src_ds_root)?;
     }
     assert_eq!(dst_dao.datasets()?, src_dao.datasets()?);
     assert_eq!(dst_dao.users(ds_uuid)?, src_dao.users(ds_uuid)?);
@@ -359,12 +359,734 @@ fn inserts() -> EmptyRes {
     Ok(())
 }
 
+#[test]
+fn albums() -> EmptyRes {
+    fn photo(ds_root: &DatasetRoot) -> Content {
+        let file = create_random_file(&ds_root.0);
+        Content {
+            sealed_value_optional: Some(content::SealedValueOptional::Photo(ContentPhoto {
+                path_option: Some(ds_root.to_relative(&file).unwrap()),
+                width: 100,
+                height: 200,
+                is_one_time: false,
+            }))
+        }
+    }
+    let dao_holder = create_simple_dao(
+        false,
+        "test",
+        (1..=3).map(|idx| create_regular_message(idx, 1)).collect_vec(),
+        2,
+        &|_, ds_root, m| {
+            if let Typed::Regular(mr) = m.typed_mut() {
+                mr.content_option = Some(photo(ds_root));
+                mr.extra_contents = vec![photo(ds_root), photo(ds_root)];
+            }
+        });
+    let daos = init_from(dao_holder.dao,
+                         dao_holder.tmp_dir.path.clone(),
+                         Some(dao_holder.tmp_dir));
+
+    let src_files = dataset_files(daos.src_dao.as_ref(), &daos.ds_uuid);
+    let dst_files = dataset_files(&daos.dst_dao, &daos.ds_uuid);
+    assert_files(&src_files, &dst_files);
+
+    for (src_cwd, dst_cwd) in daos.src_dao.chats(&daos.ds_uuid)?.iter().zip(daos.dst_dao.chats(&daos.ds_uuid)?.iter()) {
+        let src_msgs = daos.src_dao.first_messages(&src_cwd.chat, usize::MAX)?;
+        let dst_msgs = daos.dst_dao.first_messages(&dst_cwd.chat, usize::MAX)?;
+        assert_eq!(dst_msgs.len(), src_msgs.len());
+        for (dst_msg, src_msg) in dst_msgs.iter().zip(src_msgs.iter()) {
+            assert_eq!(dst_msg.files_relative().len(), 3);
+            let dst_pet = Tup::new(dst_msg, &daos.dst_ds_root, dst_cwd);
+            let src_pet = Tup::new(src_msg, &daos.src_ds_root, src_cwd);
+            assert!(dst_pet.practically_equals(&src_pet)?);
+        }
+        assert_eq!(daos.dst_dao.last_messages(&dst_cwd.chat, 1)?.as_slice(), &dst_msgs[dst_msgs.len() - 1..]);
+    }
+    Ok(())
+}
+
+#[test]
+fn rich_text_entities() -> EmptyRes {
+    let dao_holder = create_simple_dao(
+        false,
+        "test",
+        (1..=3).map(|idx| create_regular_message(idx, 1)).collect_vec(),
+        2,
+        &|_, ds_root, m| {
+            let file = create_random_file(&ds_root.0);
+            m.text = vec![
+                RichText::make_hashtag("#tag".to_owned()),
+                RichText::make_cashtag("$USD".to_owned()),
+                RichText::make_bot_command("/start".to_owned()),
+                RichText::make_email("me@example.com".to_owned()),
+                RichText::make_phone("+123".to_owned()),
+                RichText::make_bank_card("4111 1111 1111 1111".to_owned()),
+                RichText::make_custom_emoji("😁".to_owned(), Some(ds_root.to_relative(&file).unwrap())),
+            ];
+        });
+    let daos = init_from(dao_holder.dao,
+                         dao_holder.tmp_dir.path.clone(),
+                         Some(dao_holder.tmp_dir));
+
+    let src_files = dataset_files(daos.src_dao.as_ref(), &daos.ds_uuid);
+    let dst_files = dataset_files(&daos.dst_dao, &daos.ds_uuid);
+    assert_files(&src_files, &dst_files);
+
+    for (src_cwd, dst_cwd) in daos.src_dao.chats(&daos.ds_uuid)?.iter().zip(daos.dst_dao.chats(&daos.ds_uuid)?.iter()) {
+        let src_msgs = daos.src_dao.first_messages(&src_cwd.chat, usize::MAX)?;
+        let dst_msgs = daos.dst_dao.first_messages(&dst_cwd.chat, usize::MAX)?;
+        assert_eq!(dst_msgs.len(), src_msgs.len());
+        for (dst_msg, src_msg) in dst_msgs.iter().zip(src_msgs.iter()) {
+            assert_eq!(dst_msg.text.len(), 7);
+            assert_eq!(dst_msg.files_relative().len(), 1);
+            let dst_pet = Tup::new(dst_msg, &daos.dst_ds_root, dst_cwd);
+            let src_pet = Tup::new(src_msg, &daos.src_ds_root, src_cwd);
+            assert!(dst_pet.practically_equals(&src_pet)?);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn delivery_status_and_receipts() -> EmptyRes {
+    let dao_holder = create_simple_dao(
+        false,
+        "test",
+        (1..=3).map(|idx| create_regular_message(idx, 1)).collect_vec(),
+        2,
+        &|_, _, m| {
+            m.delivery_status_option = Some(DeliveryStatus::Read as i32);
+            m.receipts = vec![Receipt {
+                user_id: 2,
+                delivered_timestamp_option: Some(m.timestamp + 10),
+                read_timestamp_option: if m.internal_id % 2 == 0 { Some(m.timestamp + 20) } else { None },
+            }];
+        });
+    let daos = init_from(dao_holder.dao,
+                         dao_holder.tmp_dir.path.clone(),
+                         Some(dao_holder.tmp_dir));
+
+    for (src_cwd, dst_cwd) in daos.src_dao.chats(&daos.ds_uuid)?.iter().zip(daos.dst_dao.chats(&daos.ds_uuid)?.iter()) {
+        let src_msgs = daos.src_dao.first_messages(&src_cwd.chat, usize::MAX)?;
+        let dst_msgs = daos.dst_dao.first_messages(&dst_cwd.chat, usize::MAX)?;
+        assert_eq!(dst_msgs.len(), src_msgs.len());
+        for (dst_msg, src_msg) in dst_msgs.iter().zip(src_msgs.iter()) {
+            assert_eq!(dst_msg.delivery_status_option, Some(DeliveryStatus::Read as i32));
+            assert_eq!(dst_msg.receipts, src_msg.receipts);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn forward_origin() -> EmptyRes {
+    let dao_holder = create_simple_dao(
+        false,
+        "test",
+        (1..=3).map(|idx| create_regular_message(idx, 1)).collect_vec(),
+        2,
+        &|_, _, m| {
+            let source_id = m.source_id_option;
+            let timestamp = m.timestamp;
+            let message_regular_pat! { forward_origin_option, .. } = m.typed_mut() else { unreachable!() };
+            *forward_origin_option = match source_id {
+                Some(1) => None,
+                _ => Some(ForwardOrigin {
+                    from_id_option: Some(2),
+                    chat_name_option: Some("Original Chat".to_owned()),
+                    timestamp_option: Some(timestamp - 100),
+                    source_id_option: source_id.map(|id| id + 1000),
+                    from_name_option: Some("Original Author".to_owned()),
+                }),
+            };
+        });
+    let daos = init_from(dao_holder.dao,
+                         dao_holder.tmp_dir.path.clone(),
+                         Some(dao_holder.tmp_dir));
+
+    for (src_cwd, dst_cwd) in daos.src_dao.chats(&daos.ds_uuid)?.iter().zip(daos.dst_dao.chats(&daos.ds_uuid)?.iter()) {
+        let src_msgs = daos.src_dao.first_messages(&src_cwd.chat, usize::MAX)?;
+        let dst_msgs = daos.dst_dao.first_messages(&dst_cwd.chat, usize::MAX)?;
+        assert_eq!(dst_msgs.len(), src_msgs.len());
+        for (dst_msg, src_msg) in dst_msgs.iter().zip(src_msgs.iter()) {
+            let message_regular_pat! { forward_origin_option: src_fo, .. } = src_msg.typed() else { unreachable!() };
+            let message_regular_pat! { forward_origin_option: dst_fo, .. } = dst_msg.typed() else { unreachable!() };
+            assert_eq!(dst_fo, src_fo);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn chat_details() -> EmptyRes {
+    let (mut dao, ds, no_ds_root, _tmp_dirs) = create_sqlite_dao_with_users(4)?;
+
+    let created_ts = base_ts();
+    let group_chat = Chat {
+        description_option: Some("Group description".to_owned()),
+        created_timestamp_option: Some(created_ts),
+        member_details: vec![
+            ChatMemberDetails {
+                user_id: 1,
+                role_option: Some(ChatMemberRole::Owner as i32),
+                joined_timestamp_option: Some(created_ts),
+                left_timestamp_option: None,
+            },
+            ChatMemberDetails {
+                user_id: 3,
+                role_option: Some(ChatMemberRole::Admin as i32),
+                joined_timestamp_option: Some(created_ts + 10),
+                left_timestamp_option: Some(created_ts + 20),
+            },
+        ],
+        ..create_group_chat(&ds.uuid, 1, "Group", vec![1, 2, 3], 0)
+    };
+    dao.insert_chat(group_chat.clone(), &no_ds_root)?;
+    assert_eq!(dao.chat_option(&ds.uuid, group_chat.id)?.map(|cwd| cwd.chat), Some(group_chat.clone()));
+
+    // Details for non-members are rejected
+    let other_chat = Chat {
+        member_details: vec![ChatMemberDetails { user_id: 4, ..Default::default() }],
+        ..create_group_chat(&ds.uuid, 2, "Other Group", vec![1, 2], 0)
+    };
+    assert!(dao.insert_chat(other_chat, &no_ds_root).is_err());
+
+    dao.as_shiftable()?.shift_dataset_time(&ds.uuid, 1)?;
+    let shifted_chat = dao.chat_option(&ds.uuid, group_chat.id)?.unwrap().chat;
+    assert_eq!(shifted_chat.created_timestamp_option, Some(created_ts + 3600));
+    assert_eq!(shifted_chat.member_details[1].joined_timestamp_option, Some(created_ts + 3610));
+    assert_eq!(shifted_chat.member_details[1].left_timestamp_option, Some(created_ts + 3620));
+
+    Ok(())
+}
+
+#[test]
+fn user_profile() -> EmptyRes {
+    let (mut dao, _tmp_dir) = create_sqlite_dao();
+
+    let src_ds_tmp_dir = TmpDir::new();
+    let src_ds_root = DatasetRoot(src_ds_tmp_dir.path.clone());
+    fs::write(src_ds_root.to_absolute("avatar.jpg"), b"not really a jpg")?;
+
+    let ds = dao.insert_dataset(Dataset { uuid: ZERO_PB_UUID.clone(), alias: "My Dataset".to_owned() })?;
+    let user = User {
+        phone_number_option: Some("+111".to_owned()),
+        extra_phone_numbers: vec!["+222".to_owned(), "+333".to_owned()],
+        emails: vec!["user1@example.com".to_owned()],
+        bio_option: Some("Just a user".to_owned()),
+        img_path_option: Some("avatar.jpg".to_owned()),
+        ..create_user(&ds.uuid, 1)
+    };
+    let inserted_user = dao.insert_user(user.clone(), true, &src_ds_root)?;
+
+    // Avatar is copied into dataset root
+    let img_path = inserted_user.img_path_option.clone().unwrap();
+    assert!(img_path.starts_with("users/avatars/"));
+    let dst_ds_root = dao.dataset_root(&ds.uuid)?;
+    assert_eq!(fs::read(dst_ds_root.to_absolute(&img_path))?, b"not really a jpg");
+
+    assert_eq!(inserted_user, User { img_path_option: Some(img_path), ..user });
+    assert_eq!(dao.myself(&ds.uuid)?, inserted_user);
+
+    Ok(())
+}
+
+#[test]
+fn filter_messages() -> EmptyRes {
+    let (mut dao, ds, no_ds_root, _tmp_dirs) = create_sqlite_dao_with_users(2)?;
+
+    let base_ts = base_ts();
+    let voice_msg = Content {
+        sealed_value_optional: Some(content::SealedValueOptional::VoiceMsg(ContentVoiceMsg {
+            path_option: None,
+            mime_type: "audio/ogg".to_owned(),
+            duration_sec_option: Some(5),
+        }))
+    };
+    let msg = |id: i64, from_id: i64, typed: message::Typed|
+        make_message(id, id, from_id, vec![RichText::make_plain(format!("Message {id}"))], typed);
+    let make_regular = |id: i64, from_id: i64, mr: MessageRegular| msg(id, from_id, message::Typed::Regular(mr));
+    let no_content = no_content_regular();
+
+    let msgs = vec![
+        make_regular(1, 1, no_content.clone()),
+        make_regular(2, 2, MessageRegular {
+            reply_to_message_id_option: Some(1),
+            content_option: Some(voice_msg.clone()),
+            ..no_content.clone()
+        }),
+        make_regular(3, 1, MessageRegular {
+            edit_timestamp_option: Some(base_ts + 100),
+            content_option: Some(Content {
+                sealed_value_optional: Some(content::SealedValueOptional::Location(ContentLocation {
+                    title_option: None,
+                    address_option: None,
+                    lat_str: "1.0".to_owned(),
+                    lon_str: "2.0".to_owned(),
+                    duration_sec_option: None,
+                }))
+            }),
+            ..no_content.clone()
+        }),
+        make_regular(4, 2, MessageRegular { is_deleted: true, ..no_content.clone() }),
+        msg(5, 1, message_service!(message_service::SealedValueOptional::PhoneCall(MessageServicePhoneCall {
+            duration_sec_option: Some(60),
+            discard_reason_option: None,
+            members: vec![],
+        }))),
+        make_regular(6, 2, MessageRegular {
+            content_option: Some(Content {
+                sealed_value_optional: Some(content::SealedValueOptional::Photo(ContentPhoto {
+                    path_option: None,
+                    width: 100,
+                    height: 100,
+                    is_one_time: false,
+                }))
+            }),
+            extra_contents: vec![voice_msg.clone()],
+            ..no_content.clone()
+        }),
+    ];
+    let chat = dao.insert_chat(create_group_chat(&ds.uuid, 1, "One", vec![1, 2], msgs.len()), &no_ds_root)?;
+    dao.insert_messages(msgs.clone(), &chat, &no_ds_root)?;
+
+    let assert_filtered = |filter: MessageFilter, expected_ids: Vec<i64>| -> EmptyRes {
+        let source_ids = |msgs: Vec<Message>| msgs.into_iter().map(|m| m.source_id_option.unwrap()).collect_vec();
+        assert_eq!(source_ids(dao.filter_messages(&chat, &filter, 0, 100)?), expected_ids);
+        // In-memory matching should agree with SQL
+        assert_eq!(source_ids(msgs.iter().filter(|m| filter.matches(m)).cloned().collect_vec()), expected_ids);
+        Ok(())
+    };
+
+    assert_filtered(MessageFilter::default(), vec![1, 2, 3, 4, 5, 6])?;
+    assert_filtered(MessageFilter { from_ids: vec![2], ..Default::default() }, vec![2, 4, 6])?;
+    assert_filtered(MessageFilter {
+        timestamp_from_option: Some(base_ts + 2),
+        timestamp_to_option: Some(base_ts + 5),
+        ..Default::default()
+    }, vec![2, 3, 4])?;
+    assert_filtered(MessageFilter { content_kinds: vec![ContentKind::VoiceMsg as i32], ..Default::default() }, vec![2, 6])?;
+    assert_filtered(MessageFilter { service_kinds: vec![ServiceKind::PhoneCall as i32], ..Default::default() }, vec![5])?;
+    assert_filtered(MessageFilter {
+        content_kinds: vec![ContentKind::Location as i32],
+        service_kinds: vec![ServiceKind::PhoneCall as i32],
+        ..Default::default()
+    }, vec![3, 5])?;
+    assert_filtered(MessageFilter { has_reply_option: Some(true), ..Default::default() }, vec![2])?;
+    assert_filtered(MessageFilter { is_edited_option: Some(true), ..Default::default() }, vec![3])?;
+    assert_filtered(MessageFilter { is_deleted_option: Some(true), ..Default::default() }, vec![4])?;
+    assert_filtered(MessageFilter { from_ids: vec![2], is_deleted_option: Some(false), ..Default::default() }, vec![2, 6])?;
+    assert_filtered(MessageFilter { from_ids: vec![3], ..Default::default() }, vec![])?;
+
+    // Paging
+    assert_eq!(dao.filter_messages(&chat, &MessageFilter::default(), 1, 2)?, dao.scroll_messages(&chat, 1, 2)?);
+    assert_eq!(dao.filter_messages(&chat, &MessageFilter { from_ids: vec![2], ..Default::default() }, 1, 1)?
+                   .iter().map(|m| m.source_id_option).collect_vec(), vec![Some(4)]);
+
+    Ok(())
+}
+
+#[test]
+fn media_items() -> EmptyRes {
+    let daos = init();
+    let src_dao = daos.src_dao.as_ref();
+    let dst_dao = &daos.dst_dao;
+
+    let mut total_items = 0;
+    for cwd in src_dao.chats(&daos.ds_uuid)? {
+        let scope = SearchScope::Chat(&cwd.chat);
+        let src_items = src_dao.media_items(scope, &[], 0, usize::MAX)?;
+        let dst_items = dst_dao.media_items(scope, &[], 0, usize::MAX)?;
+        assert_same_essence(&dst_items, &src_items);
+        total_items += dst_items.len();
+
+        for item in dst_items.iter() {
+            let msg = dst_dao.messages_slice(&cwd.chat, MessageInternalId(item.message_internal_id),
+                                             MessageInternalId(item.message_internal_id))?.remove(0);
+            assert_eq!(item.timestamp, msg.timestamp);
+            assert!(item.path_option.iter().all(|p| msg.files_relative().contains(&p.as_str())));
+            if item.file_exists {
+                assert!(daos.dst_ds_root.to_absolute(item.path_option.as_ref().unwrap()).exists());
+            }
+        }
+
+        // Latest go first
+        assert!(dst_items.iter().tuple_windows()
+            .all(|(i1, i2)| (i1.timestamp, i1.message_internal_id) >= (i2.timestamp, i2.message_internal_id)));
+
+        let photos = dst_dao.media_items(scope, &[ContentKind::Photo], 0, usize::MAX)?;
+        assert!(photos.iter().all(|i| i.kind == ContentKind::Photo as i32));
+        assert_eq!(photos.len(), dst_items.iter().filter(|i| i.kind == ContentKind::Photo as i32).count());
+
+        // Paging
+        assert_eq!(dst_dao.media_items(scope, &[], 1, 2)?, dst_items.iter().skip(1).take(2).cloned().collect_vec());
+        assert_same_essence(&src_dao.media_items(scope, &[], 1, 2)?, &dst_items.iter().skip(1).take(2).cloned().collect_vec());
+    }
+    assert!(total_items > 0);
+
+    // Across chats, latest go first too, ties are broken by chat
+    let scope = SearchScope::Dataset(&daos.ds_uuid);
+    let ds_items = dst_dao.media_items(scope, &[], 0, usize::MAX)?;
+    assert_eq!(ds_items.len(), total_items);
+    assert_same_essence(&ds_items, &src_dao.media_items(scope, &[], 0, usize::MAX)?);
+    assert!(ds_items.iter().tuple_windows()
+        .all(|(i1, i2)| (i1.timestamp, i1.chat_id) >= (i2.timestamp, i2.chat_id)));
+    assert!(ds_items.iter().map(|i| i.chat_id).unique().count() > 1);
+    assert_eq!(dst_dao.media_items(scope, &[], 1, 2)?, ds_items[1..3].to_vec());
+    assert_eq!(dst_dao.media_items(SearchScope::Everywhere, &[], 0, usize::MAX)?, ds_items);
+
+    assert!(dst_dao.media_items(SearchScope::Everywhere, &[ContentKind::Poll], 0, usize::MAX).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn timeline() -> EmptyRes {
+    let daos = init();
+    let src_dao = daos.src_dao.as_ref();
+    let dst_dao = &daos.dst_dao;
+
+    let scope = SearchScope::Dataset(&daos.ds_uuid);
+    let all = dst_dao.timeline(scope, None, None, None, usize::MAX)?;
+    assert_same_essence(&all, &src_dao.timeline(scope, None, None, None, usize::MAX)?);
+    assert_eq!(all, dst_dao.timeline(SearchScope::Everywhere, None, None, None, usize::MAX)?);
+    assert!(all.iter().tuple_windows().all(|(e1, e2)| e1.message.timestamp <= e2.message.timestamp));
+
+    let mut total_messages = 0;
+    for cwd in dst_dao.chats(&daos.ds_uuid)? {
+        let chat_msgs = dst_dao.scroll_messages(&cwd.chat, 0, usize::MAX)?;
+        total_messages += chat_msgs.len();
+
+        // Within a chat, messages keep their order
+        let chat_entries = all.iter().filter(|e| e.chat_id == cwd.chat.id).map(|e| e.message.clone()).collect_vec();
+        assert_eq!(chat_entries, chat_msgs);
+
+        let scope = SearchScope::Chat(&cwd.chat);
+        assert_eq!(dst_dao.timeline(scope, None, None, None, usize::MAX)?.into_iter().map(|e| e.message).collect_vec(),
+                   chat_msgs);
+    }
+    assert_eq!(all.len(), total_messages);
+    assert!(total_messages > 0);
+
+    // Range is inclusive on the left and exclusive on the right
+    let (first_ts, last_ts) = (all.first().unwrap().message.timestamp, all.last().unwrap().message.timestamp);
+    let ranged = dst_dao.timeline(scope, Some(first_ts + 1), Some(last_ts), None, usize::MAX)?;
+    assert_eq!(ranged, all.iter()
+        .filter(|e| e.message.timestamp > first_ts && e.message.timestamp < last_ts)
+        .cloned().collect_vec());
+    assert_same_essence(&ranged, &src_dao.timeline(scope, Some(first_ts + 1), Some(last_ts), None, usize::MAX)?);
+
+    // Paging
+    let after = all[0].position();
+    assert_eq!(dst_dao.timeline(scope, None, None, Some(&after), 2)?, all[1..3].to_vec());
+    assert_eq!(dst_dao.timeline(SearchScope::Everywhere, None, None, Some(&after), 2)?, all[1..3].to_vec());
+    let src_all = src_dao.timeline(scope, None, None, None, usize::MAX)?;
+    assert_same_essence(&src_dao.timeline(scope, None, None, Some(&src_all[0].position()), 2)?, &all[1..3]);
+    assert_eq!(dst_dao.timeline(scope, Some(last_ts + 1), None, None, 10)?, vec![]);
+    assert_eq!(dst_dao.timeline(scope, None, None, Some(&all.last().unwrap().position()), 10)?, vec![]);
+
+    // Going page by page yields everything, including messages sharing a timestamp
+    let mut paged = vec![];
+    loop {
+        let page = dst_dao.timeline(scope, None, None, paged.last().map(TimelineEntry::position).as_ref(), 3)?;
+        if page.is_empty() { break; }
+        paged.extend(page);
+    }
+    assert_eq!(paged, all);
+
+    Ok(())
+}
+
+#[test]
+fn statistics() -> EmptyRes {
+    let daos = init();
+    let src_dao = daos.src_dao.as_ref();
+    let dst_dao = &daos.dst_dao;
+
+    // SQL aggregation should yield the same numbers as a generic implementation
+    for cwd in src_dao.chats(&daos.ds_uuid)? {
+        let scope = SearchScope::Chat(&cwd.chat);
+        assert_eq!(dst_dao.statistics(scope, 20)?, src_dao.statistics(scope, 20)?);
+    }
+    let scope = SearchScope::Dataset(&daos.ds_uuid);
+    let stats = dst_dao.statistics(scope, 20)?;
+    assert_eq!(stats, src_dao.statistics(scope, 20)?);
+    assert!(stats.messages_count > 0);
+    assert_eq!(stats.per_day.iter().map(|d| d.count).sum::<i64>(), stats.messages_count);
+    assert_eq!(stats.per_hour.iter().sum::<i64>(), stats.messages_count);
+    assert_eq!(stats.per_weekday.iter().sum::<i64>(), stats.messages_count);
+    assert!(stats.top_words.len() <= 20);
+
+    assert!(dst_dao.statistics(SearchScope::Everywhere, 20).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn statistics_details() -> EmptyRes {
+    let (mut dao, ds, no_ds_root, _tmp_dirs) = create_sqlite_dao_with_users(2)?;
+
+    let base_ts = base_ts();
+    let msg = |id: i64, ts_offset: i64, from_id: i64, text: &str, typed: message::Typed|
+        make_message(id, ts_offset, from_id, vec![RichText::make_plain(text.to_owned())], typed);
+    let photo = message::Typed::Regular(MessageRegular {
+        content_option: Some(Content {
+            sealed_value_optional: Some(content::SealedValueOptional::Photo(ContentPhoto {
+                path_option: None,
+                width: 100,
+                height: 100,
+                is_one_time: false,
+            }))
+        }),
+        ..no_content_regular()
+    });
+
+    let chat = dao.insert_chat(create_group_chat(&ds.uuid, 1, "One", vec![1, 2], 6), &no_ds_root)?;
+    dao.insert_messages(vec![
+        msg(1, 0, 1, "Hello there", MESSAGE_REGULAR_NO_CONTENT.clone()),
+        msg(2, 30, 2, "Hello, hello!", photo.clone()),
+        msg(3, 40, 2, "Anyone?", MESSAGE_REGULAR_NO_CONTENT.clone()),
+        msg(4, 50, 1, "", message_service!(message_service::SealedValueOptional::PhoneCall(MessageServicePhoneCall {
+            duration_sec_option: Some(120),
+            discard_reason_option: None,
+            members: vec![],
+        }))),
+        // Service messages are not taken into account when measuring response time
+        msg(5, 100, 1, "Yes, hello", MESSAGE_REGULAR_NO_CONTENT.clone()),
+        // Too late to be a response
+        msg(6, 100 + 2 * 24 * 60 * 60, 2, "Hi", MESSAGE_REGULAR_NO_CONTENT.clone()),
+    ], &chat, &no_ds_root)?;
+
+    let stats = dao.statistics(SearchScope::Chat(&chat), 2)?;
+    assert_eq!(stats.messages_count, 6);
+    assert_eq!(stats.first_message_timestamp_option, Some(base_ts));
+    assert_eq!(stats.last_message_timestamp_option, Some(base_ts + 100 + 2 * 24 * 60 * 60));
+    assert_eq!(stats.users, vec![
+        UserStatistics { user_id: 1, messages_count: 3, avg_response_time_sec_option: Some(60) },
+        UserStatistics { user_id: 2, messages_count: 3, avg_response_time_sec_option: Some(30) },
+    ]);
+    assert_eq!(stats.avg_response_time_sec_option, Some(45));
+    assert_eq!(stats.calls_count, 1);
+    assert_eq!(stats.calls_duration_sec, 120);
+    assert_eq!(stats.media_counts, vec![ContentKindCount { kind: ContentKind::Photo as i32, count: 1 }]);
+    assert_eq!(stats.per_day.len(), 2);
+    assert_eq!(stats.top_words, vec![
+        WordCount { word: "hello".to_owned(), count: 4 },
+        WordCount { word: "anyone".to_owned(), count: 1 },
+    ]);
+
+    Ok(())
+}
+
+#[test]
+fn activity_counts() -> EmptyRes {
+    let daos = init();
+    let src_dao = daos.src_dao.as_ref();
+    let dst_dao = &daos.dst_dao;
+
+    for cwd in src_dao.chats(&daos.ds_uuid)? {
+        let chat = &cwd.chat;
+        let msgs = dst_dao.scroll_messages(chat, 0, usize::MAX)?;
+        let daily = dst_dao.activity_counts(chat, None, None, ActivityGranularity::Day)?;
+        assert_eq!(daily, src_dao.activity_counts(chat, None, None, ActivityGranularity::Day)?);
+        assert_eq!(daily.iter().map(|d| d.count).sum::<i64>(), msgs.len() as i64);
+        assert!(daily.iter().all(|d| d.count > 0 && d.date.len() == 10));
+        assert!(daily.iter().tuple_windows().all(|(d1, d2)| d1.date < d2.date));
+
+        let monthly = dst_dao.activity_counts(chat, None, None, ActivityGranularity::Month)?;
+        assert_eq!(monthly, src_dao.activity_counts(chat, None, None, ActivityGranularity::Month)?);
+        assert_eq!(monthly.iter().map(|d| d.count).sum::<i64>(), msgs.len() as i64);
+        assert!(monthly.iter().all(|d| d.date.len() == 7));
+
+        // Range is inclusive on the left and exclusive on the right
+        if let (Some(first), Some(last)) = (msgs.first(), msgs.last()) {
+            let (from, to) = (Some(first.timestamp + 1), Some(last.timestamp));
+            let ranged = dst_dao.activity_counts(chat, from, to, ActivityGranularity::Day)?;
+            assert_eq!(ranged, src_dao.activity_counts(chat, from, to, ActivityGranularity::Day)?);
+            assert_eq!(ranged.iter().map(|d| d.count).sum::<i64>(),
+                       msgs.iter().filter(|m| m.timestamp > first.timestamp && m.timestamp < last.timestamp).count() as i64);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn links() -> EmptyRes {
+    let daos = init();
+    let src_dao = daos.src_dao.as_ref();
+    let dst_dao = &daos.dst_dao;
+
+    let mut total_links = 0;
+    for cwd in src_dao.chats(&daos.ds_uuid)? {
+        let scope = SearchScope::Chat(&cwd.chat);
+        let dst_links = dst_dao.links(scope, "", None, 0, usize::MAX)?;
+        assert_same_essence(&dst_links, &src_dao.links(scope, "", None, 0, usize::MAX)?);
+        assert_eq!(dst_dao.link_domains(scope, "")?, src_dao.link_domains(scope, "")?);
+        total_links += dst_links.len();
+        assert!(dst_links.iter().tuple_windows()
+            .all(|(l1, l2)| (l1.timestamp, l1.message_internal_id) >= (l2.timestamp, l2.message_internal_id)));
+        assert_eq!(dst_dao.links(scope, "", None, 1, 2)?, dst_links.iter().skip(1).take(2).cloned().collect_vec());
+    }
+    assert!(total_links > 0);
+
+    let scope = SearchScope::Dataset(&daos.ds_uuid);
+    let all_links = dst_dao.links(scope, "", None, 0, usize::MAX)?;
+    assert_eq!(all_links.len(), total_links);
+    assert_same_essence(&all_links, &src_dao.links(scope, "", None, 0, usize::MAX)?);
+    assert!(all_links.iter().tuple_windows().all(|(l1, l2)| l1.timestamp >= l2.timestamp));
+    assert_eq!(dst_dao.links(SearchScope::Everywhere, "", None, 0, usize::MAX)?, all_links);
+
+    let domains = dst_dao.link_domains(scope, "")?;
+    assert_eq!(domains.iter().map(|d| d.count).sum::<i64>(), total_links as i64);
+    assert!(domains.iter().tuple_windows().all(|(d1, d2)| d1.count >= d2.count));
+    let top_domain = &domains[0];
+    let top_domain_links = dst_dao.links(scope, "", Some(&top_domain.domain), 0, usize::MAX)?;
+    assert_eq!(top_domain_links.len() as i64, top_domain.count);
+    assert!(top_domain_links.iter().all(|l| l.domain == top_domain.domain));
+
+    let queried_links = dst_dao.links(scope, "TELEGRA.PH", None, 0, usize::MAX)?;
+    assert!(!queried_links.is_empty());
+    assert_eq!(queried_links, all_links.iter().filter(|l| l.url.contains("telegra.ph")).cloned().collect_vec());
+
+    Ok(())
+}
+
+#[test]
+fn links_extraction() -> EmptyRes {
+    let (mut dao, ds, no_ds_root, _tmp_dirs) = create_sqlite_dao_with_users(2)?;
+
+    let msg = |id: i64, from_id: i64, text: Vec<RichTextElement>|
+        make_message(id, id, from_id, text, MESSAGE_REGULAR_NO_CONTENT.clone());
+
+    let chat = dao.insert_chat(create_group_chat(&ds.uuid, 1, "One", vec![1, 2], 3), &no_ds_root)?;
+    dao.insert_messages(vec![
+        msg(1, 1, vec![
+            RichText::make_plain("Check this out: https://Example.com/article?id=1, and (see www.wikipedia.org/wiki/Rust_(language)).".to_owned()),
+            RichText::make_link(Some("Docs".to_owned()), "https://user@docs.rs:443/diesel".to_owned(), false),
+        ]),
+        msg(2, 2, vec![
+            RichText::make_bold("https://not-a-plain-text.com".to_owned()),
+            RichText::make_link(None, " ".to_owned(), true),
+            RichText::make_plain("No links here, just a file.txt".to_owned()),
+        ]),
+        msg(3, 2, vec![
+            RichText::make_plain("Another one: http://example.com!".to_owned()),
+        ]),
+    ], &chat, &no_ds_root)?;
+
+    fn urls(dao: &SqliteDao, query: &str, domain_option: Option<&str>) -> Result<Vec<(i64, String, String)>> {
+        Ok(dao.links(SearchScope::Everywhere, query, domain_option, 0, 10)?.into_iter()
+            .map(|l| (l.from_id, l.url, l.domain))
+            .collect_vec())
+    }
+
+    assert_eq!(urls(&dao, "", None)?, vec![
+        (2, "http://example.com".to_owned(), "example.com".to_owned()),
+        (1, "https://Example.com/article?id=1".to_owned(), "example.com".to_owned()),
+        (1, "www.wikipedia.org/wiki/Rust_(language)".to_owned(), "wikipedia.org".to_owned()),
+        (1, "https://user@docs.rs:443/diesel".to_owned(), "docs.rs".to_owned()),
+    ]);
+    assert_eq!(urls(&dao, "ARTICLE", None)?, vec![
+        (1, "https://Example.com/article?id=1".to_owned(), "example.com".to_owned()),
+    ]);
+    assert_eq!(urls(&dao, "", Some("docs.rs"))?, vec![
+        (1, "https://user@docs.rs:443/diesel".to_owned(), "docs.rs".to_owned()),
+    ]);
+    assert_eq!(dao.link_domains(SearchScope::Chat(&chat), "")?, vec![
+        DomainCount { domain: "example.com".to_owned(), count: 2 },
+        DomainCount { domain: "docs.rs".to_owned(), count: 1 },
+        DomainCount { domain: "wikipedia.org".to_owned(), count: 1 },
+    ]);
+    assert_eq!(dao.link_domains(SearchScope::Chat(&chat), "article")?, vec![
+        DomainCount { domain: "example.com".to_owned(), count: 1 },
+    ]);
+
+    // Links of messages inserted before links were persisted are extracted once the table is created
+    let all_urls = urls(&dao, "", None)?;
+    {
+        let mut conn = dao.conn.borrow_mut();
+        sql_query("DELETE FROM message_link").execute(conn.deref_mut())?;
+        SqliteDao::extract_existing_links(conn.deref_mut())?;
+    }
+    assert_eq!(urls(&dao, "", None)?, all_urls);
+
+    // Only ASCII case is ignored, same as in generic implementation
+    let non_ascii_url = "https://Пример.рф/Статья";
+    dao.insert_messages(vec![msg(4, 1, vec![RichText::make_plain(non_ascii_url.to_owned())])], &chat, &no_ds_root)?;
+    assert_eq!(urls(&dao, "HTTPS://Пример", None)?, vec![(1, non_ascii_url.to_owned(), "Пример.рф".to_owned())]);
+    assert_eq!(urls(&dao, "https://пример", None)?, vec![]);
+    assert!(!links::link_matches(non_ascii_url, "https://пример"));
+
+    dao.delete_chat(chat)?;
+    assert_eq!(urls(&dao, "", None)?, vec![]);
+
+    Ok(())
+}
+
+#[test]
+fn search_messages() -> EmptyRes {
+    let (mut dao, ds, no_ds_root, _tmp_dirs) = create_sqlite_dao_with_users(2)?;
+
+    let msg = |id: i64, text: &str|
+        make_message(id, id, 1, vec![RichText::make_plain(text.to_owned())], MESSAGE_REGULAR_NO_CONTENT.clone());
+
+    let chat1 = dao.insert_chat(create_group_chat(&ds.uuid, 1, "One", vec![1, 2], 3), &no_ds_root)?;
+    dao.insert_messages(vec![
+        msg(1, "Let's go hiking this weekend"),
+        msg(2, "Hiking? Again?"),
+        msg(3, "Nothing to see here"),
+    ], &chat1, &no_ds_root)?;
+    let chat2 = dao.insert_chat(create_group_chat(&ds.uuid, 2, "Two", vec![1, 2], 1), &no_ds_root)?;
+    dao.insert_messages(vec![
+        msg(1, "Weekend plans: hiking, hiking and more hiking"),
+    ], &chat2, &no_ds_root)?;
+
+    fn search(dao: &SqliteDao, scope: SearchScope, query: &str) -> Result<Vec<(i64, String)>> {
+        Ok(dao.search_messages(scope, query, 0, 10)?.into_iter()
+            .map(|h| (h.chat_id.0, h.message.searchable_string))
+            .collect_vec())
+    }
+
+    // Message with the most hits goes first, terms are matched as case-insensitive word prefixes
+    assert_eq!(search(&dao, SearchScope::Everywhere, "hik")?, vec![
+        (2, "Weekend plans: hiking, hiking and more hiking".to_owned()),
+        (1, "Hiking? Again?".to_owned()),
+        (1, "Let's go hiking this weekend".to_owned()),
+    ]);
+    assert_eq!(search(&dao, SearchScope::Dataset(&ds.uuid), "hiking weekend")?.len(), 2);
+    assert_eq!(search(&dao, SearchScope::Chat(&chat1), "hiking weekend")?, vec![
+        (1, "Let's go hiking this weekend".to_owned()),
+    ]);
+    assert_eq!(search(&dao, SearchScope::Everywhere, "nothing \"to\" (see")?.len(), 1);
+    assert_eq!(search(&dao, SearchScope::Everywhere, "swimming")?, vec![]);
+    assert_eq!(search(&dao, SearchScope::Everywhere, "  ")?, vec![]);
+
+    // Snippet highlights matched terms
+    let hits = dao.search_messages(SearchScope::Chat(&chat1), "again", 0, 10)?;
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].ds_uuid, ds.uuid);
+    assert_eq!(hits[0].message.source_id_option, Some(2));
+    assert_eq!(hits[0].snippet, format!("Hiking? {SEARCH_HIGHLIGHT_START}Again{SEARCH_HIGHLIGHT_END}?"));
+
+    // Paging
+    let all_hits = dao.search_messages(SearchScope::Everywhere, "hiking", 0, 10)?;
+    assert_eq!(dao.search_messages(SearchScope::Everywhere, "hiking", 1, 1)?, all_hits[1..2].to_vec());
+
+    // Index follows deletions
+    dao.delete_chat(chat2)?;
+    assert_eq!(search(&dao, SearchScope::Everywhere, "hiking")?.len(), 2);
+
+    Ok(())
+}
+
 #[test]
 fn update_dataset_same_uuid() -> EmptyRes {
     let (mut dao, _tmp_dir) = create_sqlite_dao();
 
     let ds = dao.insert_dataset(Dataset { uuid: ZERO_PB_UUID.clone(), alias: "My Dataset".to_owned() })?;
-    dao.insert_user(create_user(&ds.uuid, 1), true)?;
+    dao.insert_user(create_user(&ds.uuid, 1), true, &dao.dataset_root(&ds.uuid)?)?;
 
     let ds = dao.as_mutable()?.update_dataset(ds.uuid.clone(), Dataset { uuid: ds.uuid.clone(), alias: "Renamed Dataset".to_owned() })?;
     assert_eq!(dao.datasets()?.remove(0), ds);
@@ -382,7 +1104,7 @@ fn delete_dataset() -> EmptyRes {
         assert!(f.exists());
     }
     let other_ds = dao.insert_dataset(Dataset { uuid: ZERO_PB_UUID.clone(), alias: "My Dataset".to_owned() })?;
-    let other_user = dao.insert_user(create_user(&other_ds.uuid, 1), true)?;
+    let other_user = dao.insert_user(create_user(&other_ds.uuid, 1), true, &dao.dataset_root(&other_ds.uuid)?)?;
     assert_eq!(dao.datasets()?.len(), 2);
 
     dao.delete_dataset(daos.ds_uuid.clone())?;
@@ -417,8 +1139,11 @@ fn update_user() -> EmptyRes {
 
     let ds = dao.insert_dataset(Dataset { uuid: ZERO_PB_UUID.clone(), alias: "My Dataset".to_owned() })?;
 
+    let no_ds_tmp_dir = TmpDir::new();
+    let no_ds_root = DatasetRoot(no_ds_tmp_dir.path.clone());
+
     let users: Vec<User> = (1..=3)
-        .map(|i| dao.insert_user(create_user(&ZERO_PB_UUID, i as i64), i == 1))
+        .map(|i| dao.insert_user(create_user(&ZERO_PB_UUID, i as i64), i == 1, &no_ds_root))
         .try_collect()?;
 
     fn make_hello_message(internal_id: i64, from_id: UserId) -> Message {
@@ -432,9 +1157,6 @@ fn update_user() -> EmptyRes {
         )
     }
 
-    let no_ds_tmp_dir = TmpDir::new();
-    let no_ds_root = DatasetRoot(no_ds_tmp_dir.path.clone());
-
     // Group chat, with messages containing members
 
     let mut group_chat = create_group_chat(&ZERO_PB_UUID, 1, "Group",
@@ -451,6 +1173,18 @@ fn update_user() -> EmptyRes {
         make_hello_message(2, UserId(1)),
         make_hello_message(3, UserId(2)),
         make_hello_message(4, UserId(3)),
+        Message::new(
+            5, Some(5), dt("2023-12-03 12:00:00", None).timestamp() + 5, UserId(1),
+            vec![
+                RichText::make_plain("Hi ".to_owned()),
+                RichText::make_mention(Some(2), users[1].pretty_name()),
+                RichText::make_plain(" and ".to_owned()),
+                RichText::make_mention(Some(3), "u3".to_owned()),
+                RichText::make_plain(", also ".to_owned()),
+                RichText::make_mention(Some(2), "11111".to_owned()),
+            ],
+            MESSAGE_REGULAR_NO_CONTENT.clone(),
+        ),
     ];
     group_chat.msg_count = group_chat_msgs.len() as i32;
     let group_chat = dao.insert_chat(group_chat, &no_ds_root)?;
@@ -522,9 +1256,28 @@ fn update_user() -> EmptyRes {
     if let Some(message_service_pat!(GroupCreate(MessageServiceGroupCreate { members, .. }))) =
         dao.first_messages(&group_chat, 1)?.remove(0).typed
     {
-        assert_eq!(members.as_ref(), vec!["MYSELF FN", "U1 FN U1 LN", UNNAMED]);
+        assert_eq!(members, vec!["MYSELF FN", "U1 FN U1 LN", UNNAMED]);
     }
 
+    // Mentions of named users should be renamed too, unless they don't show the old name
+
+    assert_eq!(dao.last_messages(&group_chat, 1)?.remove(0).text, vec![
+        RichText::make_plain("Hi ".to_owned()),
+        RichText::make_mention(Some(2), "U1 FN U1 LN".to_owned()),
+        RichText::make_plain(" and ".to_owned()),
+        RichText::make_mention(Some(3), "u3".to_owned()),
+        RichText::make_plain(", also ".to_owned()),
+        RichText::make_mention(Some(2), "11111".to_owned()),
+    ]);
+
+    // Searchable strings should follow, so that these messages can be found by new names
+
+    let found_source_ids = dao.search_messages(SearchScope::Chat(&group_chat), "U1 LN", 0, 10)?.into_iter()
+        .map(|h| h.message.source_id_option.unwrap())
+        .sorted()
+        .collect_vec();
+    assert_eq!(found_source_ids, vec![1, 5]);
+
     Ok(())
 }
 
@@ -784,7 +1537,14 @@ fn shift_dataset_time() -> EmptyRes {
     assert_eq!(src_chats.len(), dst_chats.len());
 
     for (src_cwd, dst_cwd) in src_chats.iter().zip(dst_chats.iter()) {
-        assert_eq!(src_cwd.chat, dst_cwd.chat);
+        // Chat details are shifted along with messages
+        let mut expected_chat = src_cwd.chat.clone();
+        expected_chat.created_timestamp_option.iter_mut().for_each(|ts| *ts += TIMESTAMP_DIFF);
+        for details in expected_chat.member_details.iter_mut() {
+            details.joined_timestamp_option.iter_mut().for_each(|ts| *ts += TIMESTAMP_DIFF);
+            details.left_timestamp_option.iter_mut().for_each(|ts| *ts += TIMESTAMP_DIFF);
+        }
+        assert_eq!(dst_cwd.chat, expected_chat);
 
         let all_src_msgs = daos.src_dao.last_messages(&src_cwd.chat, src_cwd.chat.msg_count as usize)?;
         let all_dst_msgs = dao.last_messages(&dst_cwd.chat, dst_cwd.chat.msg_count as usize)?;
@@ -841,7 +1601,7 @@ fn backups() -> EmptyRes {
     dst_dao.insert_dataset(src_dao.dataset())?;
     for u in src_dao.users_single_ds() {
         let is_myself = u.id == src_dao.myself_single_ds().id;
-        dst_dao.insert_user(u, is_myself)?;
+        dst_dao.insert_user(u, is_myself, &src_ds_root)?;
     }
     for src_cwd in src_dao.chats(ds_uuid)? {
         let src_chat = src_cwd.chat;
@@ -944,3 +1704,71 @@ fn create_sqlite_dao() -> (SqliteDao, TmpDir) {
     let dao = SqliteDao::create(&tmp_dir.path.join(SqliteDao::FILENAME)).unwrap();
     (dao, tmp_dir)
 }
+
+/// Creates a DAO with a single empty dataset having users with IDs 1 to `users_count`, first one being myself.
+/// Returned dataset root is a throwaway one, to be passed when inserting entities that reference no files.
+/// Temp dirs should be kept around for as long as the DAO is used.
+fn create_sqlite_dao_with_users(users_count: i64) -> Result<(SqliteDao, Dataset, DatasetRoot, (TmpDir, TmpDir))> {
+    let (mut dao, tmp_dir) = create_sqlite_dao();
+
+    let no_ds_tmp_dir = TmpDir::new();
+    let no_ds_root = DatasetRoot(no_ds_tmp_dir.path.clone());
+
+    let ds = dao.insert_dataset(Dataset { uuid: ZERO_PB_UUID.clone(), alias: "My Dataset".to_owned() })?;
+    for i in 1..=users_count {
+        dao.insert_user(create_user(&ds.uuid, i), i == 1, &no_ds_root)?;
+    }
+    Ok((dao, ds, no_ds_root, (tmp_dir, no_ds_tmp_dir)))
+}
+
+fn base_ts() -> i64 {
+    dt("2023-12-03 12:00:00", None).timestamp()
+}
+
+/// Message with a source ID matching its internal ID, sent `ts_offset` seconds after [base_ts].
+fn make_message(id: i64, ts_offset: i64, from_id: i64, text: Vec<RichTextElement>, typed: message::Typed) -> Message {
+    Message::new(id, Some(id), base_ts() + ts_offset, UserId(from_id), text, typed)
+}
+
+/// Part of an entity that should match between DAOs, as internal IDs (and, for media, paths) differ.
+trait Essence {
+    type Essence: PartialEq + std::fmt::Debug;
+
+    fn essence(&self) -> Self::Essence;
+}
+
+impl Essence for MediaItem {
+    type Essence = (i64, i64, i64, i32, bool);
+
+    fn essence(&self) -> Self::Essence {
+        (self.chat_id, self.timestamp, self.from_id, self.kind, self.file_exists)
+    }
+}
+
+impl Essence for TimelineEntry {
+    type Essence = (PbUuid, i64, i64, Option<i64>, String);
+
+    fn essence(&self) -> Self::Essence {
+        (self.ds_uuid.clone(), self.chat_id, self.message.timestamp, self.message.source_id_option,
+         self.message.searchable_string.clone())
+    }
+}
+
+impl Essence for LinkItem {
+    type Essence = (i64, i64, i64, String, String);
+
+    fn essence(&self) -> Self::Essence {
+        (self.chat_id, self.timestamp, self.from_id, self.url.clone(), self.domain.clone())
+    }
+}
+
+fn assert_same_essence<T: Essence>(actual: &[T], expected: &[T]) {
+    assert_eq!(actual.iter().map(T::essence).collect_vec(), expected.iter().map(T::essence).collect_vec());
+}
+
+fn no_content_regular() -> MessageRegular {
+    match MESSAGE_REGULAR_NO_CONTENT.clone() {
+        message::Typed::Regular(mr) => mr,
+        _ => unreachable!()
+    }
+}
@@ -0,0 +1,121 @@
+use std::cmp;
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Local, TimeZone, Timelike};
+use itertools::Itertools;
+
+use super::*;
+
+/// Gaps between messages longer than this are not considered responses.
+pub const MAX_RESPONSE_TIME_SEC: i64 = 24 * 60 * 60;
+
+/// Shorter words are mostly prepositions and such.
+pub const MIN_WORD_LENGTH: usize = 3;
+
+/// Collects numbers that make up `Statistics`.
+/// Messages are expected to be added one chat at a time, in order.
+/// DAOs capable of aggregating the numbers themselves might fill the fields directly instead.
+#[derive(Debug, Default)]
+pub struct StatisticsAccumulator {
+    pub messages_count: i64,
+    pub first_timestamp_option: Option<i64>,
+    pub last_timestamp_option: Option<i64>,
+    pub per_user: HashMap<i64, i64>,
+    pub per_day: BTreeMap<String, i64>,
+    pub per_hour: [i64; 24],
+    pub per_weekday: [i64; 7],
+    /// Total response time and number of responses for each user
+    pub response_times: HashMap<i64, (i64, i64)>,
+    pub calls_count: i64,
+    pub calls_duration_sec: i64,
+    pub media_counts: HashMap<i32, i64>,
+    pub word_counts: HashMap<String, i64>,
+    /// Sender and timestamp of the last regular message in the current chat
+    last_regular_option: Option<(i64, i64)>,
+}
+
+impl StatisticsAccumulator {
+    pub fn start_chat(&mut self) {
+        self.last_regular_option = None;
+    }
+
+    pub fn add(&mut self, msg: &Message) {
+        let ts = msg.timestamp;
+        self.messages_count += 1;
+        self.first_timestamp_option = Some(self.first_timestamp_option.map_or(ts, |v| cmp::min(v, ts)));
+        self.last_timestamp_option = Some(self.last_timestamp_option.map_or(ts, |v| cmp::max(v, ts)));
+        *self.per_user.entry(msg.from_id).or_default() += 1;
+        if let Some(dt) = Local.timestamp_opt(ts, 0).single() {
+            *self.per_day.entry(dt.format("%Y-%m-%d").to_string()).or_default() += 1;
+            self.per_hour[dt.hour() as usize] += 1;
+            self.per_weekday[dt.weekday().num_days_from_monday() as usize] += 1;
+        }
+        self.add_words(&msg.searchable_string);
+
+        match msg.typed() {
+            message_regular_pat! { content_option, extra_contents, .. } => {
+                if let Some((prev_from_id, prev_ts)) = self.last_regular_option {
+                    let response_time = ts - prev_ts;
+                    if prev_from_id != msg.from_id && (0..=MAX_RESPONSE_TIME_SEC).contains(&response_time) {
+                        let (total, count) = self.response_times.entry(msg.from_id).or_default();
+                        *total += response_time;
+                        *count += 1;
+                    }
+                }
+                self.last_regular_option = Some((msg.from_id, ts));
+
+                for c in content_option.iter().chain(extra_contents.iter()).filter_map(|c| c.sealed_value_optional.as_ref()) {
+                    let kind = ContentKind::from(c);
+                    if MEDIA_CONTENT_KINDS.contains(&kind) {
+                        *self.media_counts.entry(kind as i32).or_default() += 1;
+                    }
+                }
+            }
+            message_service_pat!(message_service::SealedValueOptional::PhoneCall(call)) => {
+                self.calls_count += 1;
+                self.calls_duration_sec += call.duration_sec_option.unwrap_or(0) as i64;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn add_words(&mut self, text: &str) {
+        for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| w.chars().count() >= MIN_WORD_LENGTH) {
+            *self.word_counts.entry(word.to_lowercase()).or_default() += 1;
+        }
+    }
+
+    pub fn finish(self, top_words_limit: usize) -> Statistics {
+        let users = self.per_user.iter()
+            .map(|(&user_id, &messages_count)| UserStatistics {
+                user_id,
+                messages_count,
+                avg_response_time_sec_option: self.response_times.get(&user_id).map(|(total, count)| total / count),
+            })
+            .sorted_by_key(|u| (cmp::Reverse(u.messages_count), u.user_id))
+            .collect_vec();
+        let (total_response_time, responses_count) = self.response_times.values()
+            .fold((0, 0), |(t1, c1), (t2, c2)| (t1 + t2, c1 + c2));
+        Statistics {
+            messages_count: self.messages_count,
+            first_message_timestamp_option: self.first_timestamp_option,
+            last_message_timestamp_option: self.last_timestamp_option,
+            users,
+            per_day: self.per_day.into_iter().map(|(date, count)| DateCount { date, count }).collect_vec(),
+            per_hour: self.per_hour.to_vec(),
+            per_weekday: self.per_weekday.to_vec(),
+            avg_response_time_sec_option: if responses_count > 0 { Some(total_response_time / responses_count) } else { None },
+            calls_count: self.calls_count,
+            calls_duration_sec: self.calls_duration_sec,
+            media_counts: self.media_counts.into_iter()
+                .sorted_by_key(|(kind, _)| *kind)
+                .map(|(kind, count)| ContentKindCount { kind, count })
+                .collect_vec(),
+            top_words: self.word_counts.into_iter()
+                .sorted_by(|(w1, c1), (w2, c2)| c2.cmp(c1).then_with(|| w1.cmp(w2)))
+                .take(top_words_limit)
+                .map(|(word, count)| WordCount { word, count })
+                .collect_vec(),
+        }
+    }
+}
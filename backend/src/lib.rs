@@ -1,9 +1,10 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use prelude::*;
 
 use crate::dao::in_memory_dao::InMemoryDao;
-use crate::loader::Loader;
+use crate::dao::sqlite_dao::SqliteDao;
+use crate::loader::{LoadContext, LoadMode, Loader};
 
 mod protobuf;
 mod loader;
@@ -39,15 +40,36 @@ pub mod prelude {
 
 pub fn parse_file(path: &str, myself_chooser: &dyn MyselfChooser) -> Result<Box<InMemoryDao>> {
     thread_local! {
-        static LOADER: Loader = Loader::new(&ReqwestHttpClient);
+        static LOADER: Loader = create_loader();
     }
     LOADER.with(|loader| {
         loader.parse(Path::new(path), myself_chooser)
     })
 }
 
+/// Appends every loadable file found in the given directory as a separate dataset into the given database,
+/// which is created if it doesn't exist. In lenient mode, messages that couldn't be parsed are replaced with
+/// placeholders and reported as per-file warnings.
+pub fn batch_import(dir: &str,
+                    target_path: &str,
+                    lenient: bool,
+                    myself_chooser: &dyn MyselfChooser) -> Result<Vec<BatchImportResult>> {
+    let loader = create_loader();
+    let mut dst_dao = open_target(target_path)?;
+    let mode = if lenient { LoadMode::Lenient } else { LoadMode::Strict };
+    loader.batch_import(Path::new(dir), &mut dst_dao, myself_chooser, &LoadContext::new(mode))
+}
+
+/// Parses a given file and appends it into the given database (created if it doesn't exist) without keeping
+/// it in memory. Returns UUIDs of the new datasets.
+pub fn import_file(path: &str, target_path: &str, myself_chooser: &dyn MyselfChooser) -> Result<Vec<PbUuid>> {
+    let loader = create_loader();
+    let mut dst_dao = open_target(target_path)?;
+    loader.parse_into_sqlite(Path::new(path), myself_chooser, &mut LoadContext::new(LoadMode::Strict), &mut dst_dao)
+}
+
 pub fn start_server(port: u16) -> EmptyRes {
-    let loader = Loader::new(&ReqwestHttpClient);
+    let loader = create_loader();
     server::start_server(port, loader)
 }
 
@@ -61,7 +83,31 @@ pub fn debug_request_myself(port: u16) -> EmptyRes {
 // Other
 //
 
-pub trait MyselfChooser: Send {
+const PLUGINS_DIR_ENV_VAR: &str = "CHM_PLUGINS_DIR";
+
+fn open_target(target_path: &str) -> Result<SqliteDao> {
+    let target_path = Path::new(target_path);
+    ensure!(path_file_name(target_path)? == SqliteDao::FILENAME, "Target must be a {} file", SqliteDao::FILENAME);
+    SqliteDao::load_or_create(target_path)
+}
+
+/// Plugins directory is taken from CHM_PLUGINS_DIR environment variable, defaulting to "plugins" directory
+/// next to the executable.
+fn create_loader() -> Loader {
+    let mut loader = Loader::new(&ReqwestHttpClient);
+    let plugins_dir = match std::env::var_os(PLUGINS_DIR_ENV_VAR) {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => std::env::current_exe().ok().and_then(|exe| exe.parent().map(|p| p.join("plugins"))),
+    };
+    if let Some(plugins_dir) = plugins_dir.filter(|dir| dir.is_dir()) {
+        if let Err(e) = loader.add_plugins(&plugins_dir) {
+            log::warn!("Failed to load plugins from {}: {:#}", plugins_dir.display(), e);
+        }
+    }
+    loader
+}
+
+pub trait MyselfChooser: Send + Sync {
     fn choose_myself(&self, users: &[User]) -> Result<usize>;
 }
 
@@ -1,39 +1,71 @@
+use std::fmt::Display;
+use std::fs;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 use chrono::Local;
 use const_format::concatcp;
 use itertools::{Either, Itertools};
+use tempfile::TempDir;
 
 use crate::prelude::*;
-use crate::dao::ChatHistoryDao;
+use crate::dao::{ChatHistoryDao, MutableChatHistoryDao};
 use crate::dao::sqlite_dao::SqliteDao;
+use crate::loader::archive::ArchiveKind;
 use crate::loader::badoo_android::BadooAndroidDataLoader;
+use crate::loader::generic_csv::GenericCsvDataLoader;
 use crate::loader::mra::MailRuAgentDataLoader;
+use crate::loader::plugin::PluginDataLoader;
+use crate::loader::sink::{InMemorySink, LoadSink, SqliteSink};
 use crate::loader::telegram::TelegramDataLoader;
 use crate::loader::tinder_android::TinderAndroidDataLoader;
 use crate::loader::whatsapp_android::WhatsAppAndroidDataLoader;
 use crate::loader::whatsapp_text::WhatsAppTextDataLoader;
 
+mod archive;
 mod telegram;
 mod tinder_android;
 mod whatsapp_android;
 mod whatsapp_text;
 mod badoo_android;
 mod mra;
+mod generic_csv;
+mod plugin;
+pub mod sink;
 
-trait DataLoader: Send {
-    fn name(&self) -> &'static str;
+#[cfg(test)]
+#[path = "loader_tests.rs"]
+mod tests;
+
+trait DataLoader: Send + Sync {
+    fn name(&self) -> &str;
 
     /// Used in dataset alias
-    fn src_alias(&self) -> &'static str {
+    fn src_alias(&self) -> &str {
         self.name()
     }
 
     fn src_type(&self) -> SourceType;
 
-    // TODO: Add allowed files filter
+    /// Glob patterns (matched against a file name) of files this loader might accept.
+    fn file_globs(&self) -> Vec<&str>;
+
+    fn needs_myself_chooser(&self) -> bool { false }
+
+    fn needs_network(&self) -> bool { false }
+
+    fn info(&self) -> LoaderInfo {
+        LoaderInfo {
+            name: self.name().to_owned(),
+            src_type_option: Some(self.src_type() as i32),
+            file_globs: self.file_globs().into_iter().map(|g| g.to_owned()).collect_vec(),
+            needs_myself_chooser: self.needs_myself_chooser(),
+            needs_network: self.needs_network(),
+        }
+    }
 
     fn looks_about_right(&self, path: &Path) -> EmptyRes {
         ensure_file_presence(path)?;
@@ -42,7 +74,37 @@ trait DataLoader: Send {
 
     fn looks_about_right_inner(&self, path: &Path) -> EmptyRes;
 
+    /// Glob patterns of files that, besides the accepted file itself, are needed to check whether it's acceptable.
+    fn probe_companion_globs(&self) -> Vec<&str> { vec![] }
+
+    /// Directory containing everything needed to load the given (accepted) file.
+    /// Used to only extract the relevant part of an archive.
+    fn root_dir<'a>(&self, path: &'a Path) -> &'a Path {
+        path.parent().unwrap_or(Path::new(""))
+    }
+
+    /// Strict load, a shortcut for tests
+    #[cfg(test)]
     fn load(&self, path: &Path, myself_chooser: &dyn MyselfChooser) -> Result<Box<InMemoryDao>> {
+        self.load_with_context(path, myself_chooser, &mut LoadContext::new(LoadMode::Strict))
+    }
+
+    #[cfg(test)]
+    fn load_with_context(&self,
+                         path: &Path,
+                         myself_chooser: &dyn MyselfChooser,
+                         ctx: &mut LoadContext) -> Result<Box<InMemoryDao>> {
+        let mut sink = InMemorySink::default();
+        self.load_into(path, myself_chooser, ctx, &mut sink)?;
+        Ok(Box::new(sink.into_dao()?))
+    }
+
+    /// Loads a file, pushing parsed data into the given sink as it goes.
+    fn load_into(&self,
+                 path: &Path,
+                 myself_chooser: &dyn MyselfChooser,
+                 ctx: &mut LoadContext,
+                 sink: &mut dyn LoadSink) -> EmptyRes {
         let root_path_str = ensure_file_presence(path)?;
         measure(|| {
             let now_str = Local::now().format("%Y-%m-%d");
@@ -50,13 +112,188 @@ trait DataLoader: Send {
                 uuid: PbUuid::random(),
                 alias: format!("{}, loaded @ {now_str}", self.src_alias()),
             };
-            self.load_inner(path, ds, myself_chooser)
+            self.load_inner(path, ds, myself_chooser, ctx, sink)
         }, |_, t| log::info!("File {} loaded in {t} ms", root_path_str))
     }
 
-    fn load_inner(&self, path: &Path, ds: Dataset, myself_chooser: &dyn MyselfChooser) -> Result<Box<InMemoryDao>>;
+    fn load_inner(&self,
+                  path: &Path,
+                  ds: Dataset,
+                  myself_chooser: &dyn MyselfChooser,
+                  ctx: &mut LoadContext,
+                  sink: &mut dyn LoadSink) -> EmptyRes;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadMode {
+    /// Any problem aborts the whole load
+    Strict,
+    /// Messages that couldn't be parsed are replaced with placeholders, problems are collected as warnings
+    Lenient,
+}
+
+/// How many messages could be parsed between cancellation checks
+const CANCEL_CHECK_MSG_INTERVAL: usize = 1_000;
+
+/// Settings and state of a single load.
+pub struct LoadContext {
+    mode: LoadMode,
+    warnings: Vec<LoadWarning>,
+    progress: Arc<LoadProgress>,
+}
+
+impl LoadContext {
+    pub fn new(mode: LoadMode) -> Self {
+        Self::with_progress(mode, Default::default())
+    }
+
+    /// Context reporting to a progress that is observed (and possibly cancelled) from another thread.
+    pub fn with_progress(mode: LoadMode, progress: Arc<LoadProgress>) -> Self {
+        LoadContext { mode, warnings: vec![], progress }
+    }
+
+    pub fn mode(&self) -> LoadMode {
+        self.mode
+    }
+
+    pub fn set_phase(&self, phase: impl Into<String>) {
+        let phase = phase.into();
+        log::debug!("Load phase: {phase}");
+        *self.progress.phase.lock().unwrap() = phase;
+    }
+
+    /// Should be called by loaders once a chat is fully parsed. Fails if the load was cancelled.
+    pub fn chat_parsed(&self, msg_count: usize) -> EmptyRes {
+        self.progress.chats_parsed.fetch_add(1, Ordering::Relaxed);
+        self.progress.messages_parsed.fetch_add(msg_count, Ordering::Relaxed);
+        self.check_cancelled()
+    }
+
+    /// Should be called by loaders for every message while parsing a chat, so that even a huge chat can be
+    /// cancelled midway. Only checks for cancellation every so often.
+    pub fn message_parsed(&self, msg_idx: usize) -> EmptyRes {
+        if (msg_idx + 1).is_multiple_of(CANCEL_CHECK_MSG_INTERVAL) {
+            self.check_cancelled()?;
+        }
+        Ok(())
+    }
+
+    pub fn files_copied(&self, count: usize) {
+        self.progress.add_files_copied(count);
+    }
+
+    pub fn check_cancelled(&self) -> EmptyRes {
+        self.progress.check_cancelled()
+    }
+
+    /// In strict mode, returns the error with its location as a context. In lenient mode, records it as a warning
+    /// instead, so that the caller can substitute what couldn't be parsed.
+    pub fn tolerate(&mut self, location: String, error: anyhow::Error) -> EmptyRes {
+        match self.mode {
+            LoadMode::Strict => Err(error.context(location)),
+            LoadMode::Lenient => {
+                let message = format!("{:#}", error);
+                log::warn!("{location}: {message}");
+                self.warnings.push(LoadWarning { location, message });
+                Ok(())
+            }
+        }
+    }
+
+    pub fn into_warnings(self) -> Vec<LoadWarning> {
+        self.warnings
+    }
+}
+
+/// Load progress, updated by the loading thread.
+#[derive(Debug, Default)]
+pub struct LoadProgress {
+    phase: Mutex<String>,
+    chats_parsed: AtomicUsize,
+    messages_parsed: AtomicUsize,
+    files_copied: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl LoadProgress {
+    pub fn phase(&self) -> String { self.phase.lock().unwrap().clone() }
+
+    pub fn chats_parsed(&self) -> usize { self.chats_parsed.load(Ordering::Relaxed) }
+
+    pub fn messages_parsed(&self) -> usize { self.messages_parsed.load(Ordering::Relaxed) }
+
+    pub fn files_copied(&self) -> usize { self.files_copied.load(Ordering::Relaxed) }
+
+    /// Makes the load fail at the next checkpoint.
+    pub fn cancel(&self) { self.cancelled.store(true, Ordering::Relaxed) }
+
+    pub fn is_cancelled(&self) -> bool { self.cancelled.load(Ordering::Relaxed) }
+
+    fn add_files_copied(&self, count: usize) { self.files_copied.fetch_add(count, Ordering::Relaxed); }
+
+    fn check_cancelled(&self) -> EmptyRes {
+        ensure!(!self.is_cancelled(), "Load cancelled");
+        Ok(())
+    }
+}
+
+/// Merges album items (exported as separate messages, see [Message::is_album_continuation_of]) back into a single
+/// message holding all their contents. Messages are expected to be sorted.
+fn merge_albums(messages: Vec<Message>) -> Vec<Message> {
+    messages.into_iter()
+        .coalesce(|mut album_msg, msg| {
+            if msg.is_album_continuation_of(&album_msg) {
+                album_msg.merge_album_item(msg);
+                Ok(album_msg)
+            } else {
+                Err((album_msg, msg))
+            }
+        })
+        .collect_vec()
 }
 
+/// Marks a placeholder for a message that couldn't be parsed, see [placeholder_message].
+const PLACEHOLDER_MARKER: &str = "[Unparsed message]";
+
+/// Placeholder for a message that couldn't be parsed in lenient mode, keeping its raw payload as a text
+/// following a [PLACEHOLDER_MARKER].
+fn placeholder_message(source_id_option: Option<i64>, timestamp: i64, from_id: UserId, raw_payload: String) -> Message {
+    Message::new(
+        *NO_INTERNAL_ID,
+        source_id_option,
+        timestamp,
+        from_id,
+        placeholder_text(raw_payload),
+        message_regular! {
+            edit_timestamp_option: None,
+            is_deleted: false,
+            forward_from_name_option: None,
+            reply_to_message_id_option: None,
+            content_option: None,
+            extra_contents: vec![],
+            forward_origin_option: None,
+            ttl_sec_option: None,
+        },
+    )
+}
+
+fn placeholder_text(raw_payload: String) -> Vec<RichTextElement> {
+    vec![RichText::make_italic(PLACEHOLDER_MARKER.to_owned()), RichText::make_prefmt_block(raw_payload, None)]
+}
+
+/// Location of a lenient mode warning about a chat message, with the message identified the way source does it.
+fn message_location(chat_name: &str, message_key: impl Display) -> String {
+    format!("Chat {chat_name}, message {message_key}")
+}
+
+/// Location of a lenient mode warning about a line of a text file.
+fn line_location(file_name: &str, line_no: usize) -> String {
+    format!("{file_name}, line {line_no}")
+}
+
+const SQLITE_LOADER_NAME: &str = "Chat History Manager DB";
+const ARCHIVE_LOADER_NAME: &str = "Archive";
+
 pub struct Loader {
     loaders: Vec<Box<dyn DataLoader>>,
 }
@@ -71,32 +308,98 @@ impl Loader {
                 Box::new(TinderAndroidDataLoader { http_client }),
                 Box::new(BadooAndroidDataLoader),
                 Box::new(MailRuAgentDataLoader),
+                Box::new(GenericCsvDataLoader),
             ],
         }
     }
 
+    /// Registers out-of-process loaders found in the given directory, to be tried after the built-in ones.
+    pub fn add_plugins(&mut self, plugins_dir: &Path) -> EmptyRes {
+        for plugin in PluginDataLoader::discover(plugins_dir)? {
+            self.loaders.push(Box::new(plugin));
+        }
+        Ok(())
+    }
+
     /// If the given file is an internal Sqlite DB, open it, otherwise attempt to parse a file as a foreign history.
-    pub fn load(&self, path: &Path, myself_chooser: &dyn MyselfChooser) -> Result<Box<dyn ChatHistoryDao>> {
+    /// Foreign history is kept in memory, unless a target Sqlite DB (created if missing) is given - then it's written
+    /// straight into it, and that DB is returned.
+    pub fn load(&self,
+                path: &Path,
+                myself_chooser: &dyn MyselfChooser,
+                target_path_option: Option<&Path>,
+                ctx: &mut LoadContext) -> Result<Box<dyn ChatHistoryDao>> {
         let filename = path_file_name(path)?;
         if filename == SqliteDao::FILENAME {
+            ensure!(target_path_option.is_none(), "{} is a database already", path.display());
+            ctx.set_phase("Opening database");
             Ok(Box::new(SqliteDao::load(path)?))
+        } else if let Some(target_path) = target_path_option {
+            ctx.set_phase("Opening target database");
+            let mut dst_dao = SqliteDao::load_or_create(target_path)?;
+            self.parse_into_sqlite(path, myself_chooser, ctx, &mut dst_dao)?;
+            Ok(Box::new(dst_dao))
         } else {
-            Ok(self.parse(path, myself_chooser)?)
+            Ok(self.parse_with_context(path, myself_chooser, ctx)?)
         }
     }
 
-    /// Parses a history in a foreign format
+    /// Parses a history in a foreign format, possibly packed in an archive
     pub fn parse(&self, path: &Path, myself_chooser: &dyn MyselfChooser) -> Result<Box<InMemoryDao>> {
+        self.parse_with_context(path, myself_chooser, &mut LoadContext::new(LoadMode::Strict))
+    }
+
+    pub fn parse_with_context(&self,
+                              path: &Path,
+                              myself_chooser: &dyn MyselfChooser,
+                              ctx: &mut LoadContext) -> Result<Box<InMemoryDao>> {
+        let mut sink = InMemorySink::default();
+        let temp_dir_option = self.parse_into(path, myself_chooser, ctx, &mut sink)?;
+        let mut dao = sink.into_dao()?;
+        if let Some(temp_dir) = temp_dir_option {
+            dao.own_temp_dir(temp_dir);
+        }
+        Ok(Box::new(dao))
+    }
+
+    /// Parses a history in a foreign format, writing it straight into the given DAO without keeping it in memory.
+    /// Returns UUIDs of the new datasets. On failure, partially written datasets are removed.
+    pub fn parse_into_sqlite(&self,
+                             path: &Path,
+                             myself_chooser: &dyn MyselfChooser,
+                             ctx: &mut LoadContext,
+                             dst_dao: &mut SqliteDao) -> Result<Vec<PbUuid>> {
+        let mut sink = SqliteSink::new(dst_dao, ctx.progress.clone());
+        let res = self.parse_into(path, myself_chooser, ctx, &mut sink);
+        let written_uuids = sink.finish()?;
+        if let Err(e) = res {
+            let dst_uuids = dst_dao.datasets()?.into_iter().map(|ds| ds.uuid).collect_vec();
+            for ds_uuid in written_uuids.into_iter().filter(|uuid| dst_uuids.contains(uuid)) {
+                dst_dao.delete_dataset(ds_uuid)?;
+            }
+            return Err(e);
+        }
+        Ok(written_uuids)
+    }
+
+    /// Parses a file (possibly an archive) into a sink.
+    /// If archive was extracted, returns a temporary directory which should outlive the parsed data.
+    fn parse_into(&self,
+                  path: &Path,
+                  myself_chooser: &dyn MyselfChooser,
+                  ctx: &mut LoadContext,
+                  sink: &mut dyn LoadSink) -> Result<Option<TempDir>> {
         ensure!(path.exists(), "File not found");
-        let (named_errors, loads): (Vec<_>, Vec<_>) =
-            self.loaders.iter()
-                .partition_map(|loader| match loader.looks_about_right(path) {
-                    Ok(()) => Either::Right(|| loader.load(path, myself_chooser)),
-                    Err(why) => Either::Left((loader.name(), why)),
-                });
+        if let Some(kind) = ArchiveKind::of(path) {
+            return self.parse_archive(path, kind, myself_chooser, ctx, sink).map(Some);
+        }
+        let (loads, named_errors) = self.check_loaders(path);
         match loads.first() {
-            Some(load) =>
-                load(),
+            Some(loader) => {
+                ctx.set_phase(format!("Parsing {}", loader.name()));
+                loader.load_into(path, myself_chooser, ctx, sink)?;
+                Ok(None)
+            }
             None => {
                 // Report why everyone rejected the file.
                 err!("No loader accepted the file:\n{}",
@@ -104,6 +407,177 @@ impl Loader {
             }
         }
     }
+
+    /// Describes all supported formats, including internal database and archives.
+    pub fn list_loaders(&self) -> Vec<LoaderInfo> {
+        let container_info = |name: &str, file_globs: &[&str]| LoaderInfo {
+            name: name.to_owned(),
+            src_type_option: None,
+            file_globs: file_globs.iter().map(|g| (*g).to_owned()).collect_vec(),
+            needs_myself_chooser: false,
+            needs_network: false,
+        };
+        let mut result = vec![container_info(SQLITE_LOADER_NAME, &[SqliteDao::FILENAME])];
+        result.extend(self.loaders.iter().map(|loader| loader.info()));
+        result.push(container_info(ARCHIVE_LOADER_NAME, archive::ARCHIVE_GLOBS));
+        result
+    }
+
+    /// Lists loaders that accept the given file (in order of preference) and reasons other loaders rejected it.
+    pub fn probe(&self, path: &Path) -> Result<ProbeResponse> {
+        ensure!(path.exists(), "File not found");
+        let container_name_option =
+            if path_file_name(path)? == SqliteDao::FILENAME {
+                Some(SQLITE_LOADER_NAME)
+            } else if ArchiveKind::of(path).is_some() {
+                Some(ARCHIVE_LOADER_NAME)
+            } else {
+                None
+            };
+        if let Some(name) = container_name_option {
+            return Ok(ProbeResponse { accepted_by: vec![name.to_owned()], rejections: vec![] });
+        }
+        let (loads, named_errors) = self.check_loaders(path);
+        Ok(ProbeResponse {
+            accepted_by: loads.iter().map(|loader| loader.name().to_owned()).collect_vec(),
+            rejections: named_errors.into_iter()
+                .map(|(name, why)| LoaderRejection { loader_name: name.to_owned(), reason: format!("{:#}", why) })
+                .collect_vec(),
+        })
+    }
+
+    fn check_loaders(&self, path: &Path) -> (Vec<&dyn DataLoader>, Vec<(&str, anyhow::Error)>) {
+        self.loaders.iter()
+            .partition_map(|loader| match loader.looks_about_right(path) {
+                Ok(()) => Either::Left(loader.as_ref()),
+                Err(why) => Either::Right((loader.name(), why)),
+            })
+    }
+
+    /// Finds every file under the given directory that is either accepted by one of the loaders or is an archive.
+    /// Internal databases are skipped, as is everything nested within a directory of an export already found
+    /// (e.g. attachments), except for other files accepted by loaders right next to it.
+    /// Root of an export placed directly in the given directory doesn't hide anything.
+    pub fn find_loadable_files(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        ensure!(dir.is_dir(), "{} is not a directory", dir.display());
+        let files = list_all_files(dir, true)?.into_iter()
+            .filter(|file| path_file_name(file).is_ok_and(|name| name != SqliteDao::FILENAME))
+            // Shallower files first, so that export roots are known before their content is reached
+            .sorted_by_key(|file| (file.components().count(), file.clone()))
+            .collect_vec();
+
+        let mut roots: Vec<PathBuf> = vec![];
+        let mut result = vec![];
+        for file in files.iter() {
+            let Some(loader) = self.loaders.iter().find(|loader| loader.looks_about_right(file).is_ok()) else { continue };
+            let is_nested = roots.iter().any(|root| file.starts_with(root) && file.parent() != Some(root));
+            if !is_nested {
+                let root = loader.root_dir(file);
+                if root != dir {
+                    roots.push(root.to_path_buf());
+                }
+                result.push(file.clone());
+            }
+        }
+        result.extend(files.into_iter()
+            .filter(|file| ArchiveKind::of(file).is_some() && !roots.iter().any(|root| file.starts_with(root))));
+        Ok(result.into_iter().sorted().collect_vec())
+    }
+
+    /// Loads every file found by `find_loadable_files` and appends its datasets into the given DAO.
+    /// Failing to import one file does not prevent others from being imported, but cancellation stops the import.
+    pub fn batch_import(&self,
+                        dir: &Path,
+                        dst_dao: &mut SqliteDao,
+                        myself_chooser: &dyn MyselfChooser,
+                        ctx: &LoadContext) -> Result<Vec<BatchImportResult>> {
+        ctx.set_phase("Looking for files to import");
+        let files = self.find_loadable_files(dir)?;
+        log::info!("Found {} loadable files in {}", files.len(), dir.display());
+        let mut results = vec![];
+        for file in files {
+            ctx.check_cancelled()?;
+            let path = file.to_string_lossy().into_owned();
+            // Each file gets its own warnings, but progress is shared
+            let mut file_ctx = LoadContext::with_progress(ctx.mode(), ctx.progress.clone());
+            let outcome = self.parse_into_sqlite(&file, myself_chooser, &mut file_ctx, dst_dao);
+            let warnings = file_ctx.into_warnings();
+            results.push(match outcome {
+                Ok(ds_uuids) =>
+                    BatchImportResult { path, ds_uuids, error_option: None, warnings },
+                Err(e) => {
+                    ctx.check_cancelled()?;
+                    log::warn!("Failed to import {}: {}", path, e);
+                    BatchImportResult { path, ds_uuids: vec![], error_option: Some(error_to_string(&e)), warnings }
+                }
+            });
+        }
+        Ok(results)
+    }
+
+    /// Probes archive content by extracting files that loaders might accept, then extracts the rest of
+    /// the directory needed by the loader that accepted one of them into a temporary dataset root,
+    /// which is returned to the caller.
+    fn parse_archive(&self,
+                     path: &Path,
+                     kind: ArchiveKind,
+                     myself_chooser: &dyn MyselfChooser,
+                     ctx: &mut LoadContext,
+                     sink: &mut dyn LoadSink) -> Result<TempDir> {
+        let stem = archive::archive_stem(path)?;
+
+        ctx.set_phase("Probing archive");
+        // Probed files are extracted fully, as loaders (plugins especially) might look past their beginning
+        let probe_dir = tempfile::Builder::new().prefix("chm-probe_").tempdir()?;
+        let probe_root = probe_dir.path().join(stem);
+        let probe_globs = self.loaders.iter()
+            .flat_map(|loader| loader.file_globs().into_iter().chain(loader.probe_companion_globs()))
+            .collect_vec();
+        let probed = measure(|| {
+            archive::extract(path, kind, &probe_root, |p| {
+                let name_option = p.file_name().and_then(|n| n.to_str());
+                name_option.is_some_and(|name| probe_globs.iter().any(|glob| glob_matches(glob, name)))
+            })
+        }, |_, t| log::info!("Archive {} probed in {t} ms", path.display()))?;
+
+        // Shallower files are tried first, as nested files are more likely to be attachments.
+        let (loader, rel_path) = probed.iter()
+            .sorted_by_key(|p| p.components().count())
+            .find_map(|rel_path| {
+                let probe_path = probe_root.join(rel_path);
+                self.loaders.iter()
+                    .find(|loader| loader.looks_about_right(&probe_path).is_ok())
+                    .map(|loader| (loader, rel_path.clone()))
+            })
+            .with_context(|| format!("No loader accepted any file in archive {}", path.display()))?;
+
+        let root_dir = loader.root_dir(&rel_path).to_path_buf();
+        log::info!("Archive {} accepted by {} loader, extracting {}/{}",
+                   path.display(), loader.name(), stem, root_dir.display());
+
+        ctx.check_cancelled()?;
+        ctx.set_phase("Extracting archive");
+        let temp_dir = tempfile::Builder::new().prefix("chm-archive_").tempdir()?;
+        let extract_root = temp_dir.path().join(stem);
+
+        // Files extracted while probing are moved rather than extracted again
+        let moved = probed.into_iter().filter(|p| p.starts_with(&root_dir)).collect::<HashSet<_>>();
+        for rel_path in moved.iter() {
+            let dst_path = extract_root.join(rel_path);
+            fs::create_dir_all(dst_path.parent().unwrap())?;
+            fs::rename(probe_root.join(rel_path), dst_path)?;
+        }
+        drop(probe_dir);
+
+        let extracted = measure(|| {
+            archive::extract(path, kind, &extract_root, |p| p.starts_with(&root_dir) && !moved.contains(p))
+        }, |_, t| log::info!("Archive {} extracted in {t} ms", path.display()))?;
+        ctx.files_copied(moved.len() + extracted.len());
+
+        ctx.set_phase(format!("Parsing {}", loader.name()));
+        loader.load_into(&extract_root.join(&rel_path), myself_chooser, ctx, sink)?;
+        Ok(temp_dir)
+    }
 }
 
 fn ensure_file_presence(root_file: &Path) -> Result<&str> {
@@ -129,14 +603,27 @@ fn first_line(path: &Path) -> Result<String> {
     Ok(buffered.lines().next().context("File is empty")??.trim().to_owned())
 }
 
+/// Matches a file name against a glob pattern, where `*` stands for any sequence of characters and `?` for any
+/// single character.
+fn glob_matches(glob: &str, filename: &str) -> bool {
+    fn matches(glob: &[char], name: &[char]) -> bool {
+        match glob.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|skip| matches(rest, &name[skip..])),
+            Some(('?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && matches(rest, &name[1..]),
+        }
+    }
+    matches(&glob.chars().collect_vec(), &filename.chars().collect_vec())
+}
+
 // Android-specific helpers.
 pub mod android {
     pub const DATABASES: &str = "databases";
 
     /// Boilerplate for a data loader of salvaged Android sqlite database.
-    /// First construct a custom users structure, use it to read chats, then normalize the structure into
-    /// plain old Vec<User>.
-    /// Produced users should have myself as a first user.
+    /// First construct a custom users structure and pick users to be written upfront (myself going first),
+    /// then read chats, writing each one as soon as it's parsed.
     #[macro_export]
     macro_rules! android_sqlite_loader {
         (
@@ -167,10 +654,14 @@ pub mod android {
             )? DataLoader for $loader_name$(<$($generic_type_name),*>)? {
                 fn name(&self) -> &'static str { concatcp!($name, " (db)") }
 
-                fn src_alias(&self) -> &'static str { self.name() }
+                fn src_alias(&self) -> &str { self.name() }
 
                 fn src_type(&self) -> SourceType { SourceType::$tpe }
 
+                fn file_globs(&self) -> Vec<&'static str> { vec![$db_filename] }
+
+                fn needs_network(&self) -> bool { Self::NEEDS_NETWORK }
+
                 fn looks_about_right_inner(&self, path: &Path) -> EmptyRes {
                     let filename = path_file_name(path)?;
                     if filename != $db_filename {
@@ -179,8 +670,22 @@ pub mod android {
                     Ok(())
                 }
 
-                fn load_inner(&self, path: &Path, ds: Dataset, _myself_chooser: &dyn MyselfChooser) -> Result<Box<InMemoryDao>> {
-                    parse_android_db(self, path, ds)
+                fn root_dir<'a>(&self, path: &'a Path) -> &'a Path {
+                    // Media directory is a sibling of databases directory
+                    let parent = path.parent().unwrap_or(Path::new(""));
+                    match parent.parent() {
+                        Some(grandparent) if parent.ends_with(android::DATABASES) => grandparent,
+                        _ => parent,
+                    }
+                }
+
+                fn load_inner(&self,
+                              path: &Path,
+                              ds: Dataset,
+                              _myself_chooser: &dyn MyselfChooser,
+                              ctx: &mut LoadContext,
+                              sink: &mut dyn LoadSink) -> EmptyRes {
+                    parse_android_db(self, path, ds, ctx, sink)
                 }
             }
 
@@ -189,7 +694,11 @@ pub mod android {
                     $generic_type_name
                     $(: $generic_type_bound $(+ $generic_type_bound2:ident)* )?
                 ),*>
-            )? (this: &$loader_name$(<$($generic_type_name),*>)?, path: &Path, ds: Dataset) -> Result<Box<InMemoryDao>> {
+            )? (this: &$loader_name$(<$($generic_type_name),*>)?,
+                 path: &Path,
+                 ds: Dataset,
+                 ctx: &mut LoadContext,
+                 sink: &mut dyn LoadSink) -> EmptyRes {
                 let path = path.parent().unwrap();
 
                 let conn = Connection::open(path.join($db_filename))?;
@@ -201,18 +710,20 @@ pub mod android {
                     path
                 };
 
-                let mut users = this.parse_users(&conn, &ds.uuid)?;
-                let cwms = this.parse_chats(&conn, &ds.uuid, &mut users, &path)?;
+                let ds_uuid = ds.uuid.clone();
+                let users = this.parse_users(&conn, &ds_uuid)?;
 
-                let users = this.normalize_users(users, &cwms)?;
-                Ok(Box::new(InMemoryDao::new_single(
+                let initial_users = this.initial_users(&users)?;
+                let myself_id = initial_users[0].id();
+                let mut writer = DatasetWriter::start(
+                    sink,
                     format!("{} ({})", $name, path_file_name(path)?),
                     ds,
                     path.to_path_buf(),
-                    users[0].id(),
-                    users,
-                    cwms,
-                )))
+                    myself_id,
+                    initial_users,
+                )?;
+                this.parse_chats(&conn, &ds_uuid, &users, &path, ctx, &mut writer)
             }
         };
     }
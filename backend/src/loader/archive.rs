@@ -0,0 +1,98 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::{Component, PathBuf};
+
+use flate2::read::GzDecoder;
+
+use crate::prelude::*;
+
+#[cfg(test)]
+#[path = "archive_tests.rs"]
+mod tests;
+
+pub const ARCHIVE_GLOBS: &[&str] = &["*.zip", "*.tar.gz", "*.tgz"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind { Zip, TarGz }
+
+impl ArchiveKind {
+    pub fn of(path: &Path) -> Option<ArchiveKind> {
+        let filename = path_file_name(path).ok()?.to_lowercase();
+        if filename.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if filename.ends_with(".tar.gz") || filename.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// Archive name without extension, used as a name of the directory archive content is extracted into.
+pub fn archive_stem(path: &Path) -> Result<&str> {
+    let filename = path_file_name(path)?;
+    let lowercase = filename.to_lowercase();
+    let ext_len = [".zip", ".tar.gz", ".tgz"].iter()
+        .find(|ext| lowercase.ends_with(*ext))
+        .map(|ext| ext.len())
+        .with_context(|| format!("{} is not a supported archive", filename))?;
+    Ok(&filename[..(filename.len() - ext_len)])
+}
+
+/// Extracts archive files accepted by `select` into `dst` preserving their relative paths.
+/// Returns relative paths of all extracted files, in archive order.
+pub fn extract(path: &Path,
+               kind: ArchiveKind,
+               dst: &Path,
+               mut select: impl FnMut(&Path) -> bool) -> Result<Vec<PathBuf>> {
+    let mut extracted = vec![];
+    let mut process = |rel_path: &Path, reader: &mut dyn Read| -> EmptyRes {
+        if !select(rel_path) { return Ok(()); }
+        let file_path = dst.join(rel_path);
+        fs::create_dir_all(file_path.parent().unwrap())?;
+        let mut file = File::create(&file_path)?;
+        io::copy(reader, &mut file)?;
+        extracted.push(rel_path.to_path_buf());
+        Ok(())
+    };
+
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+            for i in 0..archive.len() {
+                let mut entry = archive.by_index(i)?;
+                if entry.is_dir() { continue; }
+                let rel_path = entry.enclosed_name()
+                    .with_context(|| format!("Unsafe path in archive: {}", entry.name()))?
+                    .to_path_buf();
+                process(&rel_path, &mut entry)?;
+            }
+        }
+        ArchiveKind::TarGz => {
+            let mut archive = tar::Archive::new(GzDecoder::new(File::open(path)?));
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if !entry.header().entry_type().is_file() { continue; }
+                let rel_path = sanitize(&entry.path()?)
+                    .with_context(|| format!("Unsafe path in archive: {}", String::from_utf8_lossy(&entry.path_bytes())))?;
+                process(&rel_path, &mut entry)?;
+            }
+        }
+    }
+    Ok(extracted)
+}
+
+/// Strips `.` components, returns `None` if path might point outside of extraction directory.
+fn sanitize(path: &Path) -> Option<PathBuf> {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(c) => result.push(c),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(result)
+}
@@ -0,0 +1,99 @@
+#![allow(unused_imports)]
+
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use pretty_assertions::{assert_eq, assert_ne};
+
+use crate::{NoChooser, User};
+use crate::dao::ChatHistoryDao;
+use crate::loader::Loader;
+
+use super::*;
+
+//
+// Tests
+//
+
+#[test]
+fn archive_kind() {
+    assert_eq!(ArchiveKind::of(Path::new("/a/export.zip")), Some(ArchiveKind::Zip));
+    assert_eq!(ArchiveKind::of(Path::new("/a/export.ZIP")), Some(ArchiveKind::Zip));
+    assert_eq!(ArchiveKind::of(Path::new("/a/export.tar.gz")), Some(ArchiveKind::TarGz));
+    assert_eq!(ArchiveKind::of(Path::new("/a/export.tgz")), Some(ArchiveKind::TarGz));
+    assert_eq!(ArchiveKind::of(Path::new("/a/export.tar")), None);
+    assert_eq!(ArchiveKind::of(Path::new("/a/result.json")), None);
+
+    assert_eq!(archive_stem(Path::new("/a/export.tar.gz")).unwrap(), "export");
+}
+
+#[test]
+fn loading_zip() -> EmptyRes {
+    let src_dir = resource("csv_2026-10");
+    let tmp_dir = TmpDir::new();
+    let zip_path = tmp_dir.path.join("csv export.zip");
+    {
+        let mut zip = zip::ZipWriter::new(File::create(&zip_path)?);
+        for file in list_all_files(&src_dir, true)? {
+            let rel_path = file.strip_prefix(&src_dir)?;
+            zip.start_file(path_to_str(rel_path)?, zip::write::FileOptions::default())?;
+            zip.write_all(&fs::read(&file)?)?;
+        }
+        zip.finish()?;
+    }
+
+    let loader = Loader::new(&*HTTP_CLIENT);
+    let expected = loader.parse(&src_dir.join("chat.csv"), &NoChooser)?;
+    let dao = loader.parse(&zip_path, &NoChooser)?;
+
+    let ds_root = dao.dataset_root(&dao.ds_uuid())?;
+    assert_eq!(path_file_name(&ds_root.0)?, "csv export");
+    assert!(ds_root.to_absolute("files/photo.jpg").exists());
+
+    let chat = dao.cwms_single_ds().remove(0).chat;
+    let expected_chat = expected.cwms_single_ds().remove(0).chat;
+    assert_eq!(chat.name_option, expected_chat.name_option);
+    assert_eq!(dao.first_messages(&chat, 99999)?, expected.first_messages(&expected_chat, 99999)?);
+
+    // Extracted files are removed along with the DAO
+    drop(dao);
+    assert!(!ds_root.0.exists());
+    Ok(())
+}
+
+#[test]
+fn loading_tar_gz_extracts_needed_dir_only() -> EmptyRes {
+    let src_dir = resource("telegram_2020-01");
+    let tmp_dir = TmpDir::new();
+    let tar_path = tmp_dir.path.join("export.tar.gz");
+    {
+        let mut tar = tar::Builder::new(GzEncoder::new(File::create(&tar_path)?, Compression::fast()));
+        tar.append_dir_all("nested/telegram", &src_dir)?;
+        let unrelated_path = tmp_dir.path.join("unrelated.txt");
+        fs::write(&unrelated_path, "Not a chat history")?;
+        tar.append_path_with_name(&unrelated_path, "unrelated/unrelated.txt")?;
+        tar.into_inner()?.finish()?;
+    }
+
+    let loader = Loader::new(&*HTTP_CLIENT);
+    let expected = loader.parse(&src_dir.join("result.json"), &NoChooser)?;
+    let dao = loader.parse(&tar_path, &NoChooser)?;
+
+    let ds_root = dao.dataset_root(&dao.ds_uuid())?;
+    assert!(ds_root.0.ends_with("export/nested/telegram"));
+    assert!(ds_root.to_absolute("chats/chat_01/group_photo_1.jpg").exists());
+    assert!(!ds_root.0.parent().unwrap().parent().unwrap().join("unrelated").exists());
+
+    let ds_uuid = dao.ds_uuid();
+    let expected_ds_uuid = expected.ds_uuid();
+    assert_eq!(dao.chats(&ds_uuid)?.len(), expected.chats(&expected_ds_uuid)?.len());
+    Ok(())
+}
+
+#[test]
+fn unsafe_paths() {
+    assert_eq!(sanitize(Path::new("./a/b.txt")), Some(PathBuf::from("a/b.txt")));
+    assert_eq!(sanitize(Path::new("a/../../b.txt")), None);
+    assert_eq!(sanitize(Path::new("/etc/passwd")), None);
+}
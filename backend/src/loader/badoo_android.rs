@@ -4,6 +4,7 @@ use rusqlite::Connection;
 use simd_json::prelude::*;
 
 use crate::loader::DataLoader;
+use crate::loader::sink::DatasetWriter;
 
 use super::*;
 
@@ -34,6 +35,8 @@ impl Users {
 }
 
 impl BadooAndroidDataLoader {
+    const NEEDS_NETWORK: bool = false;
+
     fn tweak_conn(&self, path: &Path, conn: &Connection) -> EmptyRes {
         conn.execute(r#"ATTACH DATABASE ?1 AS conn_db"#, [path_to_str(&path.join("CombinedConnectionsDatabase"))?])?;
         Ok(())
@@ -51,6 +54,10 @@ impl BadooAndroidDataLoader {
             last_name_option: None,
             username_option: None,
             phone_number_option: None,
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: None,
+            img_path_option: None,
         });
 
         let mut stmt = conn.prepare(r"SELECT * FROM conversation_info WHERE conversation_type = 'User'")?;
@@ -73,22 +80,30 @@ impl BadooAndroidDataLoader {
                 last_name_option: None,
                 username_option: None,
                 phone_number_option: None,
+                extra_phone_numbers: vec![],
+                emails: vec![],
+                bio_option: None,
+                img_path_option: None,
             });
         }
 
         Ok(users)
     }
 
-    fn normalize_users(&self, users: Users, _cwms: &[ChatWithMessages]) -> Result<Vec<User>> {
-        let mut users = users.user_id_to_user.into_values().collect_vec();
+    fn initial_users(&self, users: &Users) -> Result<Vec<User>> {
+        let mut users = users.user_id_to_user.values().cloned().collect_vec();
         // Set myself to be a first member.
         users.sort_by_key(|u| if u.id == *MYSELF_ID { *UserId::MIN } else { u.id });
         Ok(users)
     }
 
-    fn parse_chats(&self, conn: &Connection, ds_uuid: &PbUuid, users: &Users, path: &Path) -> Result<Vec<ChatWithMessages>> {
-        let mut cwms = vec![];
-
+    fn parse_chats(&self,
+                   conn: &Connection,
+                   ds_uuid: &PbUuid,
+                   users: &Users,
+                   path: &Path,
+                   ctx: &mut LoadContext,
+                   writer: &mut DatasetWriter) -> EmptyRes {
         let downloaded_media_path = path.join(RELATIVE_MEDIA_DIR);
         fs::create_dir_all(downloaded_media_path)?;
 
@@ -119,51 +134,13 @@ impl BadooAndroidDataLoader {
                 // TODO: if created_timestamp <> modified_timestamp, does it really mean message was edited?
 
                 // While URLs are known, following them without setting headers results in 403.
-                let (text, content_option) = {
-                    let payload_json = row.get::<_, String>("payload")?;
-                    let mut payload_bytes_vec = payload_json.as_bytes().to_vec();
-                    let parsed = simd_json::to_borrowed_value(&mut payload_bytes_vec)
-                        .with_context(|| payload_json.clone())?;
-                    let root_obj = as_object!(parsed, "root");
-                    let keys: HashSet<&str> = root_obj.keys().map(|s| s.as_ref()).collect();
-                    match row.get::<_, String>("payload_type")?.as_str() {
-                        "REACTION" => {
-                            ensure!(keys == HashSet::from(["photo_id", "photo_url", "photo_width", "photo_height",
-                                                            "photo_expiration_timestamp", "emoji_reaction", "message"]),
-                                    "Unexpected payload format for reaction to photo: {}", payload_json);
-                            let message = get_field_str!(root_obj, "message", "message");
-                            let emoji = get_field_str!(root_obj, "emoji_reaction", "emoji_reaction");
-                            (vec![RichText::make_plain(format!("{message}: {emoji}"))], None)
-                        }
-                        "AUDIO" => {
-                            ensure!(keys == HashSet::from(["id", "waveform", "url", "duration", "expiration_timestamp"]),
-                                    "Unexpected payload format for audio message: {}", payload_json);
-                            let duration_ms = get_field!(root_obj, "duration", "duration")?;
-                            let duration_sec_option = Some(duration_ms.try_as_i32()? / 1000);
-                            (vec![], Some(content::SealedValueOptional::VoiceMsg(ContentVoiceMsg {
-                                path_option: None,
-                                mime_type: "".to_string(),
-                                duration_sec_option,
-                            })))
-                        }
-                        "TEXT" => {
-                            ensure!(keys == HashSet::from(["text", "type", "substitute_id"]),
-                                    "Unexpected payload format: {}", payload_json);
-                            match get_field_str!(root_obj, "type", "type") {
-                                "TEXT" => {
-                                    let text = get_field_string!(root_obj, "text", "text");
-                                    (vec![RichText::make_plain(text)], None)
-                                }
-                                "SMILE" => {
-                                    // This is an auto-generated message, let's mark it as such
-                                    let text = get_field_string!(root_obj, "text", "text");
-                                    (vec![RichText::make_italic("(Auto-generated message)\n".to_owned()),
-                                          RichText::make_plain(text)], None)
-                                }
-                                etc => bail!("Unexpected message type {etc}!")
-                            }
-                        }
-                        etc => bail!("Unexpected payload type {etc}!")
+                let payload_json = row.get::<_, String>("payload")?;
+                let (text, content_option) = match parse_payload(&row.get::<_, String>("payload_type")?, &payload_json) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        ctx.tolerate(message_location(&name_or_unnamed(&user.first_name_option), source_id), e)?;
+                        messages.push(placeholder_message(Some(source_id), timestamp, from_id, payload_json));
+                        continue;
                     }
                 };
                 let content_option = content_option.map(|c| Content { sealed_value_optional: Some(c) });
@@ -180,13 +157,17 @@ impl BadooAndroidDataLoader {
                         forward_from_name_option: None,
                         reply_to_message_id_option,
                         content_option,
+                        extra_contents: vec![],
+                        forward_origin_option: None,
+                        ttl_sec_option: None,
                     },
                 ));
             }
             messages.iter_mut().enumerate().for_each(|(i, m)| m.internal_id = i as i64);
 
             if !messages.is_empty() {
-                cwms.push(ChatWithMessages {
+                ctx.chat_parsed(messages.len())?;
+                writer.add_chat(ChatWithMessages {
                     chat: Chat {
                         ds_uuid: ds_uuid.clone(),
                         id: user.id,
@@ -197,12 +178,63 @@ impl BadooAndroidDataLoader {
                         member_ids: vec![*MYSELF_ID, user.id],
                         msg_count: messages.len() as i32,
                         main_chat_id: None,
+                        description_option: None,
+                        created_timestamp_option: None,
+                        member_details: vec![],
                     },
                     messages,
-                });
+                }, |id| err!("User {} was not written upfront", *id))?;
             }
         }
 
-        Ok(cwms)
+        Ok(())
     }
 }
+
+fn parse_payload(payload_type: &str, payload_json: &str) -> Result<(Vec<RichTextElement>, Option<content::SealedValueOptional>)> {
+    let mut payload_bytes_vec = payload_json.as_bytes().to_vec();
+    let parsed = simd_json::to_borrowed_value(&mut payload_bytes_vec)
+        .with_context(|| payload_json.to_owned())?;
+    let root_obj = as_object!(parsed, "root");
+    let keys: HashSet<&str> = root_obj.keys().map(|s| s.as_ref()).collect();
+    let result = match payload_type {
+        "REACTION" => {
+            ensure!(keys == HashSet::from(["photo_id", "photo_url", "photo_width", "photo_height",
+                                            "photo_expiration_timestamp", "emoji_reaction", "message"]),
+                    "Unexpected payload format for reaction to photo: {}", payload_json);
+            let message = get_field_str!(root_obj, "message", "message");
+            let emoji = get_field_str!(root_obj, "emoji_reaction", "emoji_reaction");
+            (vec![RichText::make_plain(format!("{message}: {emoji}"))], None)
+        }
+        "AUDIO" => {
+            ensure!(keys == HashSet::from(["id", "waveform", "url", "duration", "expiration_timestamp"]),
+                    "Unexpected payload format for audio message: {}", payload_json);
+            let duration_ms = get_field!(root_obj, "duration", "duration")?;
+            let duration_sec_option = Some(duration_ms.try_as_i32()? / 1000);
+            (vec![], Some(content::SealedValueOptional::VoiceMsg(ContentVoiceMsg {
+                path_option: None,
+                mime_type: "".to_string(),
+                duration_sec_option,
+            })))
+        }
+        "TEXT" => {
+            ensure!(keys == HashSet::from(["text", "type", "substitute_id"]),
+                    "Unexpected payload format: {}", payload_json);
+            match get_field_str!(root_obj, "type", "type") {
+                "TEXT" => {
+                    let text = get_field_string!(root_obj, "text", "text");
+                    (vec![RichText::make_plain(text)], None)
+                }
+                "SMILE" => {
+                    // This is an auto-generated message, let's mark it as such
+                    let text = get_field_string!(root_obj, "text", "text");
+                    (vec![RichText::make_italic("(Auto-generated message)\n".to_owned()),
+                          RichText::make_plain(text)], None)
+                }
+                etc => bail!("Unexpected message type {etc}!")
+            }
+        }
+        etc => bail!("Unexpected payload type {etc}!")
+    };
+    Ok(result)
+}
@@ -10,6 +10,7 @@ use pretty_assertions::{assert_eq, assert_ne};
 use crate::{NoChooser, User};
 use crate::dao::ChatHistoryDao;
 use crate::entity_utils::*;
+use crate::loader::{LoadContext, LoadMode};
 use crate::protobuf::history::content::SealedValueOptional::*;
 use crate::protobuf::history::message::*;
 use crate::protobuf::history::message_service::SealedValueOptional::*;
@@ -41,6 +42,10 @@ fn loading_2023_12() -> EmptyRes {
         last_name_option: None,
         username_option: None,
         phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     };
 
     assert_eq!(dao.users_single_ds(), vec![myself.clone(), member.clone()]);
@@ -60,6 +65,9 @@ fn loading_2023_12() -> EmptyRes {
             member_ids: vec![myself.id, member.id],
             msg_count: 4,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: None,
+            member_details: vec![],
         });
 
         let msgs = dao.first_messages(&chat, 99999)?;
@@ -73,6 +81,9 @@ fn loading_2023_12() -> EmptyRes {
             text: vec![RichText::make_plain("Hello there!".to_owned())],
             searchable_string: "Hello there!".to_owned(),
             typed: Some(MESSAGE_REGULAR_NO_CONTENT.clone()),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[1], Message {
             internal_id: 1,
@@ -87,7 +98,13 @@ fn loading_2023_12() -> EmptyRes {
                 forward_from_name_option: None,
                 reply_to_message_id_option: Some(4313483375),
                 content_option: None,
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[2], Message {
             internal_id: 2,
@@ -108,7 +125,13 @@ fn loading_2023_12() -> EmptyRes {
                         duration_sec_option: Some(23),
                     }))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[3], Message {
             internal_id: 3,
@@ -118,12 +141,45 @@ fn loading_2023_12() -> EmptyRes {
             text: vec![RichText::make_plain("Abcde reacted to your profile: 🤔".to_owned())],
             searchable_string: "Abcde reacted to your profile: 🤔".to_owned(),
             typed: Some(MESSAGE_REGULAR_NO_CONTENT.clone()),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
     }
 
     Ok(())
 }
 
+#[test]
+fn loading_2023_12_lenient() -> EmptyRes {
+    let (res, _db_dir) = test_android::create_databases(RESOURCE_DIR, "2023-12", "", DB_FILENAME);
+    let payload = {
+        let conn = Connection::open(&res)?;
+        conn.execute("UPDATE message SET payload_type = 'GIFT' WHERE id = '4313483375'", [])?;
+        conn.query_row("SELECT payload FROM message WHERE id = '4313483375'", [], |row| row.get::<_, String>(0))?
+    };
+
+    let mut ctx = LoadContext::new(LoadMode::Strict);
+    assert!(LOADER.load_with_context(&res, &NoChooser, &mut ctx).is_err());
+
+    // Message is kept with its raw payload as a text, the rest of the chat is loaded as usual
+    let mut ctx = LoadContext::new(LoadMode::Lenient);
+    let dao = LOADER.load_with_context(&res, &NoChooser, &mut ctx)?;
+    let chat = dao.cwms_single_ds().remove(0).chat;
+    assert_eq!(chat.msg_count, 4);
+
+    let msgs = dao.first_messages(&chat, 99999)?;
+    assert_eq!(msgs[0].source_id_option, Some(4313483375));
+    assert_eq!(msgs[0].text, placeholder_text(payload));
+    assert_eq!(msgs[1].text, vec![RichText::make_plain("Reply there!".to_owned())]);
+
+    let warnings = ctx.into_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].location, "Chat Abcde, message 4313483375");
+    assert_eq!(warnings[0].message, "Unexpected payload type GIFT!");
+    Ok(())
+}
+
 //
 // Helpers
 //
@@ -136,5 +192,9 @@ fn expected_myself(ds_uuid: &PbUuid) -> User {
         last_name_option: None,
         username_option: None,
         phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     }
 }
@@ -0,0 +1,407 @@
+use std::fs;
+use std::path::{Component, PathBuf};
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+use itertools::Itertools;
+use simd_json::prelude::*;
+
+use crate::loader::{DataLoader, LoadContext};
+use crate::loader::sink::{DatasetWriter, LoadSink};
+use crate::prelude::*;
+use crate::utils::json_utils::*;
+
+#[cfg(test)]
+#[path = "generic_csv_tests.rs"]
+mod tests;
+
+const CSV_EXTENSION: &str = "csv";
+
+/// Mapping for `chat.csv` is expected to be found in `chat.mapping.json` next to it.
+const MAPPING_FILE_SUFFIX: &str = ".mapping.json";
+
+/// Loads chat logs exported as CSV by apps we have no dedicated loader for.
+/// How CSV columns are interpreted is described by a mapping file placed next to the CSV, e.g.:
+/// ```json
+/// {
+///   "delimiter": ";",
+///   "timestamp_column": "Date",
+///   "timestamp_format": "%d.%m.%Y %H:%M:%S",
+///   "timezone": "+03:00",
+///   "sender_column": "From",
+///   "text_column": "Message",
+///   "attachment_column": "File",
+///   "myself": "Me"
+/// }
+/// ```
+/// Columns are referenced by their header names.
+/// `delimiter` (defaults to `,`), `timezone` (defaults to local), `attachment_column` and `chat_name` are optional.
+/// Timezone might also be specified in timestamp itself using `%z` in the format, in which case it takes precedence.
+pub struct GenericCsvDataLoader;
+
+impl DataLoader for GenericCsvDataLoader {
+    fn name(&self) -> &'static str { "CSV (generic)" }
+
+    fn src_type(&self) -> SourceType { SourceType::TextImport }
+
+    fn file_globs(&self) -> Vec<&'static str> { vec!["*.csv"] }
+
+    fn probe_companion_globs(&self) -> Vec<&'static str> { vec!["*.mapping.json"] }
+
+    fn looks_about_right_inner(&self, path: &Path) -> EmptyRes {
+        if path.extension().and_then(|ext| ext.to_str()) != Some(CSV_EXTENSION) {
+            bail!("File {} is not a CSV file", path.display());
+        }
+        let mapping_path = mapping_path(path)?;
+        if !mapping_path.is_file() {
+            bail!("Column mapping file {} not found", mapping_path.display());
+        }
+        Ok(())
+    }
+
+    fn load_inner(&self,
+                  path: &Path,
+                  ds: Dataset,
+                  _myself_chooser: &dyn MyselfChooser,
+                  ctx: &mut LoadContext,
+                  sink: &mut dyn LoadSink) -> EmptyRes {
+        parse_csv_file(path, ds, ctx, sink)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ColumnMapping {
+    delimiter: u8,
+    timestamp_column: String,
+    timestamp_format: String,
+    timezone_option: Option<FixedOffset>,
+    sender_column: String,
+    text_column: String,
+    attachment_column_option: Option<String>,
+    myself: String,
+    chat_name_option: Option<String>,
+}
+
+/// Column indices resolved from CSV header.
+struct ColumnIndices {
+    timestamp: usize,
+    sender: usize,
+    text: usize,
+    attachment_option: Option<usize>,
+}
+
+fn mapping_path(path: &Path) -> Result<PathBuf> {
+    let file_stem = path.file_stem().and_then(|s| s.to_str())
+        .with_context(|| format!("File {} has no valid name", path.display()))?;
+    Ok(path.with_file_name(format!("{file_stem}{MAPPING_FILE_SUFFIX}")))
+}
+
+fn parse_mapping(path: &Path) -> Result<ColumnMapping> {
+    let mut file_content = fs::read(path)?;
+    let parsed = simd_json::to_borrowed_value(&mut file_content)?;
+
+    let mut delimiter = b',';
+    let mut timestamp_column = None;
+    let mut timestamp_format = None;
+    let mut timezone_option = None;
+    let mut sender_column = None;
+    let mut text_column = None;
+    let mut attachment_column_option = None;
+    let mut myself = None;
+    let mut chat_name_option = None;
+
+    parse_bw_as_object(&parsed, "mapping", |ParseCallback { key, value: v, wrong_key_action }| {
+        match key {
+            "delimiter" => {
+                let s = as_str!(v, "mapping", key);
+                ensure!(s.len() == 1, "Delimiter must be a single ASCII character, was '{s}'");
+                delimiter = s.as_bytes()[0];
+            }
+            "timestamp_column" => timestamp_column = Some(as_string!(v, "mapping", key)),
+            "timestamp_format" => timestamp_format = Some(as_string!(v, "mapping", key)),
+            "timezone" => {
+                let s = as_str!(v, "mapping", key);
+                timezone_option = Some(s.parse::<FixedOffset>()
+                    .map_err(|e| anyhow!("Invalid timezone '{s}', expected e.g. +03:00: {e}"))?);
+            }
+            "sender_column" => sender_column = Some(as_string!(v, "mapping", key)),
+            "text_column" => text_column = Some(as_string!(v, "mapping", key)),
+            "attachment_column" => attachment_column_option = as_string_option!(v, "mapping", key),
+            "myself" => myself = Some(as_string!(v, "mapping", key)),
+            "chat_name" => chat_name_option = as_string_option!(v, "mapping", key),
+            _ => return wrong_key_action()
+        }
+        Ok(())
+    })?;
+
+    Ok(ColumnMapping {
+        delimiter,
+        timestamp_column: timestamp_column.context("mapping.timestamp_column is not specified")?,
+        timestamp_format: timestamp_format.context("mapping.timestamp_format is not specified")?,
+        timezone_option,
+        sender_column: sender_column.context("mapping.sender_column is not specified")?,
+        text_column: text_column.context("mapping.text_column is not specified")?,
+        attachment_column_option,
+        myself: myself.context("mapping.myself is not specified")?,
+        chat_name_option,
+    })
+}
+
+fn parse_csv_file(path: &Path, ds: Dataset, ctx: &mut LoadContext, sink: &mut dyn LoadSink) -> EmptyRes {
+    let mapping = parse_mapping(&mapping_path(path)?)?;
+    let ds_root = path.parent().unwrap().canonicalize()?;
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(mapping.delimiter)
+        .has_headers(true)
+        .flexible(true)
+        .from_path(path)?;
+
+    let indices = {
+        let headers = reader.headers()?;
+        let find = |name: &str| headers.iter().position(|h| h.trim() == name)
+            .with_context(|| format!("Column '{name}' not found in CSV header {:?}", headers.iter().collect_vec()));
+        ColumnIndices {
+            timestamp: find(&mapping.timestamp_column)?,
+            sender: find(&mapping.sender_column)?,
+            text: find(&mapping.text_column)?,
+            attachment_option: mapping.attachment_column_option.as_deref().map(find).transpose()?,
+        }
+    };
+
+    let myself = User {
+        ds_uuid: ds.uuid.clone(),
+        id: UserId::INVALID.0 + 1,
+        first_name_option: Some(mapping.myself.clone()),
+        last_name_option: None,
+        username_option: None,
+        phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
+    };
+    let mut others: Vec<User> = vec![];
+
+    // Rows without a valid date or sender are dropped in lenient mode, as there's nothing to attribute them to
+    let file_name = path_file_name(path)?;
+    let mut messages: Vec<Message> = vec![];
+    for (row_idx, record) in reader.records().enumerate() {
+        // Header is line 1
+        let line_no = row_idx + 2;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                ctx.tolerate(super::line_location(file_name, line_no), anyhow!(e).context("Malformed row"))?;
+                continue;
+            }
+        };
+        let get = |idx: usize| record.get(idx).map(|s| s.trim()).unwrap_or_default();
+
+        let location = super::line_location(file_name, line_no);
+        let timestamp_res = parse_timestamp(get(indices.timestamp), &mapping)
+            .with_context(|| format!("Invalid timestamp '{}'", get(indices.timestamp)));
+        let sender = get(indices.sender);
+        let timestamp = match timestamp_res {
+            Ok(_) if sender.is_empty() => {
+                ctx.tolerate(location, anyhow!("Sender is empty"))?;
+                continue;
+            }
+            Ok(timestamp) => timestamp,
+            Err(e) => {
+                ctx.tolerate(location, e)?;
+                continue;
+            }
+        };
+
+        let from_id = if sender == mapping.myself {
+            myself.id()
+        } else {
+            let id = super::hash_to_id(sender);
+            if !others.iter().any(|u: &User| u.id == id) {
+                others.push(User {
+                    ds_uuid: ds.uuid.clone(),
+                    id,
+                    first_name_option: Some(sender.to_owned()),
+                    last_name_option: None,
+                    username_option: None,
+                    phone_number_option: None,
+                    extra_phone_numbers: vec![],
+                    emails: vec![],
+                    bio_option: None,
+                    img_path_option: None,
+                });
+            }
+            UserId(id)
+        };
+
+        let text = get(indices.text);
+        let text = if text.is_empty() { vec![] } else { vec![RichText::make_plain(text.to_owned())] };
+
+        let content_option = match indices.attachment_option.map(get) {
+            Some(attachment) if !attachment.is_empty() => match parse_attachment(attachment, &ds_root) {
+                Ok(content) => Some(content),
+                Err(e) => {
+                    ctx.tolerate(location, e.context(format!("Invalid attachment '{attachment}'")))?;
+                    let raw_payload = record.iter().join(&(mapping.delimiter as char).to_string());
+                    messages.push(super::placeholder_message(None, *timestamp, from_id, raw_payload));
+                    continue;
+                }
+            },
+            _ => None,
+        };
+
+        messages.push(Message::new(
+            NO_INTERNAL_ID.0,
+            None /* source_id_option */,
+            *timestamp,
+            from_id,
+            text,
+            message_regular! {
+                edit_timestamp_option: None,
+                is_deleted: false,
+                forward_from_name_option: None,
+                reply_to_message_id_option: None,
+                content_option,
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
+            },
+        ));
+    }
+
+    // Exports are not always ordered chronologically, but messages sent at the same time should keep their order.
+    messages.sort_by_key(|m| m.timestamp);
+    for (idx, m) in messages.iter_mut().enumerate() {
+        m.internal_id = idx as i64;
+    }
+
+    let file_stem = path.file_stem().unwrap().to_str().unwrap();
+    let (chat_id, tpe, default_name) = match others.as_slice() {
+        [other] => (other.id, ChatType::Personal, other.pretty_name()),
+        _ => (super::hash_to_id(file_stem), ChatType::PrivateGroup, file_stem.to_owned()),
+    };
+
+    let mut users = vec![myself];
+    users.extend(others);
+    let member_ids = users.iter().map(|u| u.id).collect_vec();
+
+    let ds_uuid = ds.uuid.clone();
+    let myself_id = users[0].id();
+    let mut writer = DatasetWriter::start(
+        sink,
+        format!("CSV ({})", path_file_name(path)?),
+        ds,
+        ds_root,
+        myself_id,
+        users,
+    )?;
+
+    ctx.chat_parsed(messages.len())?;
+    writer.add_chat(ChatWithMessages {
+        chat: Chat {
+            ds_uuid,
+            id: chat_id,
+            name_option: Some(mapping.chat_name_option.unwrap_or(default_name)),
+            source_type: SourceType::TextImport as i32,
+            tpe: tpe as i32,
+            img_path_option: None,
+            member_ids,
+            msg_count: messages.len() as i32,
+            main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: None,
+            member_details: vec![],
+        },
+        messages,
+    }, |id| err!("Unknown chat member {}", *id))
+}
+
+fn parse_timestamp(s: &str, mapping: &ColumnMapping) -> Result<Timestamp> {
+    let fmt = mapping.timestamp_format.as_str();
+    let timestamp = if fmt.contains("%z") || fmt.contains("%:z") {
+        DateTime::parse_from_str(s, fmt)?.timestamp()
+    } else {
+        let naive_dt = NaiveDateTime::parse_from_str(s, fmt)?;
+        match mapping.timezone_option {
+            Some(tz) => tz.from_local_datetime(&naive_dt).unwrap().timestamp(),
+            None => LOCAL_TZ.from_local_datetime(&naive_dt).earliest()
+                .with_context(|| format!("Local time {naive_dt} does not exist"))?.timestamp(),
+        }
+    };
+    Ok(Timestamp(timestamp))
+}
+
+/// Attachment path is resolved against the directory containing CSV.
+/// Content type is guessed by file extension, missing files are kept as path-less content.
+fn parse_attachment(attachment: &str, ds_root: &Path) -> Result<Content> {
+    use content::SealedValueOptional::*;
+
+    // Attachment should never point outside the dataset root
+    ensure!(Path::new(attachment).components().all(|c| matches!(c, Component::Normal(_))),
+            "Attachment path should be relative and contain no special components");
+
+    let file_path = ds_root.join(attachment);
+    let path_option = if file_path.is_file() {
+        Some(DatasetRoot(ds_root.to_path_buf()).to_relative(&file_path)?)
+    } else {
+        log::warn!("Attachment {} not found", file_path.display());
+        None
+    };
+    let file_name = path_file_name(&file_path)?.to_owned();
+    let ext = file_path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+
+    let content_value = match ext.as_str() {
+        "jpg" | "jpeg" | "png" | "gif" | "bmp" => Photo(ContentPhoto {
+            path_option,
+            width: 0,
+            height: 0,
+            is_one_time: false,
+        }),
+        "webp" => Sticker(ContentSticker {
+            path_option,
+            width: 0,
+            height: 0,
+            thumbnail_path_option: None,
+            emoji_option: None,
+        }),
+        "mp4" | "mov" | "webm" => Video(ContentVideo {
+            path_option,
+            title_option: None,
+            performer_option: None,
+            width: 0,
+            height: 0,
+            mime_type: match ext.as_str() {
+                "mov" => "video/quicktime",
+                "webm" => "video/webm",
+                _ => "video/mp4",
+            }.to_owned(),
+            duration_sec_option: None,
+            thumbnail_path_option: None,
+            is_one_time: false,
+        }),
+        "opus" | "ogg" => VoiceMsg(ContentVoiceMsg {
+            path_option,
+            mime_type: "audio/ogg".to_owned(),
+            duration_sec_option: None,
+        }),
+        "mp3" | "m4a" | "wav" => Audio(ContentAudio {
+            path_option,
+            title_option: None,
+            performer_option: None,
+            mime_type: match ext.as_str() {
+                "m4a" => "audio/mp4",
+                "wav" => "audio/wav",
+                _ => "audio/mpeg",
+            }.to_owned(),
+            duration_sec_option: None,
+            thumbnail_path_option: None,
+        }),
+        _ => File(ContentFile {
+            path_option,
+            file_name_option: Some(file_name),
+            mime_type_option: None,
+            thumbnail_path_option: None,
+        }),
+    };
+    Ok(Content { sealed_value_optional: Some(content_value) })
+}
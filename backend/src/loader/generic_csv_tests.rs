@@ -0,0 +1,199 @@
+#![allow(unused_imports)]
+
+use chrono::prelude::*;
+use pretty_assertions::{assert_eq, assert_ne};
+
+use crate::{NoChooser, User};
+use crate::dao::ChatHistoryDao;
+use crate::entity_utils::*;
+use crate::loader::{LoadMode, placeholder_text};
+use crate::protobuf::history::content::SealedValueOptional::*;
+use crate::protobuf::history::message::*;
+
+use super::*;
+
+const LOADER: GenericCsvDataLoader = GenericCsvDataLoader;
+
+//
+// Tests
+//
+
+#[test]
+fn loading_2026_10() -> EmptyRes {
+    let res = resource("csv_2026-10/chat.csv");
+    LOADER.looks_about_right(&res)?;
+
+    let dao = LOADER.load(&res, &NoChooser)?;
+
+    let ds_uuid = &dao.ds_uuid();
+    let myself = dao.myself_single_ds();
+    assert_eq!(myself, User {
+        ds_uuid: ds_uuid.clone(),
+        id: 1_i64,
+        first_name_option: Some("Me".to_owned()),
+        last_name_option: None,
+        username_option: None,
+        phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
+    });
+
+    let member = User {
+        ds_uuid: ds_uuid.clone(),
+        id: super::super::hash_to_id("Alice"),
+        first_name_option: Some("Alice".to_owned()),
+        last_name_option: None,
+        username_option: None,
+        phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
+    };
+
+    assert_eq!(dao.users_single_ds(), vec![myself.clone(), member.clone()]);
+
+    assert_eq!(dao.cwms_single_ds().len(), 1);
+
+    let cwm = dao.cwms_single_ds().remove(0);
+    let chat = cwm.chat;
+    assert_eq!(chat, Chat {
+        ds_uuid: ds_uuid.clone(),
+        id: member.id,
+        name_option: Some("Alice".to_owned()),
+        source_type: SourceType::TextImport as i32,
+        tpe: ChatType::Personal as i32,
+        img_path_option: None,
+        member_ids: vec![myself.id, member.id],
+        msg_count: 5,
+        main_chat_id: None,
+        description_option: None,
+        created_timestamp_option: None,
+        member_details: vec![],
+    });
+
+    let msgs = dao.first_messages(&chat, 99999)?;
+    assert_eq!(msgs.len(), 5);
+
+    let offset = FixedOffset::east_opt(3 * 3600).unwrap();
+    let regular = |content_option: Option<Content>| message_regular! {
+        edit_timestamp_option: None,
+        is_deleted: false,
+        forward_from_name_option: None,
+        reply_to_message_id_option: None,
+        content_option,
+        extra_contents: vec![],
+        forward_origin_option: None,
+        ttl_sec_option: None,
+    };
+
+    assert_eq!(msgs[0], Message::new(
+        0, None, dt("2026-05-01 10:00:00", Some(&offset)).timestamp(), myself.id(),
+        vec![RichText::make_plain("Hi there!".to_owned())], regular(None),
+    ));
+    assert_eq!(msgs[1], Message::new(
+        1, None, dt("2026-05-01 10:00:30", Some(&offset)).timestamp(), member.id(),
+        vec![RichText::make_plain("Hello!\nHow are you?".to_owned())], regular(None),
+    ));
+    assert_eq!(msgs[2], Message::new(
+        2, None, dt("2026-05-01 10:01:00", Some(&offset)).timestamp(), member.id(),
+        vec![RichText::make_plain("Look at this".to_owned())],
+        regular(Some(Content {
+            sealed_value_optional: Some(Photo(ContentPhoto {
+                path_option: Some("files/photo.jpg".to_owned()),
+                width: 0,
+                height: 0,
+                is_one_time: false,
+            }))
+        })),
+    ));
+    assert_eq!(msgs[3], Message::new(
+        3, None, dt("2026-05-01 10:02:00", Some(&offset)).timestamp(), myself.id(),
+        vec![],
+        regular(Some(Content {
+            sealed_value_optional: Some(File(ContentFile {
+                path_option: Some("files/report.pdf".to_owned()),
+                file_name_option: Some("report.pdf".to_owned()),
+                mime_type_option: None,
+                thumbnail_path_option: None,
+            }))
+        })),
+    ));
+    assert_eq!(msgs[4], Message::new(
+        4, None, dt("2026-05-01 10:03:00", Some(&offset)).timestamp(), myself.id(),
+        vec![RichText::make_plain("Missing one".to_owned())],
+        regular(Some(Content {
+            sealed_value_optional: Some(Video(ContentVideo {
+                path_option: None,
+                title_option: None,
+                performer_option: None,
+                width: 0,
+                height: 0,
+                mime_type: "video/mp4".to_owned(),
+                duration_sec_option: None,
+                thumbnail_path_option: None,
+                is_one_time: false,
+            }))
+        })),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn attachment_outside_of_root() -> EmptyRes {
+    let tmp_dir = TmpDir::new();
+    let csv_path = tmp_dir.path.join("chat.csv");
+    fs::copy(resource("csv_2026-10/chat.mapping.json"), tmp_dir.path.join("chat.mapping.json"))?;
+    for attachment in ["../photo.jpg", "files/../../photo.jpg", "/etc/passwd"] {
+        fs::write(&csv_path, format!("Date;From;Message;File\n01.05.2026 10:00:00;Me;Hi;{attachment}\n"))?;
+        let err = LOADER.load(&csv_path, &NoChooser).err().map(|e| format!("{e:?}"));
+        assert!(err.is_some_and(|e| e.contains("Invalid attachment")), "{attachment} was accepted");
+    }
+    Ok(())
+}
+
+#[test]
+fn loading_lenient() -> EmptyRes {
+    let tmp_dir = TmpDir::new();
+    let csv_path = tmp_dir.path.join("chat.csv");
+    fs::copy(resource("csv_2026-10/chat.mapping.json"), tmp_dir.path.join("chat.mapping.json"))?;
+    fs::write(&csv_path, "Date;From;Message;File\n\
+                          01.05.2026 10:00:00;Me;Hi;\n\
+                          yesterday;Alice;Bad date;\n\
+                          01.05.2026 10:01:00;;No sender;\n\
+                          01.05.2026 10:02:00;Alice;Bad file;/etc/passwd\n")?;
+
+    let mut ctx = LoadContext::new(LoadMode::Strict);
+    assert!(LOADER.load_with_context(&csv_path, &NoChooser, &mut ctx).is_err());
+
+    // Rows without date or sender are dropped, row with a bad attachment is kept as a raw text
+    let mut ctx = LoadContext::new(LoadMode::Lenient);
+    let dao = LOADER.load_with_context(&csv_path, &NoChooser, &mut ctx)?;
+    let chat = dao.cwms_single_ds().remove(0).chat;
+    assert_eq!(chat.msg_count, 2);
+
+    let msgs = dao.first_messages(&chat, 99999)?;
+    assert_eq!(msgs[0].text, vec![RichText::make_plain("Hi".to_owned())]);
+    assert_eq!(msgs[1].text, placeholder_text("01.05.2026 10:02:00;Alice;Bad file;/etc/passwd".to_owned()));
+    assert_eq!(msgs[1].timestamp, msgs[0].timestamp + 120);
+
+    let warnings = ctx.into_warnings();
+    assert_eq!(warnings.iter().map(|w| w.location.as_str()).collect_vec(),
+               vec!["chat.csv, line 3", "chat.csv, line 4", "chat.csv, line 5"]);
+    assert!(warnings[0].message.starts_with("Invalid timestamp 'yesterday'"));
+    assert_eq!(warnings[1].message, "Sender is empty");
+    assert!(warnings[2].message.starts_with("Invalid attachment '/etc/passwd'"));
+    Ok(())
+}
+
+#[test]
+fn no_mapping_file() -> EmptyRes {
+    let tmp_dir = TmpDir::new();
+    let csv_path = tmp_dir.path.join("chat.csv");
+    fs::write(&csv_path, "Date,From,Message\n")?;
+    assert!(LOADER.looks_about_right(&csv_path).is_err());
+    Ok(())
+}
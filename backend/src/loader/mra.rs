@@ -15,8 +15,10 @@ use utf16string::{LE, WStr};
 use content::SealedValueOptional as ContentSvo;
 use message_service::SealedValueOptional as ServiceSvo;
 
-use crate::dao::in_memory_dao::{DatasetEntry, InMemoryDao};
-use crate::loader::DataLoader;
+use crate::dao::in_memory_dao::DatasetEntry;
+use crate::loader;
+use crate::loader::{DataLoader, LoadContext};
+use crate::loader::sink::LoadSink;
 use crate::prelude::*;
 
 mod mra_dbs;
@@ -41,6 +43,8 @@ lazy_static! {
     // Expected entries are @mail.ru, @bk.ru, @inbox.ru and @uin.icq.
     // Could also be @chat.agent, which indicates a group chat.
     static ref EMAIL_REGEX: Regex = Regex::new(r"^[a-zA-Z0-9._-]+@([a-z-]+\.)+[a-z]+$").unwrap();
+    // SMS contacts are identified by a phone number
+    static ref PHONE_REGEX: Regex = Regex::new(r"^\+?[0-9]{7,15}$").unwrap();
 
     static ref SMILE_TAG_REGEX: Regex = Regex::new(r"<SMILE>id=(?<id>[^ ]+)( alt='(?<alt>[^']+)')?</SMILE>").unwrap();
     static ref SMILE_INLINE_REGEX: Regex = Regex::new(r":(([А-ЯË][^:\n]+)|([0-9]{3,})):").unwrap();
@@ -54,6 +58,8 @@ impl DataLoader for MailRuAgentDataLoader {
 
     fn src_type(&self) -> SourceType { SourceType::Mra }
 
+    fn file_globs(&self) -> Vec<&'static str> { vec![MRA_DBS] }
+
     fn looks_about_right_inner(&self, path: &Path) -> EmptyRes {
         if path_file_name(path)? != MRA_DBS {
             bail!("Given file is not {MRA_DBS}")
@@ -61,13 +67,14 @@ impl DataLoader for MailRuAgentDataLoader {
         Ok(())
     }
 
-    fn load_inner(&self, path: &Path, ds: Dataset, _myself_chooser: &dyn MyselfChooser) -> Result<Box<InMemoryDao>> {
+    fn load_inner(&self, path: &Path, ds: Dataset, _myself_chooser: &dyn MyselfChooser,
+                  ctx: &mut LoadContext, sink: &mut dyn LoadSink) -> EmptyRes {
         // We're not using the supplied dataset, just the name of it
-        load_mra_dbs(path, ds.alias)
+        load_mra_dbs(path, ds.alias, ctx, sink)
     }
 }
 
-fn load_mra_dbs(path: &Path, dao_name: String) -> Result<Box<InMemoryDao>> {
+fn load_mra_dbs(path: &Path, dao_name: String, ctx: &mut LoadContext, sink: &mut dyn LoadSink) -> EmptyRes {
     let parent_path = path.parent().expect("Database file has no parent!");
     let storage_path = if path_file_name(parent_path)? == "Base" {
         parent_path.parent().expect(r#""Base" directory has no parent!"#)
@@ -91,14 +98,14 @@ fn load_mra_dbs(path: &Path, dao_name: String) -> Result<Box<InMemoryDao>> {
     dataset_map.extend(dataset_map_2);
 
     // Phase 3: Convert conversations to our format.
-    mra_dbs::convert_messages(&convs_with_msgs, &mut dataset_map)?;
+    mra_dbs::convert_messages(&convs_with_msgs, &mut dataset_map, ctx)?;
 
     log::info!("Loading .db (newer) format");
     let mut conv_maps = HashMap::new();
     for subdir in DB_FILE_DIRS.iter() {
         let path = parent_path.join(subdir);
         if path.exists() {
-            let conv_map = db::load_accounts_dir(&path, &storage_path, &mut dataset_map)?;
+            let conv_map = db::load_accounts_dir(&path, &storage_path, &mut dataset_map, ctx)?;
             conv_maps.extend(conv_map);
         }
     }
@@ -109,12 +116,29 @@ fn load_mra_dbs(path: &Path, dao_name: String) -> Result<Box<InMemoryDao>> {
 
     db::merge_conversations(conv_maps, &mut dataset_map)?;
 
-    let data = dataset_map_to_dao_data(dataset_map);
-    Ok(Box::new(InMemoryDao::new(
-        dao_name,
-        storage_path,
-        data,
-    )))
+    sink.set_dao_info(dao_name, storage_path);
+    for DatasetEntry { ds, ds_root, myself_id, users, cwms } in dataset_map_to_dao_data(dataset_map) {
+        sink.start_dataset(ds, ds_root, myself_id, users)?;
+        for cwm in cwms {
+            sink.add_chat_with_messages(cwm)?;
+        }
+    }
+    Ok(())
+}
+
+/// Placeholder for a message that couldn't be converted in lenient mode.
+fn placeholder_message(
+    internal_id: i64,
+    source_id_option: Option<i64>,
+    timestamp: i64,
+    from_username: &str,
+    users: &HashMap<String, User>,
+    raw_payload: String,
+) -> Message {
+    let from_id = users.get(from_username).map(|u| u.id()).unwrap_or(MYSELF_ID);
+    let mut msg = loader::placeholder_message(source_id_option, timestamp, from_id, raw_payload);
+    msg.internal_id = internal_id;
+    msg
 }
 
 /// Old MRA format has invalid timezone while newer format treats timezone properly.
@@ -554,17 +578,28 @@ fn upsert_user(users: &mut HashMap<String, User>,
                ds_uuid: &PbUuid,
                username: &str,
                first_name_or_email: Option<String>) {
-    let user = users.entry(username.to_owned()).or_insert_with(|| User {
+    let user = users.entry(username.to_owned()).or_insert_with(|| new_user(ds_uuid, loader::hash_to_id(username), username));
+
+    if user.first_name_option.is_none() && first_name_or_email.as_ref().is_some_and(|v| v != username) {
+        user.first_name_option = first_name_or_email;
+    }
+}
+
+/// Username is either an e-mail (apart from ICQ UINs and conferences) or, for SMS contacts, a phone number.
+fn new_user(ds_uuid: &PbUuid, id: i64, username: &str) -> User {
+    let is_email = EMAIL_REGEX.is_match(username) &&
+        !username.ends_with("@uin.icq") && !username.ends_with("@chat.agent");
+    User {
         ds_uuid: ds_uuid.clone(),
-        id: loader::hash_to_id(username),
+        id,
         first_name_option: None,
         last_name_option: None,
         username_option: Some(username.to_owned()),
-        phone_number_option: None,
-    });
-
-    if user.first_name_option.is_none() && first_name_or_email.as_ref().is_some_and(|v| v != username) {
-        user.first_name_option = first_name_or_email;
+        phone_number_option: Some(username.to_owned()).filter(|u| PHONE_REGEX.is_match(u)),
+        extra_phone_numbers: vec![],
+        emails: if is_email { vec![username.to_owned()] } else { vec![] },
+        bio_option: None,
+        img_path_option: None,
     }
 }
 
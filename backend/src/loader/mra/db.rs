@@ -23,6 +23,7 @@ pub(super) fn load_accounts_dir(
     path: &Path,
     storage_path: &Path,
     dataset_map: &mut DatasetMap,
+    ctx: &mut LoadContext,
 ) -> Result<HashMap<String, ConversationsMap>> {
     let mut result: HashMap<_, _> = Default::default();
     for dir_entry in fs::read_dir(path)? {
@@ -37,7 +38,7 @@ pub(super) fn load_accounts_dir(
                 users: Default::default(),
                 cwms: Default::default(),
             });
-            let conv_map = load_account(name, &entry.ds.uuid, &path, &mut entry.users)?;
+            let conv_map = load_account(name, &entry.ds.uuid, &path, &mut entry.users, ctx)?;
             result.insert(name.to_owned(), conv_map);
         } else {
             log::warn!("{} is not a directory, ignored", name);
@@ -51,15 +52,9 @@ fn load_account(
     ds_uuid: &PbUuid,
     path: &Path,
     users: &mut HashMap<String, User>,
+    ctx: &mut LoadContext,
 ) -> Result<ConversationsMap> {
-    let myself = User {
-        ds_uuid: ds_uuid.clone(),
-        id: *MYSELF_ID,
-        first_name_option: None,
-        last_name_option: None,
-        username_option: Some(myself_username.to_owned()),
-        phone_number_option: None,
-    };
+    let myself = new_user(ds_uuid, *MYSELF_ID, myself_username);
 
     // Read whole files into the memory
     let mut db_msgs_map: HashMap<String, Vec<DbMessage>> = Default::default();
@@ -99,7 +94,7 @@ fn load_account(
 
     let mut result: ConversationsMap = Default::default();
     for (conv_username, db_msgs) in db_msgs_map.into_iter() {
-        let (new_msgs, interlocutor_ids) = process_conversation(&db_msgs, myself_username, &conv_username, users)?;
+        let (new_msgs, interlocutor_ids) = process_conversation(&db_msgs, myself_username, &conv_username, users, ctx)?;
         result.insert(conv_username, (new_msgs, interlocutor_ids));
     }
     Ok(result)
@@ -426,20 +421,36 @@ fn process_conversation(
     myself_username: &str,
     conv_username: &str,
     users: &HashMap<String, User>,
+    ctx: &mut LoadContext,
 ) -> Result<(Vec<Message>, HashSet<UserId>)> {
     let mut msgs: Vec<Message> = vec![];
     let mut ongoing_call_msg_id = None;
     let mut interlocutor_ids = HashSet::from([MYSELF_ID]);
     let mut internal_id = 0;
     for mra_msg in db_msgs {
-        if let Some(msg) = convert_message(mra_msg, internal_id, myself_username, conv_username, users,
-                                           &mut msgs, &mut ongoing_call_msg_id)? {
+        let converted = convert_message(mra_msg, internal_id, myself_username, conv_username, users,
+                                        &mut msgs, &mut ongoing_call_msg_id);
+        let converted = match converted {
+            Ok(msg_option) => msg_option,
+            Err(e) => {
+                ctx.tolerate(loader::message_location(&format!("{myself_username} with {conv_username}"), format!("{:#010x}", mra_msg.offset)), e)?;
+                let from_me = mra_msg.is_from_me().unwrap_or(false);
+                Some(placeholder_message(internal_id,
+                                         None,
+                                         filetime_to_timestamp(mra_msg.header.filetime),
+                                         if from_me { myself_username } else { conv_username },
+                                         users,
+                                         format!("{mra_msg:?}")))
+            }
+        };
+        if let Some(msg) = converted {
             interlocutor_ids.insert(UserId(msg.from_id));
             msgs.push(msg);
             internal_id += 1;
         }
     }
 
+    ctx.chat_parsed(msgs.len())?;
     Ok((msgs, interlocutor_ids))
 }
 
@@ -785,6 +796,9 @@ pub(super) fn merge_conversations(
                         member_ids: vec![], // Will be changed later
                         msg_count: -1, // Will be changed later
                         main_chat_id: None,
+                        description_option: None,
+                        created_timestamp_option: None,
+                        member_details: vec![],
                     },
                     messages: vec![],
                 }
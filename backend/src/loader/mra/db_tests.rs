@@ -2,6 +2,7 @@
 
 use pretty_assertions::{assert_eq, assert_ne};
 
+use crate::loader::{LoadContext, LoadMode};
 use crate::prelude::*;
 
 use super::*;
@@ -125,3 +126,50 @@ fn sort_messages() -> EmptyRes {
 
     Ok(())
 }
+
+#[test]
+fn lenient_unknown_message_type() -> EmptyRes {
+    let db_msgs = vec![DbMessage {
+        offset: 0x00001000,
+        header: DbMessageHeader { tpe_u8: 0xFF, ..Default::default() },
+        payload: vec![0x01],
+        ..Default::default()
+    }];
+    let users = HashMap::new();
+
+    let mut ctx = LoadContext::new(LoadMode::Strict);
+    assert!(process_conversation(&db_msgs, "me@mail.ru", "other@mail.ru", &users, &mut ctx).is_err());
+
+    // Message is replaced by a placeholder, with a warning pointing to it
+    let mut ctx = LoadContext::new(LoadMode::Lenient);
+    let (msgs, interlocutor_ids) = process_conversation(&db_msgs, "me@mail.ru", "other@mail.ru", &users, &mut ctx)?;
+    assert_eq!(msgs.len(), 1);
+    assert_eq!(msgs[0].internal_id, 0);
+    assert_eq!(msgs[0].from_id, MYSELF_ID.0);
+    assert_eq!(msgs[0].text, loader::placeholder_text(format!("{:?}", db_msgs[0])));
+    assert_eq!(interlocutor_ids, HashSet::from([MYSELF_ID]));
+
+    let warnings = ctx.into_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].location, "Chat me@mail.ru with other@mail.ru, message 0x00001000");
+    assert!(warnings[0].message.contains("Unknown message type: 0xff"));
+    Ok(())
+}
+
+#[test]
+fn user_details_from_username() {
+    let ds_uuid = PbUuid::random();
+    let user = new_user(&ds_uuid, 1, "someone@mail.ru");
+    assert_eq!(user.emails, vec!["someone@mail.ru".to_owned()]);
+    assert_eq!(user.phone_number_option, None);
+
+    let user = new_user(&ds_uuid, 2, "+79001234567");
+    assert_eq!(user.emails, Vec::<String>::new());
+    assert_eq!(user.phone_number_option, Some("+79001234567".to_owned()));
+
+    for username in ["123456789@uin.icq", "12345@chat.agent"] {
+        let user = new_user(&ds_uuid, 3, username);
+        assert_eq!(user.emails, Vec::<String>::new());
+        assert_eq!(user.phone_number_option, None);
+    }
+}
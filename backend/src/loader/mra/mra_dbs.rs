@@ -186,14 +186,7 @@ pub(super) fn collect_datasets(
 
         result.entry(myself_username.clone()).or_insert_with(|| {
             let ds_uuid = PbUuid::random();
-            let myself = User {
-                ds_uuid: ds_uuid.clone(),
-                id: *MYSELF_ID,
-                first_name_option: None,
-                last_name_option: None,
-                username_option: Some(myself_username.clone()),
-                phone_number_option: None,
-            };
+            let myself = new_user(&ds_uuid, *MYSELF_ID, &myself_username);
             MraDatasetEntry {
                 ds: Dataset { uuid: ds_uuid, alias: myself_username.clone() },
                 ds_root: storage_path.to_path_buf(),
@@ -260,6 +253,7 @@ pub(super) fn collect_datasets(
 pub(super) fn convert_messages(
     convs_with_msgs: &[MraLegacyConversationWithMessages],
     dataset_map: &mut DatasetMap,
+    ctx: &mut LoadContext,
 ) -> EmptyRes {
     for conv_w_msgs in convs_with_msgs.iter() {
         let myself_username = conv_w_msgs.conv.myself_username.to_utf8();
@@ -278,8 +272,22 @@ pub(super) fn convert_messages(
         let mut ongoing_call_msg_id = None;
         let mut interlocutor_ids = HashSet::from([*MYSELF_ID]);
         for mra_msg in conv_w_msgs.msgs.iter() {
-            if let Some(msg) = convert_message(mra_msg, internal_id, &myself_username, &conv_username, &entry.users,
-                                               &mut msgs, &mut ongoing_call_msg_id)? {
+            let converted = convert_message(mra_msg, internal_id, &myself_username, &conv_username, &entry.users,
+                                            &mut msgs, &mut ongoing_call_msg_id);
+            let converted = match converted {
+                Ok(msg_option) => msg_option,
+                Err(e) => {
+                    ctx.tolerate(loader::message_location(&format!("{myself_username} with {conv_username}"), format!("#{}", mra_msg.sequential_id)), e)?;
+                    let from_me = mra_msg.is_from_me().unwrap_or(false);
+                    Some(placeholder_message(internal_id,
+                                             Some(mra_msg.sequential_id as i64),
+                                             filetime_to_timestamp(mra_msg.header.filetime_wrong_tz),
+                                             if from_me { &myself_username } else { &conv_username },
+                                             &entry.users,
+                                             format!("{mra_msg:?}")))
+                }
+            };
+            if let Some(msg) = converted {
                 interlocutor_ids.insert(msg.from_id);
                 msgs.push(msg);
                 internal_id += 1;
@@ -306,6 +314,7 @@ pub(super) fn convert_messages(
             conv_username.clone()
         };
 
+        ctx.chat_parsed(msgs.len())?;
         entry.cwms.insert(conv_username.clone(), ChatWithMessages {
             chat: Chat {
                 ds_uuid: entry.ds.uuid.clone(),
@@ -317,6 +326,9 @@ pub(super) fn convert_messages(
                 member_ids,
                 msg_count: msgs.len() as i32,
                 main_chat_id: None,
+                description_option: None,
+                created_timestamp_option: None,
+                member_details: vec![],
             },
             messages: msgs,
         });
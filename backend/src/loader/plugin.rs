@@ -0,0 +1,239 @@
+use std::fs;
+use std::io::{BufReader, ErrorKind, Read};
+use std::process::{Command, Output, Stdio};
+use std::thread;
+
+use itertools::Itertools;
+use prost::Message as ProstMessage;
+
+use crate::loader::{DataLoader, glob_matches, LoadContext, LoadMode};
+use crate::loader::sink::{DatasetWriter, LoadSink};
+use crate::prelude::*;
+
+#[cfg(test)]
+#[path = "plugin_tests.rs"]
+mod tests;
+
+/// Loader delegating to an external executable, allowing to support formats that can't be built into the backend.
+///
+/// Plugin is any executable file in a plugins directory, invoked as:
+/// * `<plugin> info` - writes a protobuf-encoded `LoaderInfo` to stdout, called once on discovery.
+///   Myself chooser is not available to plugins, so `needs_myself_chooser` is ignored.
+///   Plugin has to declare file globs, only files matching them are offered to it.
+/// * `<plugin> check <path>` - exits with zero code if the file could be loaded by this plugin,
+///   otherwise exits with non-zero code and writes the reason to stderr.
+/// * `<plugin> load <path> [--lenient]` - writes a protobuf-encoded `PluginLoadResult` to stdout.
+///   Myself should be the first user, paths should be relative to the loaded file directory.
+///   Output is consumed as it's being written, chat by chat, so users have to go before chats
+///   (which is what protobuf encoders do anyway).
+///   Dataset UUIDs are ignored and can be left empty. On failure, exits with non-zero code and writes the
+///   error to stderr. With `--lenient`, problems that could be worked around should be reported as warnings.
+pub struct PluginDataLoader {
+    path: PathBuf,
+    name: String,
+    file_globs: Vec<String>,
+    src_type: SourceType,
+    needs_network: bool,
+}
+
+impl PluginDataLoader {
+    pub fn discover(plugins_dir: &Path) -> Result<Vec<PluginDataLoader>> {
+        let mut result = vec![];
+        for entry in fs::read_dir(plugins_dir)? {
+            let path = entry?.path();
+            if !is_executable(&path)? { continue; }
+            match PluginDataLoader::new(&path) {
+                Ok(plugin) => {
+                    log::info!("Loaded plugin {} from {}", plugin.name, path.display());
+                    result.push(plugin)
+                }
+                Err(e) => log::warn!("Skipping plugin {}: {:#}", path.display(), e),
+            }
+        }
+        result.sort_by_key(|plugin| plugin.path.clone());
+        Ok(result)
+    }
+
+    fn new(path: &Path) -> Result<PluginDataLoader> {
+        let output = run(path, &["info"])?;
+        let info = LoaderInfo::decode(output.stdout.as_slice()).context("Malformed plugin info")?;
+        ensure!(!info.name.is_empty(), "Plugin has no name");
+        ensure!(!info.file_globs.is_empty(), "Plugin declares no file globs");
+        let src_type = match info.src_type_option {
+            Some(v) => SourceType::try_from(v).map_err(|_| anyhow!("Unknown source type {v}"))?,
+            None => SourceType::TextImport,
+        };
+        Ok(PluginDataLoader {
+            path: path.to_path_buf(),
+            name: info.name,
+            file_globs: info.file_globs,
+            src_type,
+            needs_network: info.needs_network,
+        })
+    }
+}
+
+impl DataLoader for PluginDataLoader {
+    fn name(&self) -> &str { &self.name }
+
+    fn src_type(&self) -> SourceType { self.src_type }
+
+    fn file_globs(&self) -> Vec<&str> { self.file_globs.iter().map(|g| g.as_str()).collect_vec() }
+
+    fn needs_network(&self) -> bool { self.needs_network }
+
+    fn looks_about_right_inner(&self, path: &Path) -> EmptyRes {
+        // Spawning a process is expensive, so plugin is only asked about the files it might accept
+        let filename = path_file_name(path)?;
+        if !self.file_globs.iter().any(|glob| glob_matches(glob, filename)) {
+            bail!("File name doesn't match {}", self.file_globs.join(", "));
+        }
+        run(&self.path, &["check", path_to_str(path)?])?;
+        Ok(())
+    }
+
+    fn load_inner(&self,
+                  path: &Path,
+                  ds: Dataset,
+                  _myself_chooser: &dyn MyselfChooser,
+                  ctx: &mut LoadContext,
+                  sink: &mut dyn LoadSink) -> EmptyRes {
+        let mut args = vec!["load", path_to_str(path)?];
+        if ctx.mode() == LoadMode::Lenient {
+            args.push("--lenient");
+        }
+        let mut child = Command::new(&self.path).args(&args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to run plugin {}", self.path.display()))?;
+        // Reading stderr in background so that plugin doesn't block on it while we're reading stdout
+        let mut stderr = child.stderr.take().unwrap();
+        let stderr_handle = thread::spawn(move || {
+            let mut stderr_str = String::new();
+            let _ = stderr.read_to_string(&mut stderr_str);
+            stderr_str
+        });
+
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let written = self.write_output(&mut stdout, path, ds, ctx, sink);
+        if written.is_err() {
+            let _ = child.kill();
+        }
+        let status = child.wait()?;
+        let stderr = stderr_handle.join().unwrap_or_default();
+        match written {
+            _ if !status.success() && !stderr.trim().is_empty() => bail!("{}", stderr.trim()),
+            Ok(()) if !status.success() => bail!("Plugin exited with {status}"),
+            written => written,
+        }
+    }
+}
+
+impl PluginDataLoader {
+    /// Decodes `PluginLoadResult` field by field, writing each chat as soon as it's read.
+    fn write_output(&self,
+                    stdout: &mut impl Read,
+                    path: &Path,
+                    ds: Dataset,
+                    ctx: &mut LoadContext,
+                    sink: &mut dyn LoadSink) -> EmptyRes {
+        const USERS_TAG: u32 = 1;
+        const CWMS_TAG: u32 = 2;
+        const WARNINGS_TAG: u32 = 3;
+
+        let ds_uuid = ds.uuid.clone();
+        let mut users = vec![];
+        let mut next_field = read_field(stdout)?;
+        while let Some((USERS_TAG, bytes)) = next_field {
+            users.push(User { ds_uuid: ds_uuid.clone(), ..User::decode(bytes.as_slice()).context("Malformed plugin user")? });
+            next_field = read_field(stdout)?;
+        }
+        ensure!(!users.is_empty(), "Plugin returned no users");
+
+        let myself_id = users[0].id();
+        let mut writer = DatasetWriter::start(
+            sink,
+            format!("{} ({})", self.name, path_file_name(path)?),
+            ds,
+            self.root_dir(path).to_path_buf(),
+            myself_id,
+            users,
+        )?;
+        while let Some((tag, bytes)) = next_field {
+            match tag {
+                USERS_TAG => bail!("Plugin returned users after chats"),
+                CWMS_TAG => {
+                    let mut cwm = ChatWithMessages::decode(bytes.as_slice()).context("Malformed plugin chat")?;
+                    cwm.chat.ds_uuid = ds_uuid.clone();
+                    cwm.chat.msg_count = cwm.messages.len() as i32;
+                    cwm.messages.iter_mut().enumerate().for_each(|(i, m)| m.internal_id = i as i64);
+                    ctx.chat_parsed(cwm.messages.len())?;
+                    writer.add_chat(cwm, |id| err!("Plugin returned no user with ID {}", *id))?;
+                }
+                WARNINGS_TAG => {
+                    let LoadWarning { location, message } =
+                        LoadWarning::decode(bytes.as_slice()).context("Malformed plugin warning")?;
+                    ctx.tolerate(location, anyhow!(message))?;
+                }
+                _ => { /* Unknown field, ignoring */ }
+            }
+            next_field = read_field(stdout)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the next length-delimited field of a protobuf message, returning its tag and content,
+/// or `None` if input has ended.
+fn read_field(input: &mut impl Read) -> Result<Option<(u32, Vec<u8>)>> {
+    let Some(key) = read_varint(input)? else { return Ok(None) };
+    const LENGTH_DELIMITED: u64 = 2;
+    ensure!(key & 0b111 == LENGTH_DELIMITED, "Malformed plugin output: unexpected wire type in key {key}");
+    let len = read_varint(input)?.context("Malformed plugin output: field length is missing")?;
+    // Plugin output isn't trusted, so huge lengths are rejected rather than allocated upfront
+    const MAX_FIELD_LENGTH: u64 = 1 << 30;
+    ensure!(len <= MAX_FIELD_LENGTH, "Malformed plugin output: field length {len} exceeds {MAX_FIELD_LENGTH}");
+    let mut bytes = vec![0; len as usize];
+    input.read_exact(&mut bytes).context("Malformed plugin output: field is truncated")?;
+    Ok(Some(((key >> 3) as u32, bytes)))
+}
+
+fn read_varint(input: &mut impl Read) -> Result<Option<u64>> {
+    let mut result = 0_u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0_u8];
+        match input.read_exact(&mut byte) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof && shift == 0 => return Ok(None),
+            other => other.context("Malformed plugin output: varint is truncated")?,
+        }
+        result |= ((byte[0] & 0x7F) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(result));
+        }
+    }
+    bail!("Malformed plugin output: varint is too long")
+}
+
+/// Runs plugin with given arguments, failing with plugin's stderr if it exits with non-zero code.
+fn run(path: &Path, args: &[&str]) -> Result<Output> {
+    let output = Command::new(path).args(args).output()
+        .with_context(|| format!("Failed to run plugin {}", path.display()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("{}", stderr.trim());
+    }
+    Ok(output)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> Result<bool> {
+    use std::os::unix::fs::PermissionsExt;
+    let meta = fs::metadata(path)?;
+    Ok(meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> Result<bool> {
+    Ok(path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("exe")))
+}
@@ -0,0 +1,151 @@
+#![allow(unused_imports)]
+
+use pretty_assertions::{assert_eq, assert_ne};
+
+use crate::{NoChooser, User};
+use crate::dao::ChatHistoryDao;
+use crate::entity_utils::*;
+
+use super::*;
+
+//
+// Tests
+//
+
+#[cfg(unix)]
+#[test]
+fn plugin_loading() -> EmptyRes {
+    let tmp_dir = TmpDir::new();
+    let data_dir = tmp_dir.path.join("data");
+    let plugins_dir = tmp_dir.path.join("plugins");
+    fs::create_dir(&data_dir)?;
+    fs::create_dir(&plugins_dir)?;
+
+    let info = LoaderInfo {
+        name: "Test plugin".to_owned(),
+        src_type_option: None,
+        file_globs: vec!["*.chat".to_owned()],
+        needs_myself_chooser: false,
+        needs_network: false,
+    };
+    fs::write(data_dir.join("info.bin"), info.encode_to_vec())?;
+
+    let user = |id: i64, name: &str| User {
+        ds_uuid: PbUuid { value: "to be replaced".to_owned() },
+        id,
+        first_name_option: Some(name.to_owned()),
+        last_name_option: None,
+        username_option: None,
+        phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
+    };
+    let msg = Message::new(
+        999, Some(1), 1700000000, UserId(2),
+        vec![RichText::make_plain("Hi from plugin".to_owned())],
+        message_regular! {
+            edit_timestamp_option: None,
+            is_deleted: false,
+            forward_from_name_option: None,
+            reply_to_message_id_option: None,
+            content_option: None,
+            extra_contents: vec![],
+            forward_origin_option: None,
+            ttl_sec_option: None,
+        },
+    );
+    let result = PluginLoadResult {
+        users: vec![user(1, "Me"), user(2, "Bob")],
+        cwms: vec![ChatWithMessages {
+            chat: Chat {
+                ds_uuid: PbUuid { value: "to be replaced".to_owned() },
+                id: 2,
+                name_option: Some("Bob".to_owned()),
+                source_type: SourceType::TextImport as i32,
+                tpe: ChatType::Personal as i32,
+                img_path_option: None,
+                member_ids: vec![1, 2],
+                msg_count: 0,
+                main_chat_id: None,
+                description_option: None,
+                created_timestamp_option: None,
+                member_details: vec![],
+            },
+            messages: vec![msg.clone()],
+        }],
+        warnings: vec![],
+    };
+    fs::write(data_dir.join("result.bin"), result.encode_to_vec())?;
+    let lenient_result = PluginLoadResult {
+        warnings: vec![LoadWarning { location: "history.chat:2".to_owned(), message: "Unparseable line".to_owned() }],
+        ..result.clone()
+    };
+    fs::write(data_dir.join("lenient_result.bin"), lenient_result.encode_to_vec())?;
+
+    let script = format!(r#"#!/bin/sh
+case "$1" in
+  info) cat "{data_dir}/info.bin" ;;
+  check) case "$2" in *broken.chat) echo "Broken chat" >&2; exit 1 ;; esac ;;
+  load) if [ "$3" = "--lenient" ]; then cat "{data_dir}/lenient_result.bin"; else cat "{data_dir}/result.bin"; fi ;;
+esac
+"#, data_dir = data_dir.display());
+    let script_path = plugins_dir.join("test-plugin");
+    fs::write(&script_path, script)?;
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755))?;
+    }
+    // Not executable, should be ignored
+    fs::write(plugins_dir.join("README.txt"), "Not a plugin")?;
+
+    let plugins = PluginDataLoader::discover(&plugins_dir)?;
+    assert_eq!(plugins.len(), 1);
+    let plugin = &plugins[0];
+    assert_eq!(plugin.info(), LoaderInfo { src_type_option: Some(SourceType::TextImport as i32), ..info });
+
+    let chat_path = data_dir.join("history.chat");
+    fs::write(&chat_path, "whatever")?;
+    // Files not matching plugin globs aren't even offered to it
+    assert!(plugin.looks_about_right(&data_dir.join("info.bin"))
+        .is_err_and(|e| e.to_string() == "File name doesn't match *.chat"));
+    let broken_chat_path = data_dir.join("broken.chat");
+    fs::write(&broken_chat_path, "whatever")?;
+    assert!(plugin.looks_about_right(&broken_chat_path)
+        .is_err_and(|e| e.to_string() == "Broken chat"));
+    plugin.looks_about_right(&chat_path)?;
+
+    let dao = plugin.load(&chat_path, &NoChooser)?;
+    let ds_uuid = dao.ds_uuid();
+    assert_eq!(dao.name(), "Test plugin (history.chat)");
+    assert_eq!(dao.myself_single_ds().id, 1);
+    assert!(dao.users_single_ds().iter().all(|u| u.ds_uuid == ds_uuid));
+
+    let chat = dao.cwms_single_ds().remove(0).chat;
+    assert_eq!(chat.ds_uuid, ds_uuid);
+    assert_eq!(chat.msg_count, 1);
+    assert_eq!(dao.first_messages(&chat, 10)?, vec![Message { internal_id: 0, ..msg }]);
+
+    // Lenient mode is passed to the plugin, warnings reported by it are collected
+    let mut ctx = LoadContext::new(LoadMode::Lenient);
+    plugin.load_with_context(&chat_path, &NoChooser, &mut ctx)?;
+    assert_eq!(ctx.into_warnings(), lenient_result.warnings);
+    Ok(())
+}
+
+#[test]
+fn field_reading() -> EmptyRes {
+    // Tag 1, length-delimited, 3 bytes
+    let mut input: &[u8] = &[0x0A, 0x03, b'a', b'b', b'c'];
+    assert_eq!(read_field(&mut input)?, Some((1, b"abc".to_vec())));
+    assert_eq!(read_field(&mut input)?, None);
+
+    // Length of 2^35 should be rejected before allocating anything
+    let mut input: &[u8] = &[0x0A, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01];
+    assert!(read_field(&mut input).is_err_and(|e| e.to_string().contains("exceeds")));
+
+    let mut input: &[u8] = &[0x0A, 0x03, b'a'];
+    assert!(read_field(&mut input).is_err());
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use itertools::Itertools;
+
+use crate::dao::{ChatHistoryDao, MutableChatHistoryDao, WithCache};
+use crate::dao::in_memory_dao::{DatasetEntry, InMemoryDao};
+use crate::dao::sqlite_dao::SqliteDao;
+use crate::loader::LoadProgress;
+use crate::prelude::*;
+
+#[cfg(test)]
+#[path = "sink_tests.rs"]
+mod tests;
+
+/// Destination loaders push parsed data into, so that it doesn't have to be materialized as a whole before
+/// being written into its final place.
+///
+/// Data is expected to come in order: a dataset with its users, then its chats, each followed by its messages.
+/// More users might be added between chats.
+/// Several datasets might be added one after another.
+pub trait LoadSink {
+    /// Name and storage path of the resulting DAO, ignored by sinks writing into an existing one.
+    fn set_dao_info(&mut self, name: String, storage_path: PathBuf);
+
+    fn start_dataset(&mut self, ds: Dataset, ds_root: PathBuf, myself_id: UserId, users: Vec<User>) -> EmptyRes;
+
+    /// Adds more users (other than myself) to the current dataset, they have to precede chats they're members of.
+    fn add_users(&mut self, users: Vec<User>) -> EmptyRes;
+
+    fn add_chat(&mut self, chat: Chat) -> EmptyRes;
+
+    /// Appends messages to the last added chat, can be called several times per chat.
+    fn add_messages(&mut self, msgs: Vec<Message>) -> EmptyRes;
+
+    fn add_chat_with_messages(&mut self, cwm: ChatWithMessages) -> EmptyRes {
+        self.add_chat(cwm.chat)?;
+        self.add_messages(cwm.messages)
+    }
+}
+
+/// Helper for loaders producing a single dataset, writing chats into a sink one by one as they're parsed.
+///
+/// Chat members that weren't written upfront are written right before the first chat they participate in,
+/// so users not participating in any chat don't end up in the dataset.
+pub struct DatasetWriter<'a> {
+    sink: &'a mut dyn LoadSink,
+    written_user_ids: HashSet<i64>,
+}
+
+impl<'a> DatasetWriter<'a> {
+    /// Starts a dataset with the given users, which should include myself.
+    pub fn start(sink: &'a mut dyn LoadSink,
+                 name: String,
+                 ds: Dataset,
+                 ds_root: PathBuf,
+                 myself_id: UserId,
+                 users: Vec<User>) -> Result<Self> {
+        ensure!(users.iter().any(|u| u.id == *myself_id), "Myself is not among dataset users");
+        let written_user_ids = users.iter().map(|u| u.id).collect();
+        sink.set_dao_info(name, ds_root.clone());
+        sink.start_dataset(ds, ds_root, myself_id, users)?;
+        Ok(DatasetWriter { sink, written_user_ids })
+    }
+
+    /// Writes a chat with its messages, preceded by its members that weren't written yet, as given by `get_user`.
+    pub fn add_chat(&mut self,
+                    cwm: ChatWithMessages,
+                    mut get_user: impl FnMut(UserId) -> Result<User>) -> EmptyRes {
+        let new_users: Vec<User> = cwm.chat.member_ids.iter()
+            .filter(|id| !self.written_user_ids.contains(*id))
+            .map(|id| get_user(UserId(*id)))
+            .try_collect()?;
+        if !new_users.is_empty() {
+            self.written_user_ids.extend(new_users.iter().map(|u| u.id));
+            self.sink.add_users(new_users)?;
+        }
+        self.sink.add_chat_with_messages(cwm)
+    }
+}
+
+/// Collects everything into an `InMemoryDao`.
+#[derive(Default)]
+pub struct InMemorySink {
+    dao_info_option: Option<(String, PathBuf)>,
+    entries: Vec<DatasetEntry>,
+}
+
+impl InMemorySink {
+    pub fn into_dao(self) -> Result<InMemoryDao> {
+        let (name, storage_path) = self.dao_info_option.context("DAO name was not set")?;
+        Ok(InMemoryDao::new(name, storage_path, self.entries))
+    }
+
+    fn last_entry(&mut self) -> Result<&mut DatasetEntry> {
+        self.entries.last_mut().context("No dataset started")
+    }
+}
+
+impl LoadSink for InMemorySink {
+    fn set_dao_info(&mut self, name: String, storage_path: PathBuf) {
+        self.dao_info_option = Some((name, storage_path));
+    }
+
+    fn start_dataset(&mut self, ds: Dataset, ds_root: PathBuf, myself_id: UserId, users: Vec<User>) -> EmptyRes {
+        self.entries.push(DatasetEntry { ds, ds_root, myself_id, users, cwms: vec![] });
+        Ok(())
+    }
+
+    fn add_users(&mut self, users: Vec<User>) -> EmptyRes {
+        self.last_entry()?.users.extend(users);
+        Ok(())
+    }
+
+    fn add_chat(&mut self, chat: Chat) -> EmptyRes {
+        self.last_entry()?.cwms.push(ChatWithMessages { chat, messages: vec![] });
+        Ok(())
+    }
+
+    fn add_messages(&mut self, msgs: Vec<Message>) -> EmptyRes {
+        let cwm = self.last_entry()?.cwms.last_mut().context("No chat added")?;
+        cwm.messages.extend(msgs);
+        Ok(())
+    }
+}
+
+/// Writes everything straight into an SQLite database, copying referenced files along the way.
+/// Copied files are reported to the load progress, cancellation is checked before every batch of messages.
+pub struct SqliteSink<'a> {
+    dao: &'a mut SqliteDao,
+    progress: Arc<LoadProgress>,
+    src_ds_root_option: Option<DatasetRoot>,
+    chat_option: Option<Chat>,
+    ds_uuids: Vec<PbUuid>,
+}
+
+impl<'a> SqliteSink<'a> {
+    const BATCH_SIZE: usize = 5_000;
+
+    pub fn new(dao: &'a mut SqliteDao, progress: Arc<LoadProgress>) -> Self {
+        SqliteSink { dao, progress, src_ds_root_option: None, chat_option: None, ds_uuids: vec![] }
+    }
+
+    /// UUIDs of datasets that were (possibly partially) written.
+    pub fn finish(self) -> Result<Vec<PbUuid>> {
+        self.dao.invalidate_cache()?;
+        Ok(self.ds_uuids)
+    }
+}
+
+impl LoadSink for SqliteSink<'_> {
+    fn set_dao_info(&mut self, _name: String, _storage_path: PathBuf) {}
+
+    fn start_dataset(&mut self, ds: Dataset, ds_root: PathBuf, myself_id: UserId, users: Vec<User>) -> EmptyRes {
+        ensure!(!self.dao.datasets()?.iter().any(|existing| existing.uuid == ds.uuid),
+                "Dataset UUID {} is already in use!", ds.uuid.value);
+        self.ds_uuids.push(ds.uuid.clone());
+        self.dao.insert_dataset(ds)?;
+        let src_ds_root = DatasetRoot(ds_root);
+        for user in users {
+            ensure!(user.id > 0, "IDs should be positive!");
+            let is_myself = user.id == *myself_id;
+            let files_count = existing_files_count(user.img_path_option.as_deref(), &src_ds_root);
+            self.dao.insert_user(user, is_myself, &src_ds_root)?;
+            self.progress.add_files_copied(files_count);
+        }
+        self.src_ds_root_option = Some(src_ds_root);
+        self.chat_option = None;
+        Ok(())
+    }
+
+    fn add_users(&mut self, users: Vec<User>) -> EmptyRes {
+        let src_ds_root = self.src_ds_root_option.as_ref().context("No dataset started")?;
+        for user in users {
+            ensure!(user.id > 0, "IDs should be positive!");
+            let files_count = existing_files_count(user.img_path_option.as_deref(), src_ds_root);
+            self.dao.insert_user(user, false, src_ds_root)?;
+            self.progress.add_files_copied(files_count);
+        }
+        Ok(())
+    }
+
+    fn add_chat(&mut self, chat: Chat) -> EmptyRes {
+        ensure!(chat.id > 0, "IDs should be positive!");
+        let src_ds_root = self.src_ds_root_option.as_ref().context("No dataset started")?;
+        let files_count = existing_files_count(chat.img_path_option.as_deref(), src_ds_root);
+        self.chat_option = Some(self.dao.insert_chat(chat, src_ds_root)?);
+        self.progress.add_files_copied(files_count);
+        Ok(())
+    }
+
+    fn add_messages(&mut self, msgs: Vec<Message>) -> EmptyRes {
+        let src_ds_root = self.src_ds_root_option.as_ref().context("No dataset started")?;
+        let chat = self.chat_option.as_ref().context("No chat added")?;
+        for batch in &msgs.into_iter().chunks(Self::BATCH_SIZE) {
+            self.progress.check_cancelled()?;
+            let batch = batch.collect_vec();
+            let files_count = existing_files_count(batch.iter().flat_map(|m| m.files_relative()), src_ds_root);
+            self.dao.insert_messages(batch, chat, src_ds_root)?;
+            self.progress.add_files_copied(files_count);
+        }
+        Ok(())
+    }
+}
+
+/// Missing files are skipped when copying, so they are not counted either.
+fn existing_files_count<'a>(rel_paths: impl IntoIterator<Item = &'a str>, src_ds_root: &DatasetRoot) -> usize {
+    rel_paths.into_iter().filter(|p| src_ds_root.to_absolute(p).exists()).count()
+}
@@ -0,0 +1,98 @@
+#![allow(unused_imports)]
+
+use pretty_assertions::{assert_eq, assert_ne};
+
+use crate::NoChooser;
+use crate::entity_utils::*;
+use crate::loader::{LoadContext, LoadMode, LoadProgress, Loader};
+
+use super::*;
+
+type Tup<'a, T> = PracticalEqTuple<'a, T>;
+
+//
+// Tests
+//
+
+#[test]
+fn sqlite_sink_matches_in_memory() -> EmptyRes {
+    let loader = Loader::new(&*HTTP_CLIENT);
+    let src_path = resource("telegram_2020-01");
+    let src_dao = loader.parse(&src_path, &NoChooser)?;
+    let src_ds_uuid = src_dao.ds_uuid();
+    let src_ds_root = src_dao.dataset_root(&src_ds_uuid)?;
+
+    let tmp_dir = TmpDir::new();
+    let mut dst_dao = SqliteDao::create(&tmp_dir.path.join(SqliteDao::FILENAME))?;
+    let ds_uuids =
+        loader.parse_into_sqlite(&src_path, &NoChooser, &mut LoadContext::new(LoadMode::Strict), &mut dst_dao)?;
+    assert_eq!(ds_uuids.len(), 1);
+    let ds_uuid = ds_uuids[0].clone();
+    assert_eq!(dst_dao.datasets()?.into_iter().map(|ds| ds.uuid).collect_vec(), vec![ds_uuid.clone()]);
+    let dst_ds_root = dst_dao.dataset_root(&ds_uuid)?;
+
+    let with_src_uuid = |u: User| User { ds_uuid: src_ds_uuid.clone(), ..u };
+    assert_eq!(dst_dao.myself(&ds_uuid).map(with_src_uuid)?, src_dao.myself(&src_ds_uuid)?);
+    assert_eq!(dst_dao.users(&ds_uuid)?.into_iter().map(with_src_uuid).collect_vec(),
+               src_dao.users(&src_ds_uuid)?);
+
+    let src_chats = src_dao.chats(&src_ds_uuid)?;
+    let dst_chats = dst_dao.chats(&ds_uuid)?;
+    assert_eq!(src_chats.len(), dst_chats.len());
+    for (src_cwd, dst_cwd) in src_chats.iter().zip(dst_chats.iter()) {
+        assert_eq!(Chat { ds_uuid: src_ds_uuid.clone(), ..dst_cwd.chat.clone() }, src_cwd.chat);
+        let src_msgs = src_dao.first_messages(&src_cwd.chat, src_cwd.chat.msg_count as usize)?;
+        let dst_msgs = dst_dao.first_messages(&dst_cwd.chat, dst_cwd.chat.msg_count as usize)?;
+        assert!(Tup::new(&src_msgs, &src_ds_root, src_cwd)
+            .practically_equals(&Tup::new(&dst_msgs, &dst_ds_root, dst_cwd))?);
+    }
+    Ok(())
+}
+
+#[test]
+fn sqlite_sink_progress_and_cancellation() -> EmptyRes {
+    let loader = Loader::new(&*HTTP_CLIENT);
+    let tmp_dir = TmpDir::new();
+    let mut dst_dao = SqliteDao::create(&tmp_dir.path.join(SqliteDao::FILENAME))?;
+
+    let progress = Arc::new(LoadProgress::default());
+    let mut ctx = LoadContext::with_progress(LoadMode::Strict, progress.clone());
+    loader.parse_into_sqlite(&resource("telegram_2024-07_album"), &NoChooser, &mut ctx, &mut dst_dao)?;
+    assert_eq!(progress.files_copied(), 3);
+
+    // Messages are not written once the load is cancelled
+    let progress = Arc::new(LoadProgress::default());
+    progress.cancel();
+    let mut sink = SqliteSink::new(&mut dst_dao, progress);
+    let ds = Dataset { uuid: ZERO_PB_UUID.clone(), alias: "Test".to_owned() };
+    sink.start_dataset(ds, tmp_dir.path.clone(), UserId(1), vec![create_user(&ZERO_PB_UUID, 1), create_user(&ZERO_PB_UUID, 2)])?;
+    sink.add_chat(create_group_chat(&ZERO_PB_UUID, 1, "One", vec![1, 2], 1))?;
+    let msg = Message::new(1, Some(1), 0, UserId(1), vec![], MESSAGE_REGULAR_NO_CONTENT.clone());
+    assert!(sink.add_messages(vec![msg]).is_err_and(|e| e.to_string() == "Load cancelled"));
+    Ok(())
+}
+
+#[test]
+fn dataset_writer_writes_members_lazily() -> EmptyRes {
+    let ds = Dataset { uuid: ZERO_PB_UUID.clone(), alias: "Test".to_owned() };
+    let users = (1..=4).map(|id| create_user(&ZERO_PB_UUID, id)).collect_vec();
+    let get_user = |id: UserId| users.iter().find(|u| u.id == *id).cloned().context("Unknown user");
+
+    let mut sink = InMemorySink::default();
+    let mut writer =
+        DatasetWriter::start(&mut sink, "Test".to_owned(), ds, PathBuf::from("/"), UserId(1), vec![users[0].clone()])?;
+    let cwm = |id: i64, member_ids: Vec<i64>| ChatWithMessages {
+        chat: create_group_chat(&ZERO_PB_UUID, id, &id.to_string(), member_ids, 0),
+        messages: vec![],
+    };
+    writer.add_chat(cwm(1, vec![1, 2]), get_user)?;
+    writer.add_chat(cwm(2, vec![1, 2, 3]), get_user)?;
+    assert!(writer.add_chat(cwm(3, vec![1, 5]), get_user)
+        .is_err_and(|e| e.to_string() == "Unknown user"));
+
+    // User 4 doesn't participate in any chat
+    let dao = sink.into_dao()?;
+    assert_eq!(dao.users(&ZERO_PB_UUID)?, users[..3].to_vec());
+    assert_eq!(dao.chats(&ZERO_PB_UUID)?.len(), 2);
+    Ok(())
+}
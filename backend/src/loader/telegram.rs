@@ -12,8 +12,8 @@ use simd_json::borrowed::Object;
 use simd_json::BorrowedValue;
 use simd_json::prelude::*;
 
-use crate::dao::in_memory_dao::InMemoryDao;
-use crate::loader::DataLoader;
+use crate::loader::{DataLoader, LoadContext};
+use crate::loader::sink::{DatasetWriter, LoadSink};
 use crate::prelude::*;
 // Reexporting JSON utils for simplicity.
 pub use crate::utils::json_utils::*;
@@ -42,6 +42,11 @@ impl DataLoader for TelegramDataLoader {
 
     fn src_type(&self) -> SourceType { SourceType::Telegram }
 
+    fn file_globs(&self) -> Vec<&'static str> { vec![RESULT_JSON] }
+
+    /// Single chat export doesn't tell which user is myself
+    fn needs_myself_chooser(&self) -> bool { true }
+
     fn looks_about_right_inner(&self, src_path: &Path) -> EmptyRes {
         let path = get_real_path(src_path);
         if !path.exists() {
@@ -53,8 +58,13 @@ impl DataLoader for TelegramDataLoader {
         Ok(())
     }
 
-    fn load_inner(&self, path: &Path, ds: Dataset, myself_chooser: &dyn MyselfChooser) -> Result<Box<InMemoryDao>> {
-        parse_telegram_file(path, ds, myself_chooser)
+    fn load_inner(&self,
+                  path: &Path,
+                  ds: Dataset,
+                  myself_chooser: &dyn MyselfChooser,
+                  ctx: &mut LoadContext,
+                  sink: &mut dyn LoadSink) -> EmptyRes {
+        parse_telegram_file(path, ds, myself_chooser, ctx, sink)
     }
 }
 
@@ -84,12 +94,21 @@ impl Users {
                 _ => (original.first_name_option.or(new.first_name_option),
                       original.last_name_option.or(new.last_name_option))
             };
+        let mut phone_numbers = original.phone_number_option.into_iter()
+            .chain(original.extra_phone_numbers)
+            .chain(new.phone_number_option)
+            .chain(new.extra_phone_numbers)
+            .unique();
         User {
             ds_uuid: original.ds_uuid,
             id: if original.id == 0 { new.id } else { original.id },
             first_name_option,
             last_name_option,
-            phone_number_option: original.phone_number_option.or(new.phone_number_option),
+            phone_number_option: phone_numbers.next(),
+            extra_phone_numbers: phone_numbers.collect_vec(),
+            emails: original.emails.into_iter().chain(new.emails).unique().collect_vec(),
+            bio_option: original.bio_option.or(new.bio_option),
+            img_path_option: original.img_path_option.or(new.img_path_option),
             username_option: original.username_option.or(new.username_option),
         }
     }
@@ -167,7 +186,11 @@ fn get_real_path(path: &Path) -> PathBuf {
     }
 }
 
-fn parse_telegram_file(path: &Path, ds: Dataset, myself_chooser: &dyn MyselfChooser) -> Result<Box<InMemoryDao>> {
+fn parse_telegram_file(path: &Path,
+                       ds: Dataset,
+                       myself_chooser: &dyn MyselfChooser,
+                       ctx: &mut LoadContext,
+                       sink: &mut dyn LoadSink) -> EmptyRes {
     let path = get_real_path(path);
     assert!(path.exists()); // Should be checked by looks_about_right already.
 
@@ -183,18 +206,21 @@ fn parse_telegram_file(path: &Path, ds: Dataset, myself_chooser: &dyn MyselfChoo
     let start_time = Instant::now();
     let root_obj = as_object!(parsed, "root");
 
-    let mut myself = User {
-        ds_uuid: ds.uuid.clone(),
-        ..Default::default()
+    let ds_root = path.parent().unwrap().to_path_buf();
+    let output = Output {
+        sink,
+        name: format!("Telegram ({})", path_file_name(&ds_root)?),
+        ds,
+        ds_root,
     };
 
     let single_chat_keys = HashSet::from(["name", "type", "id", "messages"]);
     let keys = root_obj.keys().map(|s| s.deref()).collect::<HashSet<_>>();
-    let (users, chats_with_messages) =
+    let users =
         if single_chat_keys.is_superset(&keys) {
-            parser_single::parse(root_obj, &ds.uuid, &mut myself, myself_chooser)?
+            parser_single::parse(root_obj, myself_chooser, ctx, output)?
         } else {
-            parser_full::parse(root_obj, &ds.uuid, &mut myself)?
+            parser_full::parse(root_obj, ctx, output)?
         };
 
     log::info!("Processed in {} ms", start_time.elapsed().as_millis());
@@ -206,33 +232,67 @@ fn parse_telegram_file(path: &Path, ds: Dataset, myself_chooser: &dyn MyselfChoo
         }
     }
 
-    // Sanity check: every chat member is supposed to have an associated user.
-    for cwm in &chats_with_messages {
-        for member_id in cwm.chat.member_ids() {
-            if !users.id_to_user.contains_key(&member_id) {
-                bail!("No member with id={} found for chat with id={} '{}'",
-                      *member_id, cwm.chat.id, name_or_unnamed(&cwm.chat.name_option));
-            }
-        }
+    Ok(())
+}
+
+/// Destination for a dataset being parsed, writing can only start once myself is known.
+struct Output<'a> {
+    sink: &'a mut dyn LoadSink,
+    name: String,
+    ds: Dataset,
+    ds_root: PathBuf,
+}
+
+impl<'a> Output<'a> {
+    fn start(self, myself: &User) -> Result<ChatWriter<'a>> {
+        let myself = with_existing_img(myself.clone(), &self.ds_root);
+        let writer = DatasetWriter::start(self.sink, self.name, self.ds, self.ds_root.clone(), myself.id(), vec![myself])?;
+        Ok(ChatWriter { writer, ds_root: self.ds_root })
     }
+}
+
+struct ChatWriter<'a> {
+    writer: DatasetWriter<'a>,
+    ds_root: PathBuf,
+}
 
-    let mut users = users.id_to_user.into_values().collect_vec();
+impl ChatWriter<'_> {
+    /// Writes a parsed chat along with its members. Users added by chats that were skipped are never written.
+    fn add_chat(&mut self, mut cwm: ChatWithMessages, users: &Users) -> EmptyRes {
+        resolve_mentions(&mut cwm.messages, users);
+        let chat_id = cwm.chat.id;
+        let chat_name = name_or_unnamed(&cwm.chat.name_option);
+        let ds_root = &self.ds_root;
+        self.writer.add_chat(cwm, |member_id| {
+            // Sanity check: every chat member is supposed to have an associated user.
+            let user = users.id_to_user.get(&member_id).with_context(|| {
+                format!("No member with id={} found for chat with id={} '{}'", *member_id, chat_id, chat_name)
+            })?;
+            Ok(with_existing_img(user.clone(), ds_root))
+        })
+    }
+}
 
-    // Set myself to be a first member (not required by convention but to match existing behaviour).
-    users.sort_by_key(|u| if u.id == myself.id { *UserId::MIN } else { u.id });
+/// Mentions by username don't carry user ID, it's only known for users whose username is exported.
+fn resolve_mentions(messages: &mut [Message], users: &Users) {
+    use rich_text_element::Val;
+    let mentions = messages.iter_mut()
+        .flat_map(|m| m.text.iter_mut())
+        .filter_map(|rte| match rte.val {
+            Some(Val::Mention(ref mut mention)) if mention.user_id_option.is_none() => Some(mention),
+            _ => None,
+        });
+    for mention in mentions {
+        mention.user_id_option = users.id_to_user.values()
+            .find(|u| u.username_option.as_ref().is_some_and(|un| un.eq_ignore_ascii_case(&mention.text)))
+            .map(|u| u.id);
+    }
+}
 
-    let parent_name = path_file_name(path.parent().unwrap())?;
-    let mut result = Box::new(InMemoryDao::new_single(
-        format!("Telegram ({})", parent_name),
-        ds,
-        path.parent().unwrap().to_path_buf(),
-        myself.id(),
-        users,
-        chats_with_messages,
-    ));
-    // Some users might be added by chats that were skipped from the datasets
-    result.remove_orphan_users();
-    Ok(result)
+/// Profile pictures are only exported on request
+fn with_existing_img(user: User, ds_root: &Path) -> User {
+    let img_path_option = user.img_path_option.filter(|p| ds_root.join(p).exists());
+    User { img_path_option, ..user }
 }
 
 /** Returns a partially filled user. */
@@ -279,7 +339,8 @@ fn parse_chat(json_path: &str,
               chat_json: &Object,
               ds_uuid: &PbUuid,
               myself_id_option: Option<&UserId>,
-              users: &mut Users) -> Result<Option<ChatWithMessages>> {
+              users: &mut Users,
+              ctx: &mut LoadContext) -> Result<Option<ChatWithMessages>> {
     let mut chat: Chat = Chat {
         source_type: SourceType::Telegram as i32,
         ..Default::default()
@@ -328,8 +389,25 @@ fn parse_chat(json_path: &str,
             if skip_processing { return Ok(()); }
             let path = format!("{json_path}.messages");
             let messages_json = as_array!(value, path);
-            for v in messages_json {
-                let parsed = parse_message(&path, v, ds_uuid, users, &mut member_ids)?;
+            for (idx, v) in messages_json.iter().enumerate() {
+                ctx.message_parsed(idx)?;
+                let parsed = match parse_message(&path, v, ds_uuid, users, &mut member_ids) {
+                    Ok(parsed) => parsed,
+                    Err(e) => {
+                        let message_key = v.get("id").map(|id| id.to_string()).unwrap_or("?".to_owned());
+                        let location = super::message_location(&name_or_unnamed(&chat_name), message_key);
+                        match parse_placeholder_message(v, ds_uuid, users, &mut member_ids) {
+                            Some(msg) => {
+                                ctx.tolerate(location, e)?;
+                                ParsedMessage::Ok(Box::new(msg))
+                            }
+                            None => {
+                                ctx.tolerate(location, e.context("Message dropped as it has no valid sender or date"))?;
+                                ParsedMessage::SkipMessage
+                            }
+                        }
+                    }
+                };
                 match parsed {
                     ParsedMessage::Ok(msg) =>
                         messages.push(*msg),
@@ -355,6 +433,7 @@ fn parse_chat(json_path: &str,
     chat.name_option = chat_name;
 
     messages.sort_by_key(|m| (m.timestamp, m.internal_id));
+    let mut messages = super::merge_albums(messages);
 
     for (idx, m) in messages.iter_mut().enumerate() {
         m.internal_id = idx as i64;
@@ -362,6 +441,39 @@ fn parse_chat(json_path: &str,
 
     chat.msg_count = messages.len() as i32;
 
+    // Whoever created the group is its owner, other members join and leave as told by service messages.
+    // Those only name members, so only ones that can be told apart by name are considered.
+    let mut member_details: HashMap<UserId, ChatMemberDetails, Hasher> = Default::default();
+    for m in messages.iter() {
+        use message_service::SealedValueOptional::*;
+        let (names, joined) = match m.typed {
+            Some(message_service_pat!(GroupCreate(MessageServiceGroupCreate { ref members, .. })))
+            if chat.created_timestamp_option.is_none() => {
+                chat.created_timestamp_option = Some(m.timestamp);
+                let details = member_details_mut(&mut member_details, UserId(m.from_id));
+                details.role_option = Some(ChatMemberRole::Owner as i32);
+                details.joined_timestamp_option = Some(m.timestamp);
+                (members, true)
+            }
+            Some(message_service_pat!(GroupInviteMembers(MessageServiceGroupInviteMembers { ref members }))) =>
+                (members, true),
+            Some(message_service_pat!(GroupRemoveMembers(MessageServiceGroupRemoveMembers { ref members }))) =>
+                (members, false),
+            _ => continue
+        };
+        for name in names {
+            let Some(user_id) = resolve_member_id(name, UserId(m.from_id), &member_ids, users) else { continue };
+            let details = member_details_mut(&mut member_details, user_id);
+            if joined {
+                details.joined_timestamp_option = Some(m.timestamp);
+                details.left_timestamp_option = None;
+            } else {
+                details.left_timestamp_option = Some(m.timestamp);
+            }
+        }
+    }
+    chat.member_details = member_details.into_values().sorted_by_key(|md| md.user_id).collect_vec();
+
     // Undo the shifts introduced by Telegram 2021-05.
     match ChatType::resolve(chat.tpe)? {
         ChatType::Personal if chat.id < PERSONAL_CHAT_ID_SHIFT =>
@@ -383,9 +495,32 @@ fn parse_chat(json_path: &str,
     }
     chat.member_ids = member_ids.into_iter().map(|s| *s).collect();
 
+    ctx.chat_parsed(messages.len())?;
     Ok(Some(ChatWithMessages { chat, messages }))
 }
 
+fn member_details_mut(member_details: &mut HashMap<UserId, ChatMemberDetails, Hasher>,
+                      user_id: UserId) -> &mut ChatMemberDetails {
+    member_details.entry(user_id).or_insert_with(|| ChatMemberDetails { user_id: *user_id, ..Default::default() })
+}
+
+/// Finds a chat member by their name as shown in service messages.
+/// Actor themselves (e.g. joining by link or leaving) is recognized even if unnamed, others only if their name
+/// is unambiguous.
+fn resolve_member_id(name: &str,
+                     actor_id: UserId,
+                     member_ids: &HashSet<UserId, Hasher>,
+                     users: &Users) -> Option<UserId> {
+    let actor_name_option = users.id_to_user.get(&actor_id).map(Users::pretty_name).filter(|n| !n.is_empty());
+    if name_or_unnamed(&actor_name_option) == name {
+        return Some(actor_id);
+    }
+    member_ids.iter()
+        .filter(|id| users.id_to_user.get(id).is_some_and(|u| Users::pretty_name(u) == name))
+        .exactly_one().ok()
+        .copied()
+}
+
 //
 // Parsing message
 //
@@ -460,17 +595,21 @@ impl<'lt> MessageJson<'lt> {
     /// Retrieve a RELATIVE path!
     fn field_opt_path(&mut self, name: &'lt str) -> Result<Option<String>> {
         let field_opt = self.field_opt_str(name)?;
+        Ok(field_opt.and_then(path_option))
+    }
+}
 
-        Ok(field_opt.and_then(|s| (match s.as_str() {
-            "" => None,
-            "(File not included. Change data exporting settings to download.)" => None,
-            "(File exceeds maximum size. Change data exporting settings to download.)" => None,
-            "(File unavailable, please try again later)" => {
-                // So far looks like it may mean timed photo, or file manually skipped during export.
-                None
-            }
-            _ => Some(s)
-        })))
+/// Filters out placeholders Telegram uses in place of missing files.
+fn path_option(s: String) -> Option<String> {
+    match s.as_str() {
+        "" => None,
+        "(File not included. Change data exporting settings to download.)" => None,
+        "(File exceeds maximum size. Change data exporting settings to download.)" => None,
+        "(File unavailable, please try again later)" => {
+            // So far looks like it may mean timed photo, or file manually skipped during export.
+            None
+        }
+        _ => Some(s)
     }
 }
 
@@ -489,10 +628,13 @@ fn parse_message(json_path: &str,
     lazy_static! {
         static ref REGULAR_MSG_FIELDS: ExpectedMessageField<'static> = ExpectedMessageField {
             required_fields: hash_set(["id", "type", "date", "text", "from", "from_id"]),
-            // forwarded_from: the original source message
-            // saved_from:     where the message was last forwarded from, could match forwarded_from (ignored)
-            optional_fields: hash_set(["date_unixtime", "text_entities", "forwarded_from", "saved_from", "via_bot",
-                                       "reply_to_peer_id", "reply_to_message_id", "inline_bot_buttons"]),
+            // forwarded_from:    the original source message
+            // forwarded_from_id: ID of the original author
+            // saved_from:        where the message was last forwarded from, could match forwarded_from
+            optional_fields: hash_set(["date_unixtime", "text_entities", "forwarded_from", "forwarded_from_id",
+                                       "saved_from", "via_bot",
+                                       "reply_to_peer_id", "reply_to_message_id", "inline_bot_buttons",
+                                       "reactions", "web_page"]),
         };
 
         static ref SERVICE_MSG_FIELDS: ExpectedMessageField<'static> = ExpectedMessageField {
@@ -564,6 +706,7 @@ fn parse_message(json_path: &str,
 
     let mut source_id_option: Option<i64> = None;
     let mut timestamp: Option<i64> = None;
+    let mut reactions: Vec<Reaction> = vec![];
 
     for (k, v) in message_json.val.iter() {
         let kr = k.as_ref();
@@ -605,6 +748,10 @@ fn parse_message(json_path: &str,
                     }
                 }
             }
+            "reactions" => {
+                reactions = parse_reactions(&format!("{}.reactions", message_json.json_path), v,
+                                            ds_uuid, users, member_ids)?;
+            }
             _ => { /* Ignore, already consumed */ }
         }
     }
@@ -617,14 +764,42 @@ fn parse_message(json_path: &str,
         }
     }
 
-    Ok(ParsedMessage::Ok(Box::new(Message::new(
+    let mut message = Message::new(
         *NO_INTERNAL_ID,
         source_id_option,
         timestamp.with_context(|| format!("{}: timestamp not set", message_json.json_path))?,
         from_id,
         text,
         typed,
-    ))))
+    );
+    message.reactions = reactions;
+    Ok(ParsedMessage::Ok(Box::new(message)))
+}
+
+/// Best-effort extraction of a sender and a timestamp of a message that couldn't be parsed,
+/// to make a placeholder message out of it.
+fn parse_placeholder_message(bw: &BorrowedValue,
+                             ds_uuid: &PbUuid,
+                             users: &mut Users,
+                             member_ids: &mut HashSet<UserId, Hasher>) -> Option<Message> {
+    let obj = bw.as_object()?;
+    let source_id_option = obj.get("id").and_then(|v| v.as_i64());
+    let timestamp = match (obj.get("date_unixtime"), obj.get("date")) {
+        (Some(v), _) => parse_timestamp(v.as_str()?).ok()?,
+        (None, Some(v)) => *parse_datetime(v.as_str()?).ok()?,
+        (None, None) => return None,
+    };
+    let (id_key, name_key) = if obj.contains_key("actor_id") { ("actor_id", "actor") } else { ("from_id", "from") };
+    let mut short_user = ShortUser {
+        id: parse_user_id(obj.get(id_key)?).ok()?,
+        full_name_option: obj.get(name_key).and_then(|v| v.as_str()).map(|s| s.to_owned()),
+    };
+    if *short_user.id >= USER_ID_SHIFT {
+        short_user.id = UserId(*short_user.id - USER_ID_SHIFT);
+    }
+    let from_id = append_user(short_user, users, ds_uuid).ok()?;
+    member_ids.insert(from_id);
+    Some(super::placeholder_message(source_id_option, timestamp, from_id, bw.to_string()))
 }
 
 fn parse_regular_message(message_json: &mut MessageJson,
@@ -649,6 +824,29 @@ fn parse_regular_message(message_json: &mut MessageJson,
         Some(forwarded_from) if forwarded_from.is_null() => Some(UNKNOWN.to_owned()),
         Some(forwarded_from) => Some(as_string!(forwarded_from, json_path, "forwarded_from")),
     };
+    // Only users can be resolved, forwards from channels keep just the name
+    let forward_from_id_option = match message_json.field_opt("forwarded_from_id")? {
+        Some(id @ BorrowedValue::String(s)) if s.starts_with("user") => Some(parse_user_id(id)?),
+        _ => None,
+    }.map(|id| if *id >= USER_ID_SHIFT { *id - USER_ID_SHIFT } else { *id });
+    let forward_timestamp_option = if let Some(ref ts) = message_json.field_opt_str("forwarded_date_unixtime")? {
+        message_json.add_required("forwarded_date");
+        Some(parse_timestamp(ts)?)
+    } else if let Some(ref date) = message_json.field_opt_str("forwarded_date")? {
+        Some(*parse_datetime(date)?)
+    } else {
+        None
+    };
+    let forward_origin = ForwardOrigin {
+        from_id_option: forward_from_id_option,
+        chat_name_option: message_json.field_opt_str("saved_from")?,
+        timestamp_option: forward_timestamp_option,
+        source_id_option: None,
+        from_name_option: regular_msg.forward_from_name_option.clone().filter(|n| n.as_str() != UNKNOWN),
+    };
+    regular_msg.forward_origin_option = Some(forward_origin).filter(|fo| fo != &ForwardOrigin::default());
+    // Set for self-destructing photos and videos, their files are never exported
+    regular_msg.ttl_sec_option = message_json.field_opt_i32("self_destruct_period_seconds")?;
     if message_json.field_opt("reply_to_peer_id")?.is_none() {
         // Otherwise reply_to_message_id is pointless
         regular_msg.reply_to_message_id_option = message_json.field_opt_i64("reply_to_message_id")?;
@@ -664,6 +862,9 @@ fn parse_regular_message(message_json: &mut MessageJson,
         Some(poll) => as_object!(poll, json_path, "poll").get("question").is_some(),
     };
     let contact_info_present = message_json.field_opt("contact_information")?.is_some();
+    let web_page_option = message_json.field_opt("web_page")?
+        .map(|v| parse_web_page(&format!("{json_path}.web_page"), v))
+        .transpose()?;
 
     // Helpers to reduce boilerplate, since we can't have match guards for separate pattern arms.
     let make_content_audio = |message_json: &mut MessageJson| -> Result<Option<_>> {
@@ -696,7 +897,8 @@ fn parse_regular_message(message_json: &mut MessageJson,
                                                           loc_present,
                                                           poll_question_present,
                                                           contact_info_present) {
-        (None, None, false, false, false, false) => None,
+        // Link preview is only considered when there's no other content
+        (None, None, false, false, false, false) => web_page_option.map(SealedValueOptional::WebPage),
         (Some("sticker"), None, true, false, false, false) => {
             // Ignoring animated sticker duration
             message_json.add_optional("duration_seconds");
@@ -920,6 +1122,7 @@ fn parse_service_message(message_json: &mut MessageJson,
             }), None),
         "set_messages_ttl" => {
             let mut period = message_json.field_i64("period")?;
+            let ttl_sec_option = Some(period as i32).filter(|&p| p > 0);
             let mut period_str = "second(s)";
             let div_list = [
                 (60, "minute(s)"),
@@ -932,8 +1135,9 @@ fn parse_service_message(message_json: &mut MessageJson,
                 period_str = new_period_str;
             }
 
-            (SealedValueOptional::Notice(MessageServiceNotice {}),
-             Some(format!("Messages will be auto-deleted in {period} {period_str}")))
+            (SealedValueOptional::SetMessagesTtl(MessageServiceSetMessagesTtl {
+                ttl_sec_option,
+            }), Some(format!("Messages will be auto-deleted in {period} {period_str}")))
         }
         "edit_chat_theme" => {
             // Not really interesting to track.
@@ -1080,20 +1284,48 @@ fn parse_rich_text_object(json_path: &str,
                 false,
             ))
         }
+        "mention" => {
+            // Mention by username, user ID is resolved once the chat is parsed
+            check_keys!(["type", "text"]);
+            let text = get_field_str!(rte_json, json_path, "text");
+            Some(RichText::make_mention(None, text.strip_prefix('@').unwrap_or(text).to_owned()))
+        }
         "mention_name" => {
-            // No special treatment for mention_name, but prepent @
+            // Mention of a user without username, by name
             check_keys!(["type", "text", "user_id"]);
-            Some(RichText::make_plain(format!("@{}", get_field_str!(rte_json, json_path, "text"))))
+            let user_id = parse_user_id(get_field!(rte_json, json_path, "user_id")?)?;
+            Some(RichText::make_mention(Some(*user_id), get_field_string!(rte_json, json_path, "text")))
         }
-        "email" | "mention" | "phone" | "hashtag" | "bot_command" | "bank_card" | "cashtag" => {
-            // No special treatment for any of these
+        "hashtag" => {
             check_keys!(["type", "text"]);
-            Some(RichText::make_plain(get_field_string!(rte_json, json_path, "text")))
+            Some(RichText::make_hashtag(get_field_string!(rte_json, json_path, "text")))
+        }
+        "cashtag" => {
+            check_keys!(["type", "text"]);
+            Some(RichText::make_cashtag(get_field_string!(rte_json, json_path, "text")))
+        }
+        "bot_command" => {
+            check_keys!(["type", "text"]);
+            Some(RichText::make_bot_command(get_field_string!(rte_json, json_path, "text")))
+        }
+        "email" => {
+            check_keys!(["type", "text"]);
+            Some(RichText::make_email(get_field_string!(rte_json, json_path, "text")))
+        }
+        "phone" => {
+            check_keys!(["type", "text"]);
+            Some(RichText::make_phone(get_field_string!(rte_json, json_path, "text")))
+        }
+        "bank_card" => {
+            check_keys!(["type", "text"]);
+            Some(RichText::make_bank_card(get_field_string!(rte_json, json_path, "text")))
         }
         "custom_emoji" => {
-            // Just taken as a regular emoji
             check_keys!(["type", "text", "document_id"]);
-            Some(RichText::make_plain(get_field_string!(rte_json, json_path, "text")))
+            Some(RichText::make_custom_emoji(
+                get_field_string!(rte_json, json_path, "text"),
+                get_field_string_option!(rte_json, json_path, "document_id").and_then(path_option),
+            ))
         }
         etc =>
             bail!("Don't know how to parse RichText element of type '{etc}' for {:?}", rte_json)
@@ -1123,10 +1355,12 @@ fn simplify_rich_text(mut rtes: Vec<RichTextElement>) -> Vec<RichTextElement> {
     fn is_whitespaces(rte: &RichTextElement) -> bool {
         match rte.val.as_ref().unwrap() {
             Val::Plain(_) | Val::Bold(_) | Val::Italic(_) | Val::Underline(_) | Val::Strikethrough(_) |
-            Val::PrefmtInline(_) | Val::Blockquote(_) | Val::Spoiler(_) => {
+            Val::PrefmtInline(_) | Val::Blockquote(_) | Val::Spoiler(_) | Val::Mention(_) |
+            Val::Hashtag(_) | Val::Cashtag(_) | Val::BotCommand(_) | Val::Email(_) | Val::Phone(_) |
+            Val::BankCard(_) => {
                 rte.get_text().unwrap().chars().all(|c| c.is_whitespace())
             }
-            Val::Link(_) | Val::PrefmtBlock(_) => {
+            Val::Link(_) | Val::PrefmtBlock(_) | Val::CustomEmoji(_) => {
                 false
             }
         }
@@ -1191,6 +1425,110 @@ fn parse_inline_bot_buttons(json_path: &str, json: &BorrowedValue) -> Result<Vec
     Ok(result)
 }
 
+fn parse_web_page(json_path: &str, json: &BorrowedValue) -> Result<ContentWebPage> {
+    let mut url_option = None;
+    let mut web_page = ContentWebPage::default();
+    parse_bw_as_object(json, json_path, |CB { key, value: v, wrong_key_action }| match key {
+        "url" => {
+            url_option = as_string_option!(v, json_path, "url");
+            Ok(())
+        }
+        "title" => {
+            web_page.title_option = as_string_option!(v, json_path, "title");
+            Ok(())
+        }
+        "description" => {
+            web_page.description_option = as_string_option!(v, json_path, "description");
+            Ok(())
+        }
+        "site_name" => {
+            web_page.site_name_option = as_string_option!(v, json_path, "site_name");
+            Ok(())
+        }
+        "photo" => {
+            web_page.image_path_option = as_string_option!(v, json_path, "photo").and_then(path_option);
+            Ok(())
+        }
+        _ => wrong_key_action()
+    })?;
+    web_page.url = url_option.with_context(|| format!("{json_path}: url not set"))?;
+    Ok(web_page)
+}
+
+/// Telegram only lists a few recent reactors for each reaction, the rest are just counted and can't be preserved.
+fn parse_reactions(json_path: &str,
+                   json: &BorrowedValue,
+                   ds_uuid: &PbUuid,
+                   users: &mut Users,
+                   member_ids: &mut HashSet<UserId, Hasher>) -> Result<Vec<Reaction>> {
+    let mut result = vec![];
+    for (idx, reaction_json) in as_array!(json, json_path).iter().enumerate() {
+        let json_path = format!("{json_path}[{idx}]");
+        let mut emoji_option = None;
+        let mut custom_sticker_path_option = None;
+        parse_bw_as_object(reaction_json, &json_path, |CB { key, value: v, wrong_key_action }| match key {
+            "type" => match as_str!(v, json_path, "type") {
+                "emoji" | "custom_emoji" | "paid" => consume(),
+                etc => err!("Unknown reaction type '{etc}'"),
+            },
+            "count" => consume(),
+            "emoji" => {
+                emoji_option = as_string_option!(v, json_path, "emoji");
+                Ok(())
+            }
+            "document_id" => {
+                custom_sticker_path_option = as_string_option!(v, json_path, "document_id").and_then(path_option);
+                Ok(())
+            }
+            "recent" => consume(),
+            _ => wrong_key_action()
+        })?;
+
+        let recent = match as_object!(reaction_json, json_path).get("recent") {
+            Some(v) => as_array!(v, json_path, "recent").iter().collect_vec(),
+            None => vec![],
+        };
+        for reactor_json in recent {
+            let mut short_user = ShortUser::default();
+            let mut timestamp_option = None;
+            let mut date_option = None;
+            parse_bw_as_object(reactor_json, &json_path, |CB { key, value: v, wrong_key_action }| match key {
+                "from" => {
+                    short_user.full_name_option = as_string_option!(v, json_path, "from");
+                    Ok(())
+                }
+                "from_id" => {
+                    short_user.id = parse_user_id(v)?;
+                    Ok(())
+                }
+                "date_unixtime" => {
+                    timestamp_option = Some(parse_timestamp(as_str!(v, json_path, "date_unixtime"))?);
+                    Ok(())
+                }
+                "date" => {
+                    date_option = Some(*parse_datetime(as_str!(v, json_path, "date"))?);
+                    Ok(())
+                }
+                _ => wrong_key_action()
+            })?;
+
+            if *short_user.id >= USER_ID_SHIFT {
+                short_user.id = UserId(*short_user.id - USER_ID_SHIFT);
+            }
+            let from_id = append_user(short_user, users, ds_uuid)?;
+            member_ids.insert(from_id);
+
+            result.push(Reaction {
+                emoji_option: emoji_option.clone(),
+                custom_sticker_path_option: custom_sticker_path_option.clone(),
+                from_id: *from_id,
+                timestamp_option: timestamp_option.or(date_option),
+            });
+        }
+    }
+    Ok(result)
+}
+
 //
 // Other
 //
@@ -1,14 +1,25 @@
 use super::*;
 
 pub(super) fn parse(root_obj: &Object,
-                    ds_uuid: &PbUuid,
-                    myself: &mut User) -> Result<(Users, Vec<ChatWithMessages>)> {
+                    ctx: &mut LoadContext,
+                    output: Output) -> Result<Users> {
+    let ds_uuid = &output.ds.uuid.clone();
     let mut users: Users = Default::default();
-    let mut chats_with_messages: Vec<ChatWithMessages> = vec![];
+    let mut myself = User {
+        ds_uuid: ds_uuid.clone(),
+        ..Default::default()
+    };
+    let mut output_option = Some(output);
 
     parse_object(root_obj, "root", |CB { key, value, wrong_key_action }| match key {
         "about" => consume(),
-        "profile_pictures" => consume(),
+        "profile_pictures" => {
+            // Latest picture goes first
+            if let Some(picture) = as_array!(value, "profile_pictures").first() {
+                myself.img_path_option = Some(get_field_str!(picture, "profile_pictures[0]", "photo").to_owned());
+            }
+            Ok(())
+        }
         "frequent_contacts" => consume(),
         "other_data" => consume(),
         "stories" => consume(),
@@ -51,7 +62,10 @@ pub(super) fn parse(root_obj: &Object,
                     myself.phone_number_option = Some(as_string!(v, json_path, "phone_number"));
                     Ok(())
                 }
-                "bio" => consume(),
+                "bio" => {
+                    myself.bio_option = as_string_option!(v, json_path, "bio");
+                    Ok(())
+                }
                 _ => wrong_key_action()
             })?;
             if myself.id == 0 {
@@ -66,15 +80,19 @@ pub(super) fn parse(root_obj: &Object,
 
             let json_path = "chats";
 
+            users.insert(myself.clone());
+            let mut writer = output_option.take().context("Duplicate chats section!")?
+                .start(&users.id_to_user[&myself.id()])?;
+
             let chats_arr = as_object!(value, "chats")
                 .get("list").context("No chats list in dataset!")?
                 .as_array().with_context(|| format!("{json_path} list is not an array!"))?;
 
             for v in chats_arr {
                 if let Some(mut cwm) = parse_chat(json_path, as_object!(v, json_path, "chat"),
-                                                  ds_uuid, Some(&myself.id()), &mut users)? {
+                                                  ds_uuid, Some(&myself.id()), &mut users, ctx)? {
                     cwm.chat.ds_uuid = ds_uuid.clone();
-                    chats_with_messages.push(cwm);
+                    writer.add_chat(cwm, &users)?;
                 }
             }
 
@@ -87,7 +105,14 @@ pub(super) fn parse(root_obj: &Object,
         _ => wrong_key_action()
     })?;
 
-    users.insert(myself.clone());
+    if let Some(output) = output_option {
+        // No chats to write, but myself should still be there
+        if myself.id == 0 {
+            bail!("personal_information section is missing!");
+        }
+        users.insert(myself.clone());
+        output.start(&users.id_to_user[&myself.id()])?;
+    }
 
-    Ok((users, chats_with_messages))
+    Ok(users)
 }
@@ -7,6 +7,7 @@ use pretty_assertions::{assert_eq, assert_ne};
 use crate::{NoChooser, User};
 use crate::dao::ChatHistoryDao;
 use crate::entity_utils::*;
+use crate::loader::{LoadMode, PLACEHOLDER_MARKER};
 use crate::protobuf::history::content::SealedValueOptional::*;
 use crate::protobuf::history::message::*;
 use crate::protobuf::history::message_service::SealedValueOptional::*;
@@ -41,6 +42,10 @@ fn loading_2020_01() -> EmptyRes {
             last_name_option: Some("Www".to_owned()),
             username_option: None,
             phone_number_option: Some("+998 90 9998877".to_owned()),
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: None,
+            img_path_option: None,
         },
         member.to_user(ds_uuid),
         User {
@@ -50,6 +55,10 @@ fn loading_2020_01() -> EmptyRes {
             last_name_option: Some("Eeeeeeeeee".to_owned()),
             username_option: None,
             phone_number_option: Some("+7 999 333 44 55".to_owned()),
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: None,
+            img_path_option: None,
         },
         ShortUser::new_name_str(UserId(310242343), "Vlllllll").to_user(ds_uuid),
         ShortUser::new_name_str(UserId(333333333), "Ddddddd Uuuuuuuu").to_user(ds_uuid),
@@ -60,6 +69,10 @@ fn loading_2020_01() -> EmptyRes {
             last_name_option: None,
             username_option: None,
             phone_number_option: Some("+998 90 1112233".to_owned()),
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: None,
+            img_path_option: None,
         },
         ShortUser::new_name_str(UserId(666666666), "Iiiii Kkkkkkkkkk").to_user(ds_uuid),
         User {
@@ -69,6 +82,10 @@ fn loading_2020_01() -> EmptyRes {
             last_name_option: Some("Vvvvvvvvv".to_owned()),
             username_option: None,
             phone_number_option: Some("+7 951 123 45 67".to_owned()),
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: None,
+            img_path_option: None,
         },
     ];
 
@@ -77,6 +94,54 @@ fn loading_2020_01() -> EmptyRes {
 
     assert_eq!(dao.cwms_single_ds().len(), 4);
 
+    // Group chat, membership is derived from service messages
+    {
+        let chat = dao.cwms_single_ds().into_iter()
+            .find(|c| c.chat.name_option.as_deref() == Some("ppppppp gggggg"))
+            .unwrap().chat;
+        let ts = |s: &str| Some(dt(s, None).timestamp());
+        assert_eq!(chat.member_details, vec![
+            ChatMemberDetails {
+                user_id: myself.id,
+                role_option: None,
+                joined_timestamp_option: ts("2016-11-09 00:26:24"),
+                left_timestamp_option: None,
+            },
+            ChatMemberDetails {
+                user_id: 22222222,
+                role_option: Some(ChatMemberRole::Owner as i32),
+                joined_timestamp_option: ts("2016-11-09 00:26:24"),
+                left_timestamp_option: None,
+            },
+            ChatMemberDetails {
+                user_id: 44444444,
+                role_option: None,
+                joined_timestamp_option: ts("2016-11-09 00:26:24"),
+                left_timestamp_option: None,
+            },
+            ChatMemberDetails {
+                // Left by themselves
+                user_id: 333333333,
+                role_option: None,
+                joined_timestamp_option: None,
+                left_timestamp_option: ts("2016-11-09 00:29:03"),
+            },
+            ChatMemberDetails {
+                user_id: 555555555,
+                role_option: None,
+                joined_timestamp_option: ts("2016-11-09 00:26:24"),
+                left_timestamp_option: None,
+            },
+            ChatMemberDetails {
+                // Invited later
+                user_id: 777777777,
+                role_option: None,
+                joined_timestamp_option: ts("2016-11-09 22:56:24"),
+                left_timestamp_option: None,
+            },
+        ]);
+    }
+
     // "Ordered" chat
     {
         let cwm = dao.cwms_single_ds().into_iter()
@@ -93,6 +158,9 @@ fn loading_2020_01() -> EmptyRes {
             member_ids: vec![myself.id, *member.id],
             msg_count: 5,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: None,
+            member_details: vec![],
         });
 
         let msgs = dao.first_messages(&chat, 99999)?;
@@ -131,7 +199,10 @@ fn loading_2021_05() -> EmptyRes {
 
     let ds_uuid = &dao.ds_uuid();
     let myself = dao.myself_single_ds();
-    assert_eq!(myself, expected_myself(ds_uuid));
+    assert_eq!(myself, User {
+        img_path_option: Some("profile_pictures/photo_3@25-12-2018_16-12-45.jpg".to_owned()),
+        ..expected_myself(ds_uuid)
+    });
 
     // We only know of myself + two users (other's IDs aren't known), as well as service "member".
     let service_member =
@@ -143,6 +214,10 @@ fn loading_2021_05() -> EmptyRes {
         last_name_option: Some("Www".to_owned()),
         username_option: None,
         phone_number_option: Some("+998 90 9998877".to_owned()), // Taken from contacts list
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     };
     let member2 = User {
         ds_uuid: ds_uuid.clone(),
@@ -151,6 +226,10 @@ fn loading_2021_05() -> EmptyRes {
         last_name_option: Some("Eeeeeeeeee".to_owned()),
         username_option: None,
         phone_number_option: Some("+7 999 333 44 55".to_owned()), // Taken from contacts list
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     };
     assert_eq!(dao.users_single_ds().len(), 4);
     assert_eq!(dao.users_single_ds().iter().collect_vec(), vec![&myself, &service_member, &member1, &member2]);
@@ -174,6 +253,20 @@ fn loading_2021_05() -> EmptyRes {
             member_ids: vec![myself.id, service_member.id, member1.id, member2.id],
             msg_count: 3,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: Some(dt("2016-11-09 00:26:24", None).timestamp()),
+            member_details: vec![ChatMemberDetails {
+                user_id: member1.id,
+                role_option: Some(ChatMemberRole::Owner as i32),
+                joined_timestamp_option: Some(dt("2016-11-09 00:26:24", None).timestamp()),
+                left_timestamp_option: None,
+            }, ChatMemberDetails {
+                // Listed when the group was created
+                user_id: member2.id,
+                role_option: None,
+                joined_timestamp_option: Some(dt("2016-11-09 00:26:24", None).timestamp()),
+                left_timestamp_option: None,
+            }],
         });
 
         let msgs = dao.first_messages(&chat, 99999)?;
@@ -236,6 +329,15 @@ fn loading_2021_06_supergroup() -> EmptyRes {
             member_ids: vec![myself.id, u222222222.id, u333333333.id, u444444444.id],
             msg_count: 4,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: None,
+            // Invited member
+            member_details: vec![ChatMemberDetails {
+                user_id: u444444444.id,
+                role_option: None,
+                joined_timestamp_option: Some(dt("2020-12-22 23:11:21", None).timestamp()),
+                left_timestamp_option: None,
+            }],
         });
 
         let msgs = dao.first_messages(&chat, 99999)?;
@@ -251,6 +353,9 @@ fn loading_2021_06_supergroup() -> EmptyRes {
             typed: Some(message_service!(GroupInviteMembers(MessageServiceGroupInviteMembers {
                 members: vec![u444444444.first_name_option.unwrap()]
             }))),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
 
         assert_eq!(msgs[1], Message {
@@ -271,7 +376,13 @@ fn loading_2021_06_supergroup() -> EmptyRes {
                 forward_from_name_option: None,
                 reply_to_message_id_option: None,
                 content_option: None,
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
 
         assert_eq!(msgs[2], Message {
@@ -292,7 +403,13 @@ fn loading_2021_06_supergroup() -> EmptyRes {
                 forward_from_name_option: None,
                 reply_to_message_id_option: None,
                 content_option: None,
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[3], Message {
             internal_id: 3,
@@ -314,7 +431,13 @@ fn loading_2021_06_supergroup() -> EmptyRes {
                         vcard_path_option: None,
                     }))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
     };
     Ok(())
@@ -339,6 +462,10 @@ fn loading_2021_07() -> EmptyRes {
         last_name_option: Some("Eeeeeeeeee".to_owned()),
         username_option: None,
         phone_number_option: Some("+7 999 333 44 55".to_owned()), // Taken from contacts list
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     };
     assert_eq!(dao.users_single_ds().len(), 2);
     assert_eq!(dao.users_single_ds().iter().collect_vec(), vec![&myself, &member]);
@@ -362,6 +489,9 @@ fn loading_2021_07() -> EmptyRes {
             member_ids: vec![myself.id, member.id],
             msg_count: 2,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: None,
+            member_details: vec![],
         });
 
         let msgs = dao.first_messages(&chat, 99999)?;
@@ -379,6 +509,9 @@ fn loading_2021_07() -> EmptyRes {
                 discard_reason_option: None,
                 members: vec!["Www Wwwwww".to_owned()]
             }))),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[1], Message {
             internal_id: 1,
@@ -392,6 +525,9 @@ fn loading_2021_07() -> EmptyRes {
                 discard_reason_option: None,
                 members: vec!["Myself".to_owned()]
             }))),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
     };
     Ok(())
@@ -419,6 +555,10 @@ fn loading_2023_01() -> EmptyRes {
         last_name_option: Some("Eeeeeeeeee".to_owned()),
         username_option: None,
         phone_number_option: Some("+7 999 333 44 55".to_owned()), // Taken from contacts list
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     };
     let channel_user = User {
         ds_uuid: ds_uuid.clone(),
@@ -427,6 +567,10 @@ fn loading_2023_01() -> EmptyRes {
         last_name_option: None,
         username_option: None,
         phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     };
     assert_eq!(dao.users_single_ds().len(), 3);
     assert_eq!(dao.users_single_ds().iter().collect_vec(), vec![&myself, &member, &channel_user]);
@@ -450,6 +594,9 @@ fn loading_2023_01() -> EmptyRes {
             member_ids: vec![myself.id, member.id, channel_user.id],
             msg_count: 6,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: None,
+            member_details: vec![],
         });
 
         let msgs = dao.first_messages(&chat, 99999)?;
@@ -466,6 +613,9 @@ fn loading_2023_01() -> EmptyRes {
             typed: Some(message_service!(GroupMigrateFrom(MessageServiceGroupMigrateFrom {
                 title: "My Group".to_owned()
             }))),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[1], Message {
             internal_id: 1,
@@ -475,6 +625,9 @@ fn loading_2023_01() -> EmptyRes {
             text: vec![],
             searchable_string: "".to_owned(),
             typed: Some(message_service!(GroupMigrateTo(MessageServiceGroupMigrateTo {}))),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[2], Message {
             internal_id: 2,
@@ -482,11 +635,17 @@ fn loading_2023_01() -> EmptyRes {
             timestamp: dt("2016-11-17 17:57:40", Some(&offset)).timestamp(),
             from_id: member.id,
             text: vec![
-                // Two plaintext elements are concatenated
                 RichTextElement {
-                    searchable_string: "this contains a lot of stuff: 😁".to_owned(),
+                    searchable_string: "this contains a lot of stuff:".to_owned(),
                     val: Some(rich_text_element::Val::Plain(RtePlain {
-                        text: "this contains a lot of stuff: 😁".to_owned(),
+                        text: "this contains a lot of stuff: ".to_owned(),
+                    })),
+                },
+                RichTextElement {
+                    searchable_string: "😁".to_owned(),
+                    val: Some(rich_text_element::Val::CustomEmoji(RteCustomEmoji {
+                        text: "😁".to_owned(),
+                        path_option: Some("chats/chat_004/stickers/sticker (62).webp".to_owned()),
                     })),
                 },
                 RichTextElement {
@@ -511,7 +670,13 @@ fn loading_2023_01() -> EmptyRes {
                 forward_from_name_option: None,
                 reply_to_message_id_option: None,
                 content_option: None,
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[3], Message {
             internal_id: 3,
@@ -523,6 +688,9 @@ fn loading_2023_01() -> EmptyRes {
             typed: Some(message_service!(GroupInviteMembers(MessageServiceGroupInviteMembers {
                 members: vec![UNKNOWN.to_owned()]
             }))),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[4], Message {
             internal_id: 4,
@@ -532,6 +700,9 @@ fn loading_2023_01() -> EmptyRes {
             text: vec![],
             searchable_string: "".to_owned(),
             typed: Some(message_service!(GroupDeletePhoto(MessageServiceGroupDeletePhoto {}))),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[5], Message {
             internal_id: 5,
@@ -548,6 +719,9 @@ fn loading_2023_01() -> EmptyRes {
                     is_one_time: false,
                 }
             }))),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
     };
     Ok(())
@@ -572,6 +746,10 @@ fn loading_2023_08() -> EmptyRes {
         last_name_option: None,
         username_option: None,
         phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     };
     assert_eq!(dao.users_single_ds().len(), 2);
     assert_eq!(dao.users_single_ds().iter().collect_vec(), vec![&myself, &unnamed_user]);
@@ -595,6 +773,15 @@ fn loading_2023_08() -> EmptyRes {
             member_ids: vec![myself.id, unnamed_user.id],
             msg_count: 2,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: None,
+            // Joined by link, unnamed actor is still known by ID
+            member_details: vec![ChatMemberDetails {
+                user_id: unnamed_user.id,
+                role_option: None,
+                joined_timestamp_option: Some(1664352868),
+                left_timestamp_option: None,
+            }],
         });
 
         let msgs: &Vec<Message> = &cwm.messages;
@@ -611,6 +798,9 @@ fn loading_2023_08() -> EmptyRes {
             typed: Some(message_service!(GroupInviteMembers(MessageServiceGroupInviteMembers {
                 members: vec![UNNAMED.to_owned()]
             }))),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[1], Message {
             internal_id: 1,
@@ -632,7 +822,13 @@ fn loading_2023_08() -> EmptyRes {
                 forward_from_name_option: None,
                 reply_to_message_id_option: None,
                 content_option: None,
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
     };
     Ok(())
@@ -657,6 +853,10 @@ fn loading_2023_10_audio_video() -> EmptyRes {
         last_name_option: None,
         username_option: None,
         phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     };
     assert_eq!(dao.users_single_ds().len(), 2);
     assert_eq!(dao.users_single_ds().iter().collect_vec(), vec![&myself, &unnamed_user]);
@@ -680,6 +880,9 @@ fn loading_2023_10_audio_video() -> EmptyRes {
             member_ids: vec![myself.id, unnamed_user.id],
             msg_count: 4,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: None,
+            member_details: vec![],
         });
 
         let msgs: &Vec<Message> = &cwm.messages;
@@ -707,7 +910,13 @@ fn loading_2023_10_audio_video() -> EmptyRes {
                         thumbnail_path_option: None,
                     }))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[1], Message {
             internal_id: 1,
@@ -731,7 +940,13 @@ fn loading_2023_10_audio_video() -> EmptyRes {
                         thumbnail_path_option: Some("audio_file.mp3_thumb.jpg".to_owned()),
                     }))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[2], Message {
             internal_id: 2,
@@ -758,7 +973,13 @@ fn loading_2023_10_audio_video() -> EmptyRes {
                         is_one_time: false,
                     }))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[3], Message {
             internal_id: 3,
@@ -785,7 +1006,13 @@ fn loading_2023_10_audio_video() -> EmptyRes {
                         is_one_time: false,
                     }))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
     };
     Ok(())
@@ -831,7 +1058,41 @@ fn loading_2024_01() -> EmptyRes {
     assert_eq!(msgs[1].text, vec![
         RichText::make_plain("Messages will be auto-deleted in 31 day(s)".to_owned()),
     ]);
-    assert_matches!(&msgs[1].typed, Some(message_service_pat!(Notice(_))));
+    assert_eq!(msgs[1].typed(), &message_service!(SetMessagesTtl(MessageServiceSetMessagesTtl {
+        ttl_sec_option: Some(31 * 24 * 60 * 60),
+    })));
+
+    Ok(())
+}
+
+#[test]
+fn loading_2024_01_lenient() -> EmptyRes {
+    let tmp_dir = TmpDir::new();
+    let json = fs::read_to_string(resource("telegram_2024-01").join("result.json"))?;
+    let json = json.replacen(r#""reply_to_peer_id": "channel9876543210","#,
+                             r#""reply_to_peer_id": "channel9876543210", "mystery_field": 42,"#, 1);
+    fs::write(tmp_dir.path.join("result.json"), json)?;
+
+    assert!(LOADER.load(&tmp_dir.path, &NoChooser).is_err());
+
+    let mut ctx = LoadContext::new(LoadMode::Lenient);
+    let dao = LOADER.load_with_context(&tmp_dir.path, &NoChooser, &mut ctx)?;
+
+    let warnings = ctx.into_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].location.ends_with(", message 11111"), "{}", warnings[0].location);
+    assert!(warnings[0].message.contains("mystery_field"));
+
+    let msgs = &dao.cwms_single_ds()[0].messages;
+    assert_eq!(msgs.len(), 2);
+    assert_eq!(msgs[0].source_id_option, Some(11111));
+    assert_eq!(msgs[0].timestamp, 1665499755);
+    assert_eq!(msgs[0].from_id, 11111111);
+    assert_eq!(msgs[0].text.len(), 2);
+    assert_eq!(msgs[0].text[0], RichText::make_italic(PLACEHOLDER_MARKER.to_owned()));
+    assert!(matches!(&msgs[0].text[1].val, Some(rich_text_element::Val::PrefmtBlock(RtePrefmtBlock { text, .. }))
+                         if text.contains("mystery_field")));
+    assert_matches!(&msgs[1].typed, Some(message_service_pat!(SetMessagesTtl(_))));
 
     Ok(())
 }
@@ -846,6 +1107,7 @@ fn loading_2024_02() -> EmptyRes {
         LOADER.load(&res, &NoChooser)?;
 
     let cwm = &dao.cwms_single_ds()[0];
+    assert_eq!(cwm.chat.created_timestamp_option, Some(1665499755));
     let msgs = &cwm.messages;
     assert_eq!(msgs.len() as i32, 2);
 
@@ -867,12 +1129,259 @@ fn loading_2024_02() -> EmptyRes {
             forward_from_name_option: Some("Forwarded From Name".to_owned()),
             reply_to_message_id_option: None,
             content_option: None,
+            extra_contents: vec![],
+            forward_origin_option: Some(ForwardOrigin {
+                from_id_option: Some(22222222),
+                chat_name_option: Some("Saved From Name".to_owned()),
+                timestamp_option: None,
+                source_id_option: None,
+                from_name_option: Some("Forwarded From Name".to_owned()),
+            }),
+            ttl_sec_option: None,
         }),
+        reactions: vec![],
+        delivery_status_option: None,
+        receipts: vec![],
     });
 
     Ok(())
 }
 
+#[test]
+fn loading_2024_02_forward_details() -> EmptyRes {
+    let res = resource("telegram_2024-02_create-channel_saved-from");
+    let json = fs::read_to_string(res.join("result.json"))?;
+
+    let load_forward_origin = |json: String| -> Result<Option<ForwardOrigin>> {
+        let tmp_dir = TmpDir::new();
+        fs::write(tmp_dir.path.join("result.json"), json)?;
+        let dao = LOADER.load(&tmp_dir.path, &NoChooser)?;
+        let cwms = dao.cwms_single_ds();
+        let message_regular_pat! { forward_origin_option, .. } = cwms[0].messages[1].typed()
+            else { bail!("Message is not regular") };
+        Ok(forward_origin_option.clone())
+    };
+
+    // Original date is taken into account when present
+    let with_date = json.replacen(r#""saved_from": "Saved From Name","#,
+                                  r#""saved_from": "Saved From Name",
+      "forwarded_date": "2022-10-01T12:00:00",
+      "forwarded_date_unixtime": "1664625600","#, 1);
+    assert_eq!(load_forward_origin(with_date)?, Some(ForwardOrigin {
+        from_id_option: Some(22222222),
+        chat_name_option: Some("Saved From Name".to_owned()),
+        timestamp_option: Some(1664625600),
+        source_id_option: None,
+        from_name_option: Some("Forwarded From Name".to_owned()),
+    }));
+
+    // Channel is not a user, so only its name is kept
+    let from_channel = json.replacen(r#""forwarded_from_id": "user22222222","#,
+                                     r#""forwarded_from_id": "channel1234567890","#, 1);
+    assert_eq!(load_forward_origin(from_channel)?, Some(ForwardOrigin {
+        from_id_option: None,
+        chat_name_option: Some("Saved From Name".to_owned()),
+        timestamp_option: None,
+        source_id_option: None,
+        from_name_option: Some("Forwarded From Name".to_owned()),
+    }));
+
+    Ok(())
+}
+
+#[test]
+fn loading_2024_03_reactions() -> EmptyRes {
+    let res = resource("telegram_2024-03_reactions");
+    LOADER.looks_about_right(&res)?;
+
+    let dao =
+        LOADER.load(&res, &NoChooser)?;
+
+    let cwm = &dao.cwms_single_ds()[0];
+    assert_eq!(cwm.chat.member_ids, vec![11111111, 22222222]);
+
+    let msgs = &cwm.messages;
+    assert_eq!(msgs.len() as i32, 2);
+
+    // Paid reaction has no recent reactors listed and is skipped
+    assert_eq!(msgs[0].reactions, vec![
+        Reaction {
+            emoji_option: Some("👍".to_owned()),
+            custom_sticker_path_option: None,
+            from_id: 22222222,
+            timestamp_option: Some(dt("2024-03-01 12:01:00", None).timestamp()),
+        },
+        Reaction {
+            emoji_option: Some("👍".to_owned()),
+            custom_sticker_path_option: None,
+            from_id: 11111111,
+            timestamp_option: Some(dt("2024-03-01 12:02:00", None).timestamp()),
+        },
+        Reaction {
+            emoji_option: None,
+            custom_sticker_path_option: Some("stickers/AnimatedSticker.tgs".to_owned()),
+            from_id: 22222222,
+            timestamp_option: Some(dt("2024-03-01 12:03:00", None).timestamp()),
+        },
+    ]);
+    assert_eq!(msgs[0].files_relative(), vec!["stickers/AnimatedSticker.tgs"]);
+
+    assert_eq!(msgs[1].reactions, vec![
+        Reaction {
+            emoji_option: None,
+            custom_sticker_path_option: None,
+            from_id: 22222222,
+            timestamp_option: Some(dt("2024-03-01 12:11:00", None).timestamp()),
+        },
+    ]);
+
+    Ok(())
+}
+
+#[test]
+fn loading_2024_04_web_page() -> EmptyRes {
+    let res = resource("telegram_2024-04_web-page");
+    LOADER.looks_about_right(&res)?;
+
+    let dao =
+        LOADER.load(&res, &NoChooser)?;
+
+    let cwm = &dao.cwms_single_ds()[0];
+    let msgs = &cwm.messages;
+    assert_eq!(msgs.len() as i32, 2);
+
+    let web_page = |m: &Message| match m.typed() {
+        message_regular_pat! { content_option: Some(Content { sealed_value_optional: Some(WebPage(v)) }), .. } => v.clone(),
+        etc => panic!("Not a web page: {etc:?}"),
+    };
+
+    assert_eq!(msgs[0].text, vec![RichText::make_link(Some("https://example.com/".to_owned()),
+                                                      "https://example.com/".to_owned(), false)]);
+    assert_eq!(web_page(&msgs[0]), ContentWebPage {
+        url: "https://example.com/".to_owned(),
+        title_option: Some("Example Domain".to_owned()),
+        description_option: Some("This domain is for use in illustrative examples in documents.".to_owned()),
+        site_name_option: Some("Example".to_owned()),
+        image_path_option: Some("photos/preview.jpg".to_owned()),
+    });
+    assert_eq!(msgs[0].files_relative(), vec!["photos/preview.jpg"]);
+    assert!(msgs[0].searchable_string.contains("Example Domain"));
+
+    assert_eq!(web_page(&msgs[1]), ContentWebPage {
+        url: "https://example.org/".to_owned(),
+        title_option: Some("Example Domain".to_owned()),
+        description_option: None,
+        site_name_option: None,
+        image_path_option: None,
+    });
+
+    Ok(())
+}
+
+#[test]
+fn loading_2024_05_mentions() -> EmptyRes {
+    let res = resource("telegram_2024-05_mentions");
+    LOADER.looks_about_right(&res)?;
+
+    let dao =
+        LOADER.load(&res, &NoChooser)?;
+
+    let cwm = &dao.cwms_single_ds()[0];
+    let msgs = &cwm.messages;
+    assert_eq!(msgs.len() as i32, 1);
+
+    // Only myself has a known username
+    assert_eq!(msgs[0].text, vec![
+        RichText::make_plain("Hey ".to_owned()),
+        RichText::make_mention(None, "bbbbb_user".to_owned()),
+        RichText::make_plain(", ask ".to_owned()),
+        RichText::make_mention(Some(33333333), "Ccccc Cccccc".to_owned()),
+        RichText::make_plain(" and ".to_owned()),
+        RichText::make_mention(Some(11111111), "Aaaaa_Me".to_owned()),
+        RichText::make_plain("!".to_owned()),
+    ]);
+    assert!(msgs[0].searchable_string.contains("bbbbb_user"));
+
+    Ok(())
+}
+
+#[test]
+fn loading_2024_06_entities() -> EmptyRes {
+    let res = resource("telegram_2024-06_entities");
+    LOADER.looks_about_right(&res)?;
+
+    let dao =
+        LOADER.load(&res, &NoChooser)?;
+
+    let cwm = &dao.cwms_single_ds()[0];
+    let msgs = &cwm.messages;
+    assert_eq!(msgs.len() as i32, 1);
+
+    let space = || RichText::make_plain(" ".to_owned());
+    assert_eq!(msgs[0].text, vec![
+        RichText::make_hashtag("#news".to_owned()),
+        space(),
+        RichText::make_cashtag("$USD".to_owned()),
+        space(),
+        RichText::make_bot_command("/start".to_owned()),
+        space(),
+        RichText::make_email("me@example.com".to_owned()),
+        space(),
+        RichText::make_phone("+1 234 567 89 00".to_owned()),
+        space(),
+        RichText::make_bank_card("4111 1111 1111 1111".to_owned()),
+        space(),
+        RichText::make_custom_emoji("😁".to_owned(), Some("stickers/sticker.webp".to_owned())),
+        RichText::make_custom_emoji("👍".to_owned(), None),
+    ]);
+    assert_eq!(msgs[0].searchable_string,
+               "#news $USD /start me@example.com +1 234 567 89 00 4111 1111 1111 1111 😁 👍");
+    assert_eq!(msgs[0].files_relative(), vec!["stickers/sticker.webp"]);
+
+    Ok(())
+}
+
+/// Hand-made export shaped like Telegram Desktop ones: album items are separate messages sent at the same second,
+/// with only the first one having a caption.
+#[test]
+fn loading_2024_07_album() -> EmptyRes {
+    let res = resource("telegram_2024-07_album");
+    LOADER.looks_about_right(&res)?;
+
+    let dao =
+        LOADER.load(&res, &NoChooser)?;
+
+    let cwm = &dao.cwms_single_ds()[0];
+    let msgs = &cwm.messages;
+    assert_eq!(msgs.len() as i32, 2);
+    assert_eq!(cwm.chat.msg_count, 2);
+
+    let photo = |path: &str, width: i32, height: i32| Content {
+        sealed_value_optional: Some(Photo(ContentPhoto {
+            path_option: Some(path.to_owned()),
+            width,
+            height,
+            is_one_time: false,
+        }))
+    };
+
+    assert_eq!(msgs[0].source_id_option, Some(11111));
+    assert_eq!(msgs[0].text, vec![RichText::make_plain("Album caption".to_owned())]);
+    match msgs[0].typed() {
+        Typed::Regular(mr) => {
+            assert_eq!(mr.content_option, Some(photo("photos/photo_1.jpg", 800, 600)));
+            assert_eq!(mr.extra_contents, vec![photo("photos/photo_2.jpg", 600, 800)]);
+        }
+        etc => panic!("Not a regular message: {etc:?}"),
+    }
+    assert_eq!(msgs[0].files_relative(), vec!["photos/photo_1.jpg", "photos/photo_2.jpg"]);
+
+    assert_eq!(msgs[1].source_id_option, Some(11113));
+    assert_eq!(msgs[1].files_relative(), vec!["photos/photo_3.jpg"]);
+
+    Ok(())
+}
+
 #[test]
 fn inline_bot_buttons() -> EmptyRes {
     let res = resource("telegram_2024-01_inline-bot-buttons");
@@ -928,5 +1437,9 @@ fn expected_myself(ds_uuid: &PbUuid) -> User {
         last_name_option: Some("Aaaaaaaaaaa".to_owned()),
         username_option: Some("@frozenspider".to_owned()),
         phone_number_option: Some("+998 91 1234567".to_owned()),
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: Some("There should be bio, shouldn't there?".to_owned()),
+        img_path_option: None,
     }
 }
@@ -3,6 +3,7 @@ use std::fs;
 use rusqlite::Connection;
 
 use crate::loader::DataLoader;
+use crate::loader::sink::DatasetWriter;
 
 use super::*;
 
@@ -26,10 +27,13 @@ type UserKey = String;
 type Users = HashMap<UserKey, User>;
 
 impl<H: HttpClient + 'static> TinderAndroidDataLoader<H> {
+    /// GIFs are downloaded as they aren't stored locally
+    const NEEDS_NETWORK: bool = true;
+
     fn tweak_conn(&self, _path: &Path, _conn: &Connection) -> EmptyRes { Ok(()) }
 
-    fn normalize_users(&self, users: Users, _cwms: &[ChatWithMessages]) -> Result<Vec<User>> {
-        let mut users = users.into_values().collect_vec();
+    fn initial_users(&self, users: &Users) -> Result<Vec<User>> {
+        let mut users = users.values().cloned().collect_vec();
         // Set myself to be a first member.
         users.sort_by_key(|u| if u.id == *MYSELF_ID { *UserId::MIN } else { u.id });
         Ok(users)
@@ -45,6 +49,10 @@ impl<H: HttpClient + 'static> TinderAndroidDataLoader<H> {
             last_name_option: None,
             username_option: None,
             phone_number_option: None,
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: None,
+            img_path_option: None,
         });
 
         let mut stmt = conn.prepare(r"SELECT * FROM match_person")?;
@@ -63,15 +71,23 @@ impl<H: HttpClient + 'static> TinderAndroidDataLoader<H> {
                 last_name_option: None,
                 username_option: None,
                 phone_number_option: None,
+                extra_phone_numbers: vec![],
+                emails: vec![],
+                bio_option: None,
+                img_path_option: None,
             });
         }
 
         Ok(users)
     }
 
-    fn parse_chats(&self, conn: &Connection, ds_uuid: &PbUuid, users: &Users, path: &Path) -> Result<Vec<ChatWithMessages>> {
-        let mut cwms = vec![];
-
+    fn parse_chats(&self,
+                   conn: &Connection,
+                   ds_uuid: &PbUuid,
+                   users: &Users,
+                   path: &Path,
+                   ctx: &mut LoadContext,
+                   writer: &mut DatasetWriter) -> EmptyRes {
         let downloaded_media_path = path.join(RELATIVE_MEDIA_DIR);
         fs::create_dir_all(&downloaded_media_path)?;
 
@@ -90,8 +106,8 @@ impl<H: HttpClient + 'static> TinderAndroidDataLoader<H> {
             let mut messages = vec![];
             while let Some(row) = rows.next()? {
                 // Source ID is way too large to fit into i64, so we use hash instead.
-                let source_id = row.get::<_, String>("id")?;
-                let source_id = hash_to_id(&source_id);
+                let source_key = row.get::<_, String>("id")?;
+                let source_id = hash_to_id(&source_key);
 
                 let timestamp = row.get::<_, i64>("sent_date")? / 1000;
 
@@ -100,29 +116,14 @@ impl<H: HttpClient + 'static> TinderAndroidDataLoader<H> {
                 let text = row.get::<_, String>("text")?;
                 let (text, content_option) = if text.starts_with("https://media.tenor.com/") {
                     // This is a GIF, let's download it and include it as a sticker.
-                    // Example: https://media.tenor.com/mYFQztB4EHoAAAAM/house-hugh-laurie.gif?width=220&height=226
-                    let hash = hash_to_id(&text);
-                    let filename = format!("{}.gif", hash);
-                    let gif_path = downloaded_media_path.join(&filename);
-                    if !gif_path.exists() {
-                        log::info!("Downloading {}", text);
-                        let bytes = self.http_client.get_bytes(&text)?;
-                        fs::write(&gif_path, bytes)?;
+                    match self.download_gif(&text, &downloaded_media_path, ctx) {
+                        Ok(content) => (vec![], Some(content)),
+                        Err(e) => {
+                            ctx.tolerate(message_location(&user.pretty_name(), &source_key), e)?;
+                            messages.push(placeholder_message(Some(source_id), timestamp, from_id, text));
+                            continue;
+                        }
                     }
-                    let (width, height) = {
-                        let split = text.split(['?', '&']).skip(1).collect_vec();
-                        (split.iter().find(|s| s.starts_with("width=")).map(|s| s[6..].parse()).unwrap_or(Ok(0))?,
-                         split.iter().find(|s| s.starts_with("height=")).map(|s| s[7..].parse()).unwrap_or(Ok(0))?)
-                    };
-                    (vec![], Some(Content {
-                        sealed_value_optional: Some(content::SealedValueOptional::Sticker(ContentSticker {
-                            path_option: Some(format!("{RELATIVE_MEDIA_DIR}/{filename}")),
-                            width: width * 2,
-                            height: height * 2,
-                            thumbnail_path_option: None,
-                            emoji_option: None,
-                        }))
-                    }))
                 } else {
                     (vec![RichText::make_plain(text)], None)
                 };
@@ -139,12 +140,16 @@ impl<H: HttpClient + 'static> TinderAndroidDataLoader<H> {
                         forward_from_name_option: None,
                         reply_to_message_id_option: None,
                         content_option,
+                        extra_contents: vec![],
+                        forward_origin_option: None,
+                        ttl_sec_option: None,
                     },
                 ));
             }
             messages.iter_mut().enumerate().for_each(|(i, m)| m.internal_id = i as i64);
 
-            cwms.push(ChatWithMessages {
+            ctx.chat_parsed(messages.len())?;
+            writer.add_chat(ChatWithMessages {
                 chat: Chat {
                     ds_uuid: ds_uuid.clone(),
                     id: user.id,
@@ -155,11 +160,41 @@ impl<H: HttpClient + 'static> TinderAndroidDataLoader<H> {
                     member_ids: vec![*MYSELF_ID, user.id],
                     msg_count: messages.len() as i32,
                     main_chat_id: None,
+                    description_option: None,
+                    created_timestamp_option: None,
+                    member_details: vec![],
                 },
                 messages,
-            });
+            }, |id| err!("User {} was not written upfront", *id))?;
         }
 
-        Ok(cwms)
+        Ok(())
+    }
+
+    /// Example: https://media.tenor.com/mYFQztB4EHoAAAAM/house-hugh-laurie.gif?width=220&height=226
+    fn download_gif(&self, url: &str, downloaded_media_path: &Path, ctx: &LoadContext) -> Result<Content> {
+        let (width, height) = {
+            let split = url.split(['?', '&']).skip(1).collect_vec();
+            (split.iter().find(|s| s.starts_with("width=")).map(|s| s[6..].parse()).unwrap_or(Ok(0))?,
+             split.iter().find(|s| s.starts_with("height=")).map(|s| s[7..].parse()).unwrap_or(Ok(0))?)
+        };
+        let hash = hash_to_id(url);
+        let filename = format!("{}.gif", hash);
+        let gif_path = downloaded_media_path.join(&filename);
+        if !gif_path.exists() {
+            log::info!("Downloading {}", url);
+            let bytes = self.http_client.get_bytes(url)?;
+            fs::write(&gif_path, bytes)?;
+            ctx.files_copied(1);
+        }
+        Ok(Content {
+            sealed_value_optional: Some(content::SealedValueOptional::Sticker(ContentSticker {
+                path_option: Some(format!("{RELATIVE_MEDIA_DIR}/{filename}")),
+                width: width * 2,
+                height: height * 2,
+                thumbnail_path_option: None,
+                emoji_option: None,
+            }))
+        })
     }
 }
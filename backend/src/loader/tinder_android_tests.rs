@@ -10,6 +10,7 @@ use pretty_assertions::{assert_eq, assert_ne};
 use crate::{NoChooser, User};
 use crate::dao::ChatHistoryDao;
 use crate::entity_utils::*;
+use crate::loader::{LoadContext, LoadMode};
 use crate::protobuf::history::content::SealedValueOptional::*;
 use crate::protobuf::history::message::*;
 use crate::protobuf::history::message_service::SealedValueOptional::*;
@@ -47,6 +48,10 @@ fn loading_2023_11() -> EmptyRes {
         last_name_option: None,
         username_option: None,
         phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     };
 
     assert_eq!(dao.users_single_ds(), vec![myself.clone(), member.clone()]);
@@ -66,6 +71,9 @@ fn loading_2023_11() -> EmptyRes {
             member_ids: vec![myself.id, member.id],
             msg_count: 2,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: None,
+            member_details: vec![],
         });
 
         let msgs = dao.first_messages(&chat, 99999)?;
@@ -79,6 +87,9 @@ fn loading_2023_11() -> EmptyRes {
             text: vec![RichText::make_plain("Sending you a text!".to_owned())],
             searchable_string: "Sending you a text!".to_owned(),
             typed: Some(MESSAGE_REGULAR_NO_CONTENT.clone()),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[1], Message {
             internal_id: 1,
@@ -101,7 +112,13 @@ fn loading_2023_11() -> EmptyRes {
                         emoji_option: None,
                     }))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
     }
 
@@ -111,6 +128,38 @@ fn loading_2023_11() -> EmptyRes {
     Ok(())
 }
 
+#[test]
+fn loading_2023_11_lenient() -> EmptyRes {
+    let (res, db_dir) = test_android::create_databases(RESOURCE_DIR, "2023-11", ".db", DB_FILENAME);
+    let _media_dir = TmpDir::new_at(db_dir.path.parent().unwrap().join(MEDIA_DIR));
+    let (key, url) = {
+        let conn = Connection::open(&res)?;
+        conn.execute("UPDATE message SET text = REPLACE(text, 'width=', 'width=x') WHERE text LIKE 'https://media.tenor.com/%'", [])?;
+        conn.query_row("SELECT id, text FROM message WHERE text LIKE 'https://media.tenor.com/%'", [],
+                       |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+    };
+
+    let mut ctx = LoadContext::new(LoadMode::Strict);
+    assert!(LOADER.load_with_context(&res, &NoChooser, &mut ctx).is_err());
+
+    // GIF is kept with its URL as a text, the rest of the chat is loaded as usual
+    let mut ctx = LoadContext::new(LoadMode::Lenient);
+    let dao = LOADER.load_with_context(&res, &NoChooser, &mut ctx)?;
+    let chat = dao.cwms_single_ds().remove(0).chat;
+    assert_eq!(chat.msg_count, 2);
+
+    let msgs = dao.first_messages(&chat, 99999)?;
+    assert_eq!(msgs[0].text, vec![RichText::make_plain("Sending you a text!".to_owned())]);
+    assert_eq!(msgs[1].source_id_option, Some(5405907581016140653));
+    assert_eq!(msgs[1].text, placeholder_text(url));
+
+    let warnings = ctx.into_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].location, format!("Chat Abcde, message {key}"));
+    assert_eq!(warnings[0].message, "invalid digit found in string");
+    Ok(())
+}
+
 //
 // Helpers
 //
@@ -123,5 +172,9 @@ fn expected_myself(ds_uuid: &PbUuid) -> User {
         last_name_option: None,
         username_option: None,
         phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     }
 }
@@ -6,8 +6,8 @@ use num_traits::FromPrimitive;
 use regex::Regex;
 use rusqlite::{Connection, OptionalExtension, Row, Statement};
 
-use crate::dao::in_memory_dao::InMemoryDao;
-use crate::loader::DataLoader;
+use crate::loader::{DataLoader, LoadContext};
+use crate::loader::sink::DatasetWriter;
 
 use super::*;
 
@@ -17,6 +17,7 @@ mod tests;
 
 lazy_static! {
     static ref PHONE_JID_REGEX: Regex = Regex::new(r"^([\d]{5,})@s.whatsapp.net$").unwrap();
+    static ref MENTION_REGEX: Regex = Regex::new(r"@([\d]{5,})").unwrap();
 }
 
 /// Some notes about the implementation:
@@ -54,25 +55,17 @@ impl Users {
 }
 
 impl WhatsAppAndroidDataLoader {
+    const NEEDS_NETWORK: bool = false;
+
     fn tweak_conn(&self, path: &Path, conn: &Connection) -> EmptyRes {
         conn.execute(r#"ATTACH DATABASE ?1 AS wa_db"#, [path_to_str(&path.join("wa.db"))?])?;
         Ok(())
     }
 
-    fn normalize_users(&self, users: Users, cwms: &[ChatWithMessages]) -> Result<Vec<User>> {
+    /// Other users are only written once found participating in chats.
+    fn initial_users(&self, users: &Users) -> Result<Vec<User>> {
         let myself_id = users.myself_id.unwrap();
-        // Filter out users not participating in chats.
-        let participating_user_ids: HashSet<i64, Hasher> = cwms.iter()
-            .map(|cwm| &cwm.chat)
-            .flat_map(|c| &c.member_ids)
-            .copied()
-            .collect();
-        let mut users = users.id_to_user.into_values()
-            .filter(|u| u.id == *myself_id || participating_user_ids.contains(&u.id))
-            .collect_vec();
-        // Set myself to be a first member (not required by convention but to match existing behaviour).
-        users.sort_by_key(|u| if u.id == *myself_id { *UserId::MIN } else { u.id });
-        Ok(users)
+        Ok(vec![users.id_to_user[&myself_id].clone()])
     }
 
     fn parse_users(&self, conn: &Connection, ds_uuid: &PbUuid) -> Result<Users> {
@@ -100,6 +93,8 @@ impl WhatsAppAndroidDataLoader {
             GROUP BY jid.raw_string
         ")?, ds_uuid, &mut users)?;
 
+        fill_details_from_vcards(conn, &mut users)?;
+
         // It's not clear how to get own ID from WhatsApp.
         // As such:
         // - Using a first legal ID (i.e. "1") for myself.
@@ -120,20 +115,125 @@ impl WhatsAppAndroidDataLoader {
             last_name_option: None,
             username_option: None,
             phone_number_option: None,
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: None,
+            img_path_option: None,
         });
 
+        fill_myself_details(conn, &mut users)?;
+
         Ok(users)
     }
 
     fn parse_chats(&self,
                    conn: &Connection,
                    ds_uuid: &PbUuid,
-                   users: &mut Users,
-                   _path: &Path) -> Result<Vec<ChatWithMessages>> {
-        parse_chats(conn, ds_uuid, users)
+                   users: &Users,
+                   _path: &Path,
+                   ctx: &mut LoadContext,
+                   writer: &mut DatasetWriter) -> EmptyRes {
+        parse_chats(conn, ds_uuid, users, ctx, writer)
     }
 }
 
+/// Group join message for myself references my own JID, so that details can be taken from the matching contact.
+fn fill_myself_details(conn: &Connection, users: &mut Users) -> EmptyRes {
+    let mut stmt = conn.prepare(r"
+        SELECT jid.raw_string AS jid
+        FROM message_system_group
+        INNER JOIN message_system_chat_participant
+                   ON message_system_chat_participant.message_row_id = message_system_group.message_row_id
+        INNER JOIN jid ON jid._id = message_system_chat_participant.user_jid_row_id
+        WHERE message_system_group.is_me_joined = 1
+        ORDER BY message_system_group.message_row_id ASC
+    ")?;
+    let myself_id = users.myself_id.unwrap();
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let jid = row.get::<_, String>("jid")?;
+        let Some(user) = users.jids.get(&jid).and_then(|id| users.id_to_user.get(id)).cloned() else { continue };
+        let myself: &mut User = users.id_to_user.get_mut(&myself_id).unwrap();
+        if myself.first_name_option.is_none() { myself.first_name_option = user.first_name_option };
+        if myself.last_name_option.is_none() { myself.last_name_option = user.last_name_option };
+        if myself.username_option.is_none() { myself.username_option = user.username_option };
+        if myself.phone_number_option.is_none() { myself.phone_number_option = user.phone_number_option };
+        if myself.extra_phone_numbers.is_empty() { myself.extra_phone_numbers = user.extra_phone_numbers };
+        if myself.emails.is_empty() { myself.emails = user.emails };
+        if myself.bio_option.is_none() { myself.bio_option = user.bio_option };
+        if myself.img_path_option.is_none() { myself.img_path_option = user.img_path_option };
+    }
+    Ok(())
+}
+
+/// Shared contacts carry phone numbers and e-mails that contacts list lacks.
+/// vCard describes a known user if its WhatsApp ID (WAID) matches the user's JID.
+fn fill_details_from_vcards(conn: &Connection, users: &mut Users) -> EmptyRes {
+    let mut stmt = conn.prepare("SELECT vcard FROM message_vcard WHERE vcard IS NOT NULL")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let vcard = row.get::<_, String>("vcard")?;
+        // Malformed vCards are reported when their messages are parsed
+        let Ok(Some(details)) = parse_vcard_details(&vcard) else { continue };
+        let jid = format!("{}@s.whatsapp.net", details.waid);
+        let Some(user) = users.jids.get(&jid).and_then(|id| users.id_to_user.get_mut(id)) else { continue };
+        let mut known_digits = user.phone_number_option.iter()
+            .chain(user.extra_phone_numbers.iter())
+            .map(|p| phone_digits(p))
+            .collect_vec();
+        known_digits.push(details.waid);
+        for phone in details.phones {
+            let digits = phone_digits(&phone);
+            if !known_digits.contains(&digits) {
+                known_digits.push(digits);
+                user.extra_phone_numbers.push(phone);
+            }
+        }
+        for email in details.emails {
+            if !user.emails.iter().any(|e| e.eq_ignore_ascii_case(&email)) {
+                user.emails.push(email);
+            }
+        }
+    }
+    Ok(())
+}
+
+struct VcardDetails {
+    waid: String,
+    phones: Vec<String>,
+    emails: Vec<String>,
+}
+
+/// Returns None if vCard has no WhatsApp number.
+fn parse_vcard_details(vcard: &str) -> Result<Option<VcardDetails>> {
+    let mut vcard = VcardParser::new(BufReader::new(vcard.as_bytes()));
+    let Some(vcard) = vcard.next() else { return Ok(None) };
+    let vcard = vcard?;
+
+    let values_of = |name: &str| vcard.properties.iter()
+        .filter(|p| p.name.split('.').contains(&name))
+        .collect_vec();
+
+    let waid = values_of("TEL").into_iter()
+        .filter_map(|p| p.params.as_ref())
+        .flat_map(|params| params.iter())
+        .find(|(k, _)| k == "WAID")
+        .and_then(|(_, v)| v.first().cloned());
+    let Some(waid) = waid else { return Ok(None) };
+
+    let non_empty_values = |name: &str| values_of(name).into_iter()
+        .filter_map(|p| p.value.clone())
+        .map(|v| v.trim().to_owned())
+        .filter(|v| !v.is_empty())
+        .collect_vec();
+
+    Ok(Some(VcardDetails { waid, phones: non_empty_values("TEL"), emails: non_empty_values("EMAIL") }))
+}
+
+fn phone_digits(phone: &str) -> String {
+    phone.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
 fn parse_users_from_stmt(stmt: &mut Statement, ds_uuid: &PbUuid, users: &mut Users) -> EmptyRes {
     let mut rows = stmt.query([])?;
     while let Some(row) = rows.next()? {
@@ -152,6 +252,8 @@ fn parse_users_from_stmt(stmt: &mut Statement, ds_uuid: &PbUuid, users: &mut Use
             PHONE_JID_REGEX.captures(&jid).map(|c| format!("+{}", c.get(1).unwrap().as_str()))
         });
 
+        let img_path_option = Some(format!("files/Avatars/{jid}.j"));
+
         let username_option = if phone_number_option.is_none() {
             // If phone number is left unknown, we're using JID as a username in order to not lose information
             Some(jid)
@@ -168,6 +270,10 @@ fn parse_users_from_stmt(stmt: &mut Statement, ds_uuid: &PbUuid, users: &mut Use
             last_name_option: None, // Last name is unreliable
             username_option,
             phone_number_option,
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: row.get::<_, Option<String>>("status")?.filter(|s| !s.is_empty()),
+            img_path_option,
         });
     }
     Ok(())
@@ -197,8 +303,7 @@ enum MessageType {
     BusinessItemTemplated = 25,
     OneTimePassword = 27,
     WhatsAppMessage = 28,
-    /// Details are in `message_ephemeral_setting`.
-    /// (Not sure if that's only "set" or "set or unset")
+    /// Details are in `message_ephemeral_setting`, zero duration means timer was disabled.
     DisappearTimerSet = 36,
     OneTimePhoto = 42,
     OneTimeVideo = 43,
@@ -221,8 +326,8 @@ enum SystemActionType {
     /// Details are in `message_system_value_change`
     BecameBusinessAccount = 46,
     BlockContact = 58,
-    /// Details are in `message_ephemeral_setting`.
-    /// (Not sure if that's only "unset" or "set or unset", looks like an opposite of [MessageType::DisappearTimerSet])
+    /// Details are in `message_ephemeral_setting`, zero duration means timer was disabled.
+    /// (Looks like an opposite of [MessageType::DisappearTimerSet])
     DisappearTimerDisabled = 59,
     /// Details are in `message_system_initial_privacy_provider`, but no idea what it is
     PrivacyProvider = 67,
@@ -235,15 +340,18 @@ enum SystemActionType {
 mod columns {
     pub mod chat {
         pub const SUBJECT: &str = "subject";
+        pub const CREATED_TIMESTAMP: &str = "created_timestamp";
     }
 
     pub mod message {
+        pub const ID: &str = "_id";
         pub const TIMESTAMP: &str = "timestamp";
         pub const FROM_ME: &str = "from_me";
         pub const KEY: &str = "key_id";
         pub const TYPE: &str = "message_type";
         pub const TEXT: &str = "text_data";
         pub const RECIPIENT_COUNT: &str = "recipient_count";
+        pub const STATUS: &str = "status";
 
         // References
         pub const SENDER_JID_ROW_ID: &str = "sender_jid_row_id";
@@ -268,11 +376,51 @@ mod columns {
         pub const DURATION: &str = "live_location_share_duration";
     }
 
+    pub mod message_text {
+        pub const URL: &str = "url";
+        pub const TITLE: &str = "page_title";
+        pub const DESCRIPTION: &str = "description";
+    }
+
+    pub mod message_ephemeral_setting {
+        pub const SETTING_DURATION: &str = "setting_duration";
+    }
+
     pub mod message_revoked {
         pub const REVOKED_KEY: &str = "revoked_key_id";
         pub const REVOKE_TIMESTAMP: &str = "revoke_timestamp";
     }
 
+    pub mod message_add_on {
+        pub const TIMESTAMP: &str = "timestamp";
+        pub const FROM_ME: &str = "from_me";
+
+        // References
+        pub const PARENT_MESSAGE_ROW_ID: &str = "parent_message_row_id";
+    }
+
+    pub mod message_add_on_reaction {
+        pub const REACTION: &str = "reaction";
+    }
+
+    pub mod receipt_user {
+        pub const RECEIPT_TIMESTAMP: &str = "receipt_timestamp";
+        pub const READ_TIMESTAMP: &str = "read_timestamp";
+        pub const PLAYED_TIMESTAMP: &str = "played_timestamp";
+
+        // References
+        pub const MESSAGE_ROW_ID: &str = "message_row_id";
+        pub const USER_JID_ROW_ID: &str = "receipt_user_jid_row_id";
+    }
+
+    pub mod group_participant_user {
+        pub const RANK: &str = "rank";
+
+        // References
+        pub const GROUP_JID_ROW_ID: &str = "group_jid_row_id";
+        pub const USER_JID_ROW_ID: &str = "user_jid_row_id";
+    }
+
     pub mod call_logs {
         pub const TIMESTAMP: &str = "timestamp";
         pub const FROM_ME: &str = "from_me";
@@ -283,10 +431,17 @@ mod columns {
     pub const SENDER_JID: &str = "sender_jid";
     pub const GROUP_USER_JID: &str = "group_user_jid";
     pub const MIGRATE_USER_JID: &str = "migrate_user_jid";
+    pub const RECEIPT_USER_JID: &str = "receipt_user_jid";
+    pub const PARTICIPANT_USER_JID: &str = "participant_user_jid";
     pub const PARENT_KEY_ID: &str = "parent_key_id";
+    pub const EPHEMERAL_DURATION: &str = "ephemeral_duration";
 }
 
-fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result<Vec<ChatWithMessages>> {
+fn parse_chats(conn: &Connection,
+               ds_uuid: &PbUuid,
+               users: &Users,
+               ctx: &mut LoadContext,
+               writer: &mut DatasetWriter) -> EmptyRes {
     let mut cwms_map: HashMap<Jid, ChatWithMessages> = Default::default();
     let myself_id = users.myself_id.unwrap();
 
@@ -336,6 +491,11 @@ fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result
                 member_ids: vec![],
                 msg_count: 0, // Some messages might be filtered out later, so at this point we're leaving it unset
                 main_chat_id: None,
+                description_option: None,
+                created_timestamp_option: row.get::<_, Option<i64>>(columns::chat::CREATED_TIMESTAMP)?
+                    .filter(|&ts| ts > 0)
+                    .map(|ts| ts / 1000),
+                member_details: vec![],
             },
             messages: Vec::with_capacity(row.get::<_, usize>("msgs_count")?),
         });
@@ -349,9 +509,13 @@ fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result
      * - Forwarded messages do not specify source.
      * - Call logs are stored separately - in call_log table.
      * - For source_id, we're using hash of `message.key_id` and `call_log.call_id`.
+     * - Reactions are stored as message add-ons, an emptied reaction means it was taken back.
+     * - Per-user delivery/read receipts for outgoing messages are stored in receipt_user.
+     * - Disappearing message timer is stored in message_ephemeral, its changes - in message_ephemeral_setting.
+     * - Group admins are stored in group_participant_user, join/leave times are only known from system messages.
      */
     let mut msgs_stmt = {
-        use columns::{*, chat::*, message::*, message_revoked::*};
+        use columns::{*, chat::*, message::*, message_revoked::*, message_ephemeral_setting::*};
         fn join_by_message_id(table_name: &str) -> String {
             format!("LEFT JOIN {table_name} ON {table_name}.message_row_id = message._id")
         }
@@ -369,8 +533,11 @@ fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result
                   {},
                   {},
                   message_vcard.vcard,
+                  {},
                   message_revoked.{REVOKED_KEY},
                   message_revoked.{REVOKE_TIMESTAMP},
+                  message_ephemeral.duration AS {EPHEMERAL_DURATION},
+                  message_ephemeral_setting.{SETTING_DURATION},
                   message_system.action_type,
                   message_system_group.is_me_joined,
                   group_user_jid.raw_string AS {GROUP_USER_JID},
@@ -392,6 +559,9 @@ fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result
               {}
               {}
               {}
+              {}
+              {}
+              {}
               LEFT  JOIN jid  group_user_jid   ON group_user_jid._id   = message_system_chat_participant.user_jid_row_id
               LEFT  JOIN jid  migrate_user_jid ON migrate_user_jid._id = message_system_number_change.old_jid_row_id
               WHERE chat_jid.raw_string = ?1
@@ -407,13 +577,21 @@ fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result
                     .map(|c| format!("message_location.{c}")).join(", ");
                 format!("CAST(message_location.{LAT} AS text) AS {LAT}, CAST(message_location.{LON} AS text) AS {LON}, {rest}")
             },
+            {
+                use columns::message_text::*;
+                [URL, TITLE, DESCRIPTION].iter()
+                    .map(|c| format!("message_text.{c}")).join(", ")
+            },
             join_by_message_id("message_edit_info"),
             join_by_message_id("message_quoted"),
             join_by_message_id("message_forwarded"),
             join_by_message_id("message_media"),
             join_by_message_id("message_location"),
             join_by_message_id("message_vcard"),
+            join_by_message_id("message_text"),
             join_by_message_id("message_revoked"),
+            join_by_message_id("message_ephemeral"),
+            join_by_message_id("message_ephemeral_setting"),
             join_by_message_id("message_system"),
             join_by_message_id("message_system_group"),
             join_by_message_id("message_system_chat_participant"),
@@ -421,6 +599,51 @@ fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result
             join_by_message_id("message_system_block_contact"),
         ))?
     };
+    let mut reactions_stmt = {
+        use columns::{*, message_add_on::*, message_add_on_reaction::*};
+        conn.prepare(&format!(
+            r"SELECT
+                  sender_jid.raw_string AS {SENDER_JID},
+                  message_add_on.*,
+                  message_add_on_reaction.{REACTION}
+              FROM message_add_on
+              INNER JOIN message_add_on_reaction ON message_add_on_reaction.message_add_on_row_id = message_add_on._id
+              INNER JOIN chat                    ON chat._id                    = message_add_on.chat_row_id
+              INNER JOIN jid  chat_jid           ON chat_jid._id                = chat.jid_row_id
+              LEFT  JOIN jid  sender_jid         ON sender_jid._id              = message_add_on.sender_jid_row_id
+              WHERE chat_jid.raw_string = ?1
+                AND message_add_on_reaction.{REACTION} <> ''
+              ORDER BY message_add_on.{TIMESTAMP} ASC",
+        ))?
+    };
+    let mut receipts_stmt = {
+        use columns::{*, receipt_user::*};
+        conn.prepare(&format!(
+            r"SELECT
+                  receipt_user_jid.raw_string AS {RECEIPT_USER_JID},
+                  receipt_user.*
+              FROM receipt_user
+              INNER JOIN message               ON message._id          = receipt_user.{MESSAGE_ROW_ID}
+              INNER JOIN chat                  ON chat._id             = message.chat_row_id
+              INNER JOIN jid  chat_jid         ON chat_jid._id         = chat.jid_row_id
+              INNER JOIN jid  receipt_user_jid ON receipt_user_jid._id = receipt_user.{USER_JID_ROW_ID}
+              WHERE chat_jid.raw_string = ?1
+              ORDER BY receipt_user._id ASC",
+        ))?
+    };
+    let mut participants_stmt = {
+        use columns::{*, group_participant_user::*};
+        conn.prepare(&format!(
+            r"SELECT
+                  participant_user_jid.raw_string AS {PARTICIPANT_USER_JID},
+                  group_participant_user.{RANK}
+              FROM group_participant_user
+              INNER JOIN jid  group_jid            ON group_jid._id            = group_participant_user.{GROUP_JID_ROW_ID}
+              INNER JOIN jid  participant_user_jid ON participant_user_jid._id = group_participant_user.{USER_JID_ROW_ID}
+              WHERE group_jid.raw_string = ?1
+              ORDER BY group_participant_user._id ASC",
+        ))?
+    };
     let mut calls_stmt = {
         use columns::*;
         conn.prepare(&format!(
@@ -435,9 +658,9 @@ fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result
         ))?
     };
 
-    for (jid, cwm) in cwms_map.iter_mut() {
-        let mut msg_rows = msgs_stmt.query([jid])?;
-        let mut call_rows = calls_stmt.query([jid])?;
+    for (jid, mut cwm) in cwms_map {
+        let mut msg_rows = msgs_stmt.query([&jid])?;
+        let mut call_rows = calls_stmt.query([&jid])?;
         let chat: &mut Chat = &mut cwm.chat;
         let chat_tpe = ChatType::resolve(chat.tpe).unwrap();
 
@@ -446,7 +669,70 @@ fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result
 
         let mut msg_key_to_source_id: HashMap<MessageKey, i64, Hasher> = Default::default();
 
+        let mut msg_row_id_to_reactions: HashMap<i64, Vec<Reaction>, Hasher> = Default::default();
+        let mut reaction_rows = reactions_stmt.query([&jid])?;
+        while let Some(row) = reaction_rows.next()? {
+            let from_me = row.get::<_, i32>(columns::message_add_on::FROM_ME)? == 1;
+            let sender_jid = row.get::<_, Option<String>>(columns::SENDER_JID)?;
+            let from_id: UserId = match (chat_tpe, sender_jid) {
+                _ if from_me => myself_id,
+                (ChatType::Personal, _) => UserId(chat.id),
+                (ChatType::PrivateGroup, None) => myself_id,
+                (ChatType::PrivateGroup, Some(sender_jid)) => UserId(hash_to_id(&sender_jid)),
+            };
+            if !users.id_to_user.contains_key(&from_id) {
+                log::warn!("Reaction from unknown user {} skipped in chat {}", *from_id, name_or_unnamed(&chat.name_option));
+                continue;
+            }
+            member_ids.insert(from_id);
+
+            let parent_row_id = row.get::<_, i64>(columns::message_add_on::PARENT_MESSAGE_ROW_ID)?;
+            msg_row_id_to_reactions.entry(parent_row_id).or_default().push(Reaction {
+                emoji_option: row.get(columns::message_add_on_reaction::REACTION)?,
+                custom_sticker_path_option: None,
+                from_id: *from_id,
+                timestamp_option: row.get::<_, Option<i64>>(columns::message_add_on::TIMESTAMP)?.map(|ts| ts / 1000),
+            });
+        }
+
+        let mut msg_row_id_to_receipts: HashMap<i64, Vec<Receipt>, Hasher> = Default::default();
+        let mut receipt_rows = receipts_stmt.query([&jid])?;
+        while let Some(row) = receipt_rows.next()? {
+            use columns::receipt_user::*;
+            let user_jid = row.get::<_, String>(columns::RECEIPT_USER_JID)?;
+            let Some(&user_id) = users.jids.get(&user_jid) else {
+                log::warn!("Receipt from unknown user {user_jid} skipped in chat {}", name_or_unnamed(&chat.name_option));
+                continue;
+            };
+            let get_ts = |col: &str| -> Result<Option<i64>> {
+                Ok(row.get::<_, Option<i64>>(col)?.filter(|&ts| ts > 0).map(|ts| ts / 1000))
+            };
+            let parent_row_id = row.get::<_, i64>(MESSAGE_ROW_ID)?;
+            msg_row_id_to_receipts.entry(parent_row_id).or_default().push(Receipt {
+                user_id: *user_id,
+                delivered_timestamp_option: get_ts(RECEIPT_TIMESTAMP)?,
+                // Media being played implies it was read
+                read_timestamp_option: get_ts(READ_TIMESTAMP)?.or(get_ts(PLAYED_TIMESTAMP)?),
+            });
+        }
+
+        let mut member_details: HashMap<UserId, ChatMemberDetails, Hasher> = Default::default();
+        let mut participant_rows = participants_stmt.query([&jid])?;
+        while let Some(row) = participant_rows.next()? {
+            let user_jid = row.get::<_, String>(columns::PARTICIPANT_USER_JID)?;
+            let Some(&user_id) = users.jids.get(&user_jid) else { continue };
+            let role = match row.get::<_, i32>(columns::group_participant_user::RANK)? {
+                1 => ChatMemberRole::Admin,
+                2 => ChatMemberRole::Owner, // Superadmin
+                _ => ChatMemberRole::Member,
+            };
+            member_details_mut(&mut member_details, user_id).role_option = Some(role as i32);
+        }
+
+        let mut msg_idx = 0;
         while let Some(row) = msg_rows.next()? {
+            ctx.message_parsed(msg_idx)?;
+            msg_idx += 1;
             let from_me = match row.get(columns::message::FROM_ME)? {
                 0 => false,
                 1 => true,
@@ -469,21 +755,30 @@ fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result
             assert!(users.id_to_user.contains_key(&from_id));
             member_ids.insert(from_id);
 
-            let msg_tpe = row.get::<_, i32>(columns::message::TYPE)?;
-            let msg_tpe = FromPrimitive::from_i32(msg_tpe).with_context(|| format!("Unknown message type ID: {msg_tpe}"))?;
-
-            let (typed, text_column) = {
+            let parsed = (|| -> Result<_> {
+                let msg_tpe = row.get::<_, i32>(columns::message::TYPE)?;
+                let msg_tpe: MessageType = FromPrimitive::from_i32(msg_tpe)
+                    .with_context(|| format!("Unknown message type ID: {msg_tpe}"))?;
                 let result_option = match msg_tpe {
-                    MessageType::System | MessageType::MissedCall =>
-                        parse_system_message(row, msg_tpe, users, &mut member_ids)?,
+                    MessageType::System | MessageType::MissedCall | MessageType::DisappearTimerSet =>
+                        parse_system_message(row, msg_tpe, users, &mut member_ids, &mut member_details)?,
                     MessageType::VideoCall =>
                         None, // Will be processed when parsing call_rows
                     _ =>
                         parse_regular_message(row, msg_tpe, &msg_key_to_source_id)?
                 };
-                match result_option {
-                    Some(v) => v,
-                    None => continue
+                Ok(result_option.map(|v| (msg_tpe, v)))
+            })();
+
+            let (msg_tpe, (typed, text_column)) = match parsed {
+                Ok(Some(v)) => v,
+                Ok(None) => continue,
+                Err(e) => {
+                    let key: MessageKey = row.get(columns::message::KEY)?;
+                    ctx.tolerate(message_location(&name_or_unnamed(&chat.name_option), &key), e)?;
+                    let ts = row.get::<_, i64>(columns::message::TIMESTAMP)?;
+                    cwm.messages.push(placeholder_message(Some(hash_to_id(&key)), ts / 1000, from_id, row_dump(row)));
+                    continue;
                 }
             };
 
@@ -493,7 +788,7 @@ fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result
                 None => vec![], // Data type implies no text
                 Some(Ok(None)) => vec![], // Text not supplies
                 Some(Ok(Some(s))) if s.is_empty() => vec![],
-                Some(Ok(Some(text))) => vec![RichText::make_plain(text)],
+                Some(Ok(Some(text))) => parse_text(text, users),
                 Some(Err(e)) => return Err(e)?
             };
 
@@ -510,14 +805,22 @@ fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result
 
             let ts = row.get::<_, i64>(columns::message::TIMESTAMP)?;
 
-            cwm.messages.push(Message::new(
+            let mut message = Message::new(
                 *NO_INTERNAL_ID,
                 Some(source_id),
                 ts / 1000,
                 from_id,
                 text,
                 typed,
-            ));
+            );
+            let row_id = row.get::<_, i64>(columns::message::ID)?;
+            message.reactions = msg_row_id_to_reactions.remove(&row_id).unwrap_or_default();
+            if from_me && matches!(message.typed(), message::Typed::Regular(_)) {
+                message.delivery_status_option =
+                    parse_delivery_status(row.get(columns::message::STATUS)?).map(|s| s as i32);
+                message.receipts = msg_row_id_to_receipts.remove(&row_id).unwrap_or_default();
+            }
+            cwm.messages.push(message);
         }
 
         while let Some(row) = call_rows.next()? {
@@ -554,26 +857,36 @@ fn parse_chats(conn: &Connection, ds_uuid: &PbUuid, users: &mut Users) -> Result
 
         // We're relying on sort_by_key being stable
         cwm.messages.sort_by_key(|m| m.timestamp);
+        cwm.messages = merge_albums(cwm.messages);
         cwm.messages.iter_mut().enumerate().for_each(|(i, m)| m.internal_id = i as i64);
 
         chat.msg_count = cwm.messages.len() as i32;
+        chat.member_details = member_details.into_values()
+            .filter(|md| member_ids.contains(&UserId(md.user_id)))
+            .sorted_by_key(|md| md.user_id)
+            .collect_vec();
         chat.member_ids = member_ids.into_iter().map(|id| *id).sorted().collect_vec();
+        ctx.chat_parsed(cwm.messages.len())?;
+
+        // WhatsApp has a lot of chats with block/unblock/migration messages only, which might be related to
+        // changing phone number. These chats are not interesting.
+        if cwm.chat.msg_count > 0 && cwm.messages.iter().any(|m| matches!(m.typed(), message::Typed::Regular(_))) {
+            writer.add_chat(cwm, |id| {
+                users.id_to_user.get(&id).cloned().with_context(|| format!("User {} not found", *id))
+            })?;
+        }
     }
 
-    // WhatsApp has a lot of chats with block/unblock/migration messages only, which might be related to
-    // changing phone number. These chats are not interesting.
-    Ok(cwms_map.into_values()
-        .filter(|cwm| cwm.chat.msg_count > 0)
-        .filter(|cwm| cwm.messages.iter().any(|m| matches!(m.typed(), message::Typed::Regular(_))))
-        .collect_vec())
+    Ok(())
 }
 
 /// Returns `None` for rows that should be skipped.
 fn parse_system_message<'a>(
     row: &Row,
     msg_tpe: MessageType,
-    users: &'a mut Users,
+    users: &'a Users,
     chat_member_ids: &mut HashSet<UserId, Hasher>,
+    chat_member_details: &mut HashMap<UserId, ChatMemberDetails, Hasher>,
 ) -> Result<Option<(message::Typed, Option<&'static str>)>> {
     use message_service::SealedValueOptional;
     use message_service::SealedValueOptional::*;
@@ -584,22 +897,14 @@ fn parse_system_message<'a>(
             let action_type = FromPrimitive::from_i32(action_type)
                 .with_context(|| format!("Unknown system message type ID: {action_type}"))?;
 
-            let mut get_group_user = |users: &'a mut Users, column: &str| -> Result<&'a User> {
+            let mut get_group_user = |users: &'a Users, column: &str| -> Result<&'a User> {
                 let user_id = UserId(hash_to_id(&row.get::<_, String>(column)?));
 
                 if row.get::<_, Option<i8>>("is_me_joined")? == Some(1) {
-                    // Found a second reference to myself! Time to update
-                    // Also, workaround for mut-immut borrowing from id_to_user
-                    let user = users.id_to_user.get(&user_id)
-                        .unwrap_or_else(|| panic!("{}", row.get::<_, String>(column).unwrap())).clone();
+                    // This is a second reference to myself, see fill_myself_details
                     let myself_id = users.myself_id.unwrap();
                     chat_member_ids.insert(myself_id);
-                    let myself: &mut User = users.id_to_user.get_mut(&myself_id).unwrap();
-                    if myself.first_name_option.is_none() { myself.first_name_option = user.first_name_option };
-                    if myself.last_name_option.is_none() { myself.last_name_option = user.last_name_option };
-                    if myself.username_option.is_none() { myself.username_option = user.username_option };
-                    if myself.phone_number_option.is_none() { myself.phone_number_option = user.phone_number_option };
-                    Ok(myself)
+                    Ok(&users.id_to_user[&myself_id])
                 } else {
                     chat_member_ids.insert(user_id);
                     Ok(&users.id_to_user[&user_id])
@@ -628,12 +933,17 @@ fn parse_system_message<'a>(
                 }
                 SystemActionType::GroupUserAdd => {
                     let user = get_group_user(users, columns::GROUP_USER_JID)?;
+                    let details = member_details_mut(chat_member_details, UserId(user.id));
+                    details.joined_timestamp_option = Some(row.get::<_, i64>(columns::message::TIMESTAMP)? / 1000);
+                    details.left_timestamp_option = None;
                     GroupInviteMembers(MessageServiceGroupInviteMembers {
                         members: vec![user.pretty_name()],
                     })
                 }
                 SystemActionType::GroupUserRemove => {
                     let user = get_group_user(users, columns::GROUP_USER_JID)?;
+                    member_details_mut(chat_member_details, UserId(user.id)).left_timestamp_option =
+                        Some(row.get::<_, i64>(columns::message::TIMESTAMP)? / 1000);
                     GroupRemoveMembers(MessageServiceGroupRemoveMembers {
                         members: vec![user.pretty_name()],
                     })
@@ -650,13 +960,21 @@ fn parse_system_message<'a>(
                         is_blocked: row.get::<_, i8>("is_blocked")? == 1
                     })
                 }
-                SystemActionType::PrivacyProvider | SystemActionType::DisappearTimerDisabled |
+                SystemActionType::DisappearTimerDisabled => {
+                    text_column = None;
+                    SetMessagesTtl(parse_ttl_setting(row)?)
+                }
+                SystemActionType::PrivacyProvider |
                 SystemActionType::BecameBusinessAccount | SystemActionType::BusinessState |
                 SystemActionType::IsAContact => {
                     return Ok(None);
                 }
             }
         }
+        MessageType::DisappearTimerSet => {
+            text_column = None;
+            SetMessagesTtl(parse_ttl_setting(row)?)
+        }
         MessageType::MissedCall =>
             PhoneCall(MessageServicePhoneCall {
                 duration_sec_option: None,
@@ -686,7 +1004,16 @@ fn parse_regular_message(
 
     // TODO: Extract thumbnails from message_thumbnails (not message_thumbnail!) and media_hash_thumbnail
     let content_option = match msg_tpe {
-        MessageType::Text => None,
+        MessageType::Text => row.get::<_, Option<String>>(columns::message_text::URL)?.map(|url| -> Result<_> {
+            // Preview image is only stored as an embedded thumbnail, not as a file
+            Ok(WebPage(ContentWebPage {
+                url,
+                title_option: row.get(columns::message_text::TITLE)?,
+                description_option: row.get(columns::message_text::DESCRIPTION)?,
+                site_name_option: None,
+                image_path_option: None,
+            }))
+        }).transpose()?,
         MessageType::Picture =>
             Some(Photo(ContentPhoto {
                 path_option: row.get(columns::message_media::FILE_PATH)?, // TODO: One-time photos
@@ -786,9 +1113,10 @@ fn parse_regular_message(
         }
         // We're not interested in these
         MessageType::WaitingForMessage | MessageType::BusinessItem | MessageType::BusinessItemTemplated |
-        MessageType::OneTimePassword | MessageType::WhatsAppMessage | MessageType::DisappearTimerSet =>
+        MessageType::OneTimePassword | MessageType::WhatsAppMessage =>
             return Ok(None),
         MessageType::System => unreachable!(),
+        MessageType::DisappearTimerSet => unreachable!(),
         MessageType::MissedCall => unreachable!(),
         MessageType::VideoCall => unreachable!(),
     }.map(|c| Content { sealed_value_optional: Some(c) });
@@ -796,6 +1124,10 @@ fn parse_regular_message(
     // WhatsApp does not preserve real source
     let forward_from_name_option = row.get::<_, Option<i64>>("forward_score")?
         .map(|_| SOMEONE.to_owned());
+    let forward_origin_option = forward_from_name_option.as_ref().map(|name| ForwardOrigin {
+        from_name_option: Some(name.clone()),
+        ..Default::default()
+    });
 
     // Note 1: We could *technically* restore deleted message content when replying to the original!
     //         Not doing that now though.
@@ -815,9 +1147,61 @@ fn parse_regular_message(
         forward_from_name_option,
         reply_to_message_id_option,
         content_option,
+        extra_contents: vec![],
+        forward_origin_option,
+        ttl_sec_option: get_zero_as_null(row, columns::EPHEMERAL_DURATION)?,
     }, text_column)))
 }
 
+fn parse_ttl_setting(row: &Row) -> Result<MessageServiceSetMessagesTtl> {
+    Ok(MessageServiceSetMessagesTtl {
+        ttl_sec_option: get_zero_as_null(row, columns::message_ephemeral_setting::SETTING_DURATION)?,
+    })
+}
+
+fn member_details_mut(member_details: &mut HashMap<UserId, ChatMemberDetails, Hasher>,
+                      user_id: UserId) -> &mut ChatMemberDetails {
+    member_details.entry(user_id).or_insert_with(|| ChatMemberDetails { user_id: *user_id, ..Default::default() })
+}
+
+/// Only makes sense for outgoing messages.
+fn parse_delivery_status(status: i32) -> Option<DeliveryStatus> {
+    match status {
+        4 => Some(DeliveryStatus::Sent),
+        5 => Some(DeliveryStatus::Delivered),
+        8 | 13 => Some(DeliveryStatus::Read), // Played or read
+        _ => None
+    }
+}
+
+/// Splits out mentions of known users (in form of "@<phone number>") from the message text.
+/// Mention text is kept as a phone number, same as in the source - user name is to be resolved by ID when displayed.
+fn parse_text(text: String, users: &Users) -> Vec<RichTextElement> {
+    let mut result = vec![];
+    let mut last_end = 0;
+    for cap in MENTION_REGEX.captures_iter(&text) {
+        let (whole, phone) = (cap.get(0).unwrap(), cap.get(1).unwrap().as_str());
+        let Some(user_id) = users.jids.get(&format!("{phone}@s.whatsapp.net")) else { continue };
+        if whole.start() > last_end {
+            result.push(RichText::make_plain(text[last_end..whole.start()].to_owned()));
+        }
+        result.push(RichText::make_mention(Some(**user_id), phone.to_owned()));
+        last_end = whole.end();
+    }
+    if last_end < text.len() {
+        result.push(RichText::make_plain(text[last_end..].to_owned()));
+    }
+    result
+}
+
+/// Debug representation of all row columns, used as a text of a placeholder message.
+fn row_dump(row: &Row) -> String {
+    let stmt = row.as_ref();
+    (0..stmt.column_count())
+        .map(|i| format!("{}: {:?}", stmt.column_name(i).unwrap_or("?"), row.get_ref(i).ok()))
+        .join(", ")
+}
+
 fn get_zero_as_null(row: &Row, col_name: &str) -> Result<Option<i32>> {
     Ok(row.get::<_, Option<i32>>(col_name)?.filter(|&i| i != 0))
 }
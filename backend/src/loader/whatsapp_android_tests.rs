@@ -9,6 +9,7 @@ use pretty_assertions::{assert_eq, assert_ne};
 use crate::{NoChooser, User};
 use crate::dao::ChatHistoryDao;
 use crate::entity_utils::*;
+use crate::loader::{LoadContext, LoadMode};
 use crate::protobuf::history::content::SealedValueOptional::*;
 use crate::protobuf::history::message::*;
 use crate::protobuf::history::message_service::SealedValueOptional::*;
@@ -81,6 +82,38 @@ fn vcards() -> EmptyRes {
     Ok(())
 }
 
+#[test]
+fn text_mentions() {
+    let ds_uuid = PbUuid::random();
+    let mut users: Users = Default::default();
+    let named_id = users.add_or_get_user_id("11111@s.whatsapp.net".to_owned());
+    users.id_to_user.insert(named_id, User {
+        ds_uuid: ds_uuid.clone(),
+        id: *named_id,
+        first_name_option: Some("Named".to_owned()),
+        last_name_option: None,
+        username_option: None,
+        phone_number_option: Some("+11111".to_owned()),
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
+    });
+    let unnamed_id = users.add_or_get_user_id("22222@s.whatsapp.net".to_owned());
+
+    // Names are not substituted, mention text stays as it was in the source
+    assert_eq!(parse_text("Hi @11111 and @22222, not @33333".to_owned(), &users), vec![
+        RichText::make_plain("Hi ".to_owned()),
+        RichText::make_mention(Some(*named_id), "11111".to_owned()),
+        RichText::make_plain(" and ".to_owned()),
+        RichText::make_mention(Some(*unnamed_id), "22222".to_owned()),
+        RichText::make_plain(", not @33333".to_owned()),
+    ]);
+    assert_eq!(parse_text("@11111".to_owned(), &users), vec![
+        RichText::make_mention(Some(*named_id), "11111".to_owned()),
+    ]);
+}
+
 #[test]
 fn loading_2023_10() -> EmptyRes {
     let (res, _db_dir) = test_android::create_databases(RESOURCE_DIR, "2023-10", ".db", DB_FILENAME);
@@ -99,6 +132,10 @@ fn loading_2023_10() -> EmptyRes {
         last_name_option: None,
         username_option: None,
         phone_number_option: Some("+11111".to_owned()),
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: Some("files/Avatars/11111@s.whatsapp.net.j".to_owned()),
     };
 
     assert_eq!(dao.users_single_ds(), vec![myself.clone(), member.clone()]);
@@ -118,6 +155,22 @@ fn loading_2023_10() -> EmptyRes {
             member_ids: vec![myself.id, member.id],
             msg_count: 2,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: Some(1643607839),
+            member_details: vec![
+                ChatMemberDetails {
+                    user_id: myself.id,
+                    role_option: None,
+                    joined_timestamp_option: Some(1643607839),
+                    left_timestamp_option: None,
+                },
+                ChatMemberDetails {
+                    user_id: member.id,
+                    role_option: Some(ChatMemberRole::Owner as i32),
+                    joined_timestamp_option: None,
+                    left_timestamp_option: None,
+                },
+            ],
         });
 
         let msgs = dao.first_messages(&chat, 99999)?;
@@ -133,6 +186,9 @@ fn loading_2023_10() -> EmptyRes {
             typed: Some(message_service!(GroupInviteMembers(MessageServiceGroupInviteMembers {
                 members: vec![myself.pretty_name()],
             }))),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[1], Message {
             internal_id: 1,
@@ -154,7 +210,25 @@ fn loading_2023_10() -> EmptyRes {
                 forward_from_name_option: Some(SOMEONE.to_owned()),
                 reply_to_message_id_option: msgs[0].source_id_option,
                 content_option: None,
+                extra_contents: vec![],
+                forward_origin_option: Some(ForwardOrigin {
+                    from_name_option: Some(SOMEONE.to_owned()),
+                    ..Default::default()
+                }),
+                ttl_sec_option: None,
             }),
+            reactions: vec![Reaction {
+                emoji_option: Some("👍".to_owned()),
+                custom_sticker_path_option: None,
+                from_id: member.id,
+                timestamp_option: Some(1661417600),
+            }],
+            delivery_status_option: Some(DeliveryStatus::Read as i32),
+            receipts: vec![Receipt {
+                user_id: member.id,
+                delivered_timestamp_option: Some(1661417520),
+                read_timestamp_option: Some(1661417550),
+            }],
         });
     }
 
@@ -169,8 +243,11 @@ fn loading_2023_10() -> EmptyRes {
             tpe: ChatType::Personal as i32,
             img_path_option: Some("files/Avatars/11111@s.whatsapp.net.j".to_owned()),
             member_ids: vec![myself.id, member.id],
-            msg_count: 2,
+            msg_count: 5,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: Some(1687705763),
+            member_details: vec![],
         });
 
         let msgs = dao.first_messages(&chat, 99999)?;
@@ -197,7 +274,13 @@ fn loading_2023_10() -> EmptyRes {
                         duration_sec_option: Some(123),
                     }))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
 
         assert_eq!(msgs[1], Message {
@@ -213,12 +296,126 @@ fn loading_2023_10() -> EmptyRes {
                 forward_from_name_option: None,
                 reply_to_message_id_option: None,
                 content_option: None,
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
+            }),
+            reactions: vec![],
+            delivery_status_option: Some(DeliveryStatus::Delivered as i32),
+            receipts: vec![],
+        });
+
+        assert_eq!(msgs[2].timestamp, 1694000000);
+        assert_eq!(msgs[2].from_id, member.id);
+        assert_eq!(msgs[2].text, vec![RichText::make_plain("Look: https://example.com/".to_owned())]);
+        assert_eq!(msgs[2].typed(), &message_regular! {
+            edit_timestamp_option: None,
+            is_deleted: false,
+            forward_from_name_option: None,
+            reply_to_message_id_option: None,
+            content_option: Some(Content {
+                sealed_value_optional: Some(WebPage(ContentWebPage {
+                    url: "https://example.com/".to_owned(),
+                    title_option: Some("Example Domain".to_owned()),
+                    description_option: Some("This domain is for use in illustrative examples in documents.".to_owned()),
+                    site_name_option: None,
+                    image_path_option: None,
+                }))
             }),
+            extra_contents: vec![],
+            forward_origin_option: None,
+            ttl_sec_option: None,
         });
+
+        assert_eq!(msgs[3].from_id, myself.id);
+        assert_eq!(msgs[3].text, vec![]);
+        assert_eq!(msgs[3].typed(), &message_service!(SetMessagesTtl(MessageServiceSetMessagesTtl {
+            ttl_sec_option: Some(604800),
+        })));
+
+        assert_eq!(msgs[4].from_id, member.id);
+        assert_eq!(msgs[4].text, vec![RichText::make_plain("Now you see me".to_owned())]);
+        assert_matches!(msgs[4].typed(), message_regular_pat! { ttl_sec_option: Some(604800), .. });
     }
     Ok(())
 }
 
+#[test]
+fn loading_2023_10_lenient() -> EmptyRes {
+    let (res, _db_dir) = test_android::create_databases(RESOURCE_DIR, "2023-10", ".db", DB_FILENAME);
+    Connection::open(&res)?.execute("UPDATE message SET message_type = 999 WHERE key_id = 'GROUPMSG99999'", [])?;
+
+    let mut ctx = LoadContext::new(LoadMode::Strict);
+    assert!(LOADER.load_with_context(&res, &NoChooser, &mut ctx).is_err());
+
+    // Message is replaced by a placeholder with a row dump as a text
+    let mut ctx = LoadContext::new(LoadMode::Lenient);
+    let dao = LOADER.load_with_context(&res, &NoChooser, &mut ctx)?;
+    let myself = dao.myself_single_ds();
+    let cwm = dao.cwms_single_ds().into_iter().find(|cwm| cwm.chat.tpe == ChatType::PrivateGroup as i32).unwrap();
+    assert_eq!(cwm.chat.msg_count, 2);
+
+    let msgs = dao.first_messages(&cwm.chat, 99999)?;
+    let placeholder = &msgs[1];
+    assert_eq!(placeholder.source_id_option, Some(4824408779253713719));
+    assert_eq!(placeholder.timestamp, 1661417508);
+    assert_eq!(placeholder.from_id, myself.id);
+    assert_eq!(placeholder.text[0], RichText::make_italic(PLACEHOLDER_MARKER.to_owned()));
+    assert!(placeholder.searchable_string.contains("message_type: Some(Integer(999))"));
+
+    let warnings = ctx.into_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].location.starts_with("Chat My Group, message "));
+    assert_eq!(warnings[0].message, "Unknown message type ID: 999");
+    Ok(())
+}
+
+#[test]
+fn loading_2023_10_multi_photo() -> EmptyRes {
+    let (res, _db_dir) = test_android::create_databases(RESOURCE_DIR, "2023-10", ".db", DB_FILENAME);
+    Connection::open(&res)?.execute_batch(r"
+        INSERT INTO message VALUES(7460,148,1,'PERSONALMSG999910',0,5,0,0,NULL,0,0,1694200000100,1694200000900,-1,1,'Two photos',0,0,7460,0,NULL);
+        INSERT INTO message VALUES(7461,148,1,'PERSONALMSG999911',0,5,0,0,NULL,0,0,1694200000400,1694200001200,-1,1,NULL,0,0,7461,0,NULL);
+        INSERT INTO message VALUES(7462,148,1,'PERSONALMSG999912',0,5,0,0,NULL,0,0,1694200005000,1694200005500,-1,1,NULL,0,0,7462,0,NULL);
+        INSERT INTO message_media(message_row_id, chat_row_id, file_path, width, height) VALUES
+            (7460, 148, 'Media/WhatsApp Images/Sent/IMG-1.jpg', 800, 600),
+            (7461, 148, 'Media/WhatsApp Images/Sent/IMG-2.jpg', 600, 800),
+            (7462, 148, 'Media/WhatsApp Images/Sent/IMG-3.jpg', 640, 480);
+    ")?;
+
+    let dao = LOADER.load(&res, &NoChooser)?;
+    let cwm = dao.cwms_single_ds().into_iter().find(|cwm| cwm.chat.tpe == ChatType::Personal as i32).unwrap();
+    let msgs = dao.first_messages(&cwm.chat, 99999)?;
+    let (album, separate) = (&msgs[msgs.len() - 2], &msgs[msgs.len() - 1]);
+
+    // Photos sent together are merged, the one sent a few seconds later is not
+    assert_eq!(album.source_id_option, Some(hash_to_id("PERSONALMSG999910")));
+    assert_eq!(album.text, vec![RichText::make_plain("Two photos".to_owned())]);
+    assert_eq!(album.files_relative(), vec!["Media/WhatsApp Images/Sent/IMG-1.jpg", "Media/WhatsApp Images/Sent/IMG-2.jpg"]);
+    assert_eq!(separate.source_id_option, Some(hash_to_id("PERSONALMSG999912")));
+    assert_eq!(separate.files_relative(), vec!["Media/WhatsApp Images/Sent/IMG-3.jpg"]);
+    Ok(())
+}
+
+#[test]
+fn loading_2023_10_vcard_details() -> EmptyRes {
+    let (res, _db_dir) = test_android::create_databases(RESOURCE_DIR, "2023-10", ".db", DB_FILENAME);
+    Connection::open(&res)?.execute(r"INSERT INTO message_vcard(message_row_id, vcard) VALUES (7460, ?1)", [
+        "BEGIN:VCARD\nVERSION:3.0\nN:;Bbbbb;;;\nFN:Bbbbb\n\
+         item1.TEL;waid=11111:+1 1111\nitem1.X-ABLabel:Mobile\n\
+         TEL;TYPE=HOME:+22 333 444\nEMAIL;TYPE=INTERNET:bbbbb@example.com\nEND:VCARD"
+    ])?;
+
+    let dao = LOADER.load(&res, &NoChooser)?;
+    let member = dao.users_single_ds().into_iter().find(|u| u.id == 9017079856339592512_i64).unwrap();
+
+    // WhatsApp number is already known, others are added
+    assert_eq!(member.phone_number_option, Some("+11111".to_owned()));
+    assert_eq!(member.extra_phone_numbers, vec!["+22 333 444".to_owned()]);
+    assert_eq!(member.emails, vec!["bbbbb@example.com".to_owned()]);
+    Ok(())
+}
+
 //
 // Helpers
 //
@@ -231,6 +428,10 @@ fn expected_myself(ds_uuid: &PbUuid) -> User {
         last_name_option: None,
         username_option: None,
         phone_number_option: Some("+00000".to_owned()),
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: Some("Available".to_owned()),
+        img_path_option: Some("files/Avatars/00000@s.whatsapp.net.j".to_owned()),
     }
 }
 
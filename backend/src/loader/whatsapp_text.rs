@@ -5,8 +5,8 @@ use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::dao::in_memory_dao::InMemoryDao;
-use crate::loader::DataLoader;
+use crate::loader::{DataLoader, LoadContext};
+use crate::loader::sink::{DatasetWriter, LoadSink};
 use crate::prelude::*;
 
 #[cfg(test)]
@@ -29,6 +29,8 @@ impl DataLoader for WhatsAppTextDataLoader {
 
     fn src_type(&self) -> SourceType { SourceType::TextImport }
 
+    fn file_globs(&self) -> Vec<&'static str> { vec!["WhatsApp Chat with *.txt"] }
+
     fn looks_about_right_inner(&self, path: &Path) -> EmptyRes {
         let filename = path_file_name(path)?;
         if !FILENAME_REGEX.is_match(filename) {
@@ -40,41 +42,51 @@ impl DataLoader for WhatsAppTextDataLoader {
         Ok(())
     }
 
-    fn load_inner(&self, path: &Path, ds: Dataset, _myself_chooser: &dyn MyselfChooser) -> Result<Box<InMemoryDao>> {
-        parse_whatsapp_text_file(path, ds)
+    fn load_inner(&self,
+                  path: &Path,
+                  ds: Dataset,
+                  _myself_chooser: &dyn MyselfChooser,
+                  ctx: &mut LoadContext,
+                  sink: &mut dyn LoadSink) -> EmptyRes {
+        parse_whatsapp_text_file(path, ds, ctx, sink)
     }
 }
 
-fn parse_whatsapp_text_file(path: &Path, ds: Dataset) -> Result<Box<InMemoryDao>> {
+fn parse_whatsapp_text_file(path: &Path, ds: Dataset, ctx: &mut LoadContext, sink: &mut dyn LoadSink) -> EmptyRes {
     let file_content = fs::read_to_string(path)?;
     let (myself, other) = parse_users(&ds.uuid, path_file_name(path)?, &file_content)?;
 
-    let messages = parse_messages(&file_content, &myself, &other)?;
+    let messages = parse_messages(&file_content, path_file_name(path)?, &myself, &other, ctx)?;
+    ctx.chat_parsed(messages.len())?;
 
-    let cwms = vec![ChatWithMessages {
+    let ds_uuid = ds.uuid.clone();
+    let (myself_id, other_id, other_name) = (myself.id, other.id, other.pretty_name());
+    let parent_name = path_file_name(path.parent().unwrap())?;
+    let mut writer = DatasetWriter::start(
+        sink,
+        format!("WhatsApp ({})", parent_name),
+        ds,
+        path.parent().unwrap().to_path_buf(),
+        myself.id(),
+        vec![myself, other],
+    )?;
+    writer.add_chat(ChatWithMessages {
         chat: Chat {
-            ds_uuid: ds.uuid.clone(),
-            id: other.id, // Using user ID as a chat ID
-            name_option: Some(other.pretty_name()),
+            ds_uuid,
+            id: other_id, // Using user ID as a chat ID
+            name_option: Some(other_name),
             source_type: SourceType::TextImport as i32,
             tpe: ChatType::Personal as i32,
             img_path_option: None,
-            member_ids: vec![myself.id, other.id],
+            member_ids: vec![myself_id, other_id],
             msg_count: messages.len() as i32,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: None,
+            member_details: vec![],
         },
         messages
-    }];
-
-    let parent_name = path_file_name(path.parent().unwrap())?;
-    Ok(Box::new(InMemoryDao::new_single(
-        format!("WhatsApp ({})", parent_name),
-        ds,
-        path.parent().unwrap().to_path_buf(),
-        myself.id(),
-        vec![myself, other],
-        cwms,
-    )))
+    }, |id| err!("Unknown chat member {}", *id))
 }
 
 fn parse_users(ds_uuid: &PbUuid, filename: &str, content: &str) -> Result<(User, User)> {
@@ -104,6 +116,10 @@ fn parse_users(ds_uuid: &PbUuid, filename: &str, content: &str) -> Result<(User,
         last_name_option: None,
         username_option: None,
         phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     }, User {
         ds_uuid: ds_uuid.clone(),
         id: super::hash_to_id(other_name),
@@ -111,10 +127,18 @@ fn parse_users(ds_uuid: &PbUuid, filename: &str, content: &str) -> Result<(User,
         last_name_option: None,
         username_option: None,
         phone_number_option: if other_name.starts_with('+') { Some(other_name.to_owned()) } else { None },
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     }))
 }
 
-fn parse_messages(content: &str, myself: &User, other: &User) -> Result<Vec<Message>> {
+fn parse_messages(content: &str,
+                  file_name: &str,
+                  myself: &User,
+                  other: &User,
+                  ctx: &mut LoadContext) -> Result<Vec<Message>> {
     const NOTICE_LINE: &str = "Messages and calls are end-to-end encrypted.";
     const TIMER_LINE: &str = "updated the message timer. New messages will disappear from this chat";
 
@@ -126,30 +150,43 @@ fn parse_messages(content: &str, myself: &User, other: &User) -> Result<Vec<Mess
     let mut timestamp: Timestamp = Timestamp::MIN;
     let mut lines: Vec<&str> = Vec::with_capacity(10);
     let mut last_internal_id = NO_INTERNAL_ID;
+    let mut first_line_no = 0;
+    // Reported once the message is collected, so that it gets a single warning
+    let mut datetime_error: Option<anyhow::Error> = None;
 
-    let mut iter = content.lines().peekable();
-    while let Some(line) = iter.next() {
+    let mut iter = content.lines().enumerate().peekable();
+    while let Some((line_idx, line)) = iter.next() {
         if line.contains(NOTICE_LINE) || line.contains(TIMER_LINE) || line.ends_with(&is_a_contact_line) {
             continue;
         }
         match MESSAGE_PREFIX_REGEX.captures(line) {
             Some(capture) => {
                 // First message line
-                let timestamp2: Timestamp = parse_datetime(capture.get(1).unwrap().as_str())?;
-                if *timestamp2 > *timestamp {
-                    timestamp = timestamp2;
-                } else {
-                    // Multiple messages have the same timestamp - treat them as 1 second apart
-                    timestamp = Timestamp(*timestamp + 1);
+                first_line_no = line_idx + 1;
+                let datetime_str = capture.get(1).unwrap().as_str();
+                match parse_datetime(datetime_str) {
+                    Ok(timestamp2) => {
+                        if *timestamp2 > *timestamp {
+                            timestamp = timestamp2;
+                        } else {
+                            // Multiple messages have the same timestamp - treat them as 1 second apart
+                            timestamp = Timestamp(*timestamp + 1);
+                        }
+
+                        let username_str = capture.get(2).unwrap().as_str();
+                        user_id = Some(if username_str == myself.pretty_name() {
+                            myself.id()
+                        } else {
+                            other.id()
+                        });
+                    }
+                    Err(e) => {
+                        // Author is left unknown, so that the message is dropped
+                        datetime_error = Some(e.context(format!("Invalid date '{datetime_str}'")));
+                        user_id = None;
+                    }
                 }
 
-                let username_str = capture.get(2).unwrap().as_str();
-                user_id = Some(if username_str == myself.pretty_name() {
-                    myself.id()
-                } else {
-                    other.id()
-                });
-
                 lines.push(capture.get(3).unwrap().as_str());
             }
             None => {
@@ -158,21 +195,40 @@ fn parse_messages(content: &str, myself: &User, other: &User) -> Result<Vec<Mess
             }
         }
         match iter.peek() {
-            Some(l) if !MESSAGE_PREFIX_REGEX.is_match(l) => {
+            Some((_, l)) if !MESSAGE_PREFIX_REGEX.is_match(l) => {
                 // Multiline message continues, NOOP
             }
             _ => {
                 // Time to process collected info
-                let timestamp = if timestamp != Timestamp::MIN { *timestamp } else { bail!("Message timestamp unknown for line '{line}'") };
-                let from_id = user_id.context("Message author unknown for line '{line}'")?;
+                let location = super::line_location(file_name, first_line_no);
+                let from_id = match user_id {
+                    Some(from_id) if timestamp != Timestamp::MIN => from_id,
+                    _ => {
+                        let e = datetime_error.take().unwrap_or_else(|| anyhow!("No valid sender or date"));
+                        ctx.tolerate(location, e.context("Message dropped"))?;
+                        lines.clear();
+                        continue;
+                    }
+                };
 
                 last_internal_id = MessageInternalId(*last_internal_id + 1);
 
-                let (text, content_option) = parse_message_text(&lines)?;
+                let (text, content_option) = match parse_message_text(&lines) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        ctx.tolerate(location, e)?;
+                        let mut msg = super::placeholder_message(None, *timestamp, from_id, lines.iter().join("\n"));
+                        msg.internal_id = *last_internal_id;
+                        result.push(msg);
+                        user_id = None;
+                        lines.clear();
+                        continue;
+                    }
+                };
                 result.push(Message::new(
                     *last_internal_id,
                     None /* source_id_option */,
-                    timestamp,
+                    *timestamp,
                     from_id,
                     text,
                     message_regular! {
@@ -181,6 +237,9 @@ fn parse_messages(content: &str, myself: &User, other: &User) -> Result<Vec<Mess
                         forward_from_name_option: None,
                         reply_to_message_id_option: None,
                         content_option,
+                        extra_contents: vec![],
+                        forward_origin_option: None,
+                        ttl_sec_option: None,
                     },
                 ));
                 user_id = None;
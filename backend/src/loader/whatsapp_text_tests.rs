@@ -7,6 +7,7 @@ use pretty_assertions::{assert_eq, assert_ne};
 use crate::{NoChooser, User};
 use crate::dao::ChatHistoryDao;
 use crate::entity_utils::*;
+use crate::loader::{LoadMode, placeholder_text};
 use crate::protobuf::history::content::SealedValueOptional::*;
 use crate::protobuf::history::message::*;
 use crate::protobuf::history::message_service::SealedValueOptional::*;
@@ -37,6 +38,10 @@ fn loading_2023_10() -> EmptyRes {
         last_name_option: None,
         username_option: None,
         phone_number_option: Some("+123 45 6789".to_owned()),
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     };
 
     assert_eq!(dao.users_single_ds(), vec![myself.clone(), member.clone()]);
@@ -56,6 +61,9 @@ fn loading_2023_10() -> EmptyRes {
             member_ids: vec![myself.id, member.id],
             msg_count: 10,
             main_chat_id: None,
+            description_option: None,
+            created_timestamp_option: None,
+            member_details: vec![],
         });
 
         let msgs = dao.first_messages(&chat, 99999)?;
@@ -79,6 +87,9 @@ fn loading_2023_10() -> EmptyRes {
             ],
             searchable_string: "hello there! this is a multi-line message!".to_owned(),
             typed: Some(MESSAGE_REGULAR_NO_CONTENT.clone()),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[1], Message {
             internal_id: 1,
@@ -95,6 +106,9 @@ fn loading_2023_10() -> EmptyRes {
             ],
             searchable_string: "and these messages".to_owned(),
             typed: Some(MESSAGE_REGULAR_NO_CONTENT.clone()),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[2], Message {
             internal_id: 2,
@@ -111,6 +125,9 @@ fn loading_2023_10() -> EmptyRes {
             ],
             searchable_string: "should not be reordered!".to_owned(),
             typed: Some(MESSAGE_REGULAR_NO_CONTENT.clone()),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[3], Message {
             internal_id: 3,
@@ -127,6 +144,9 @@ fn loading_2023_10() -> EmptyRes {
             ],
             searchable_string: "should not be reordered indeed!".to_owned(),
             typed: Some(MESSAGE_REGULAR_NO_CONTENT.clone()),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[4], Message {
             internal_id: 4,
@@ -155,7 +175,13 @@ fn loading_2023_10() -> EmptyRes {
                         is_one_time: false,
                     }))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[5], Message {
             internal_id: 5,
@@ -182,7 +208,13 @@ fn loading_2023_10() -> EmptyRes {
                         is_one_time: false,
                     }))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[6], Message {
             internal_id: 6,
@@ -203,7 +235,13 @@ fn loading_2023_10() -> EmptyRes {
                         duration_sec_option: None,
                     }))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[7], Message {
             internal_id: 7,
@@ -226,7 +264,13 @@ fn loading_2023_10() -> EmptyRes {
                         emoji_option: None,
                     }))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[8], Message {
             internal_id: 8,
@@ -243,7 +287,13 @@ fn loading_2023_10() -> EmptyRes {
                 content_option: Some(Content {
                     sealed_value_optional: Some(FILE_UNAVAILABLE.clone())
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
         assert_eq!(msgs[9], Message {
             internal_id: 9,
@@ -260,12 +310,52 @@ fn loading_2023_10() -> EmptyRes {
                 content_option: Some(Content {
                     sealed_value_optional: Some(FILE_UNAVAILABLE.clone())
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         });
     }
     Ok(())
 }
 
+#[test]
+fn loading_lenient() -> EmptyRes {
+    let tmp_dir = TmpDir::new();
+    let res = tmp_dir.path.join("WhatsApp Chat with Bob.txt");
+    fs::write(&res, "10/1/23, 10:00 - Me: Hi\n\
+                     13/13/2023, 10:01 - Bob: Bad date\n\
+                     10/1/23, 10:02 - Bob: XYZ-0001.bin (file attached)\n\
+                     Caption\n\
+                     10/1/23, 10:03 - Me: Bye\n")?;
+
+    let mut ctx = LoadContext::new(LoadMode::Strict);
+    assert!(LOADER.load_with_context(&res, &NoChooser, &mut ctx).is_err());
+
+    // Message with a bad date is dropped, message with an unknown attachment is kept as a raw text
+    let mut ctx = LoadContext::new(LoadMode::Lenient);
+    let dao = LOADER.load_with_context(&res, &NoChooser, &mut ctx)?;
+    let chat = dao.cwms_single_ds().remove(0).chat;
+    assert_eq!(chat.msg_count, 3);
+
+    let msgs = dao.first_messages(&chat, 99999)?;
+    assert_eq!(msgs.iter().map(|m| m.text.clone()).collect_vec(), vec![
+        vec![RichText::make_plain("Hi".to_owned())],
+        placeholder_text("XYZ-0001.bin (file attached)\nCaption".to_owned()),
+        vec![RichText::make_plain("Bye".to_owned())],
+    ]);
+
+    let warnings = ctx.into_warnings();
+    assert_eq!(warnings.iter().map(|w| w.location.as_str()).collect_vec(),
+               vec!["WhatsApp Chat with Bob.txt, line 2", "WhatsApp Chat with Bob.txt, line 3"]);
+    assert!(warnings[0].message.starts_with("Message dropped: Invalid date '13/13/2023, 10:01'"));
+    assert_eq!(warnings[1].message, "Unknown file type: XYZ-0001.bin");
+    Ok(())
+}
+
 //
 // Helpers
 //
@@ -278,6 +368,10 @@ fn expected_myself(ds_uuid: &PbUuid) -> User {
         last_name_option: None,
         username_option: None,
         phone_number_option: None,
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     }
 }
 
@@ -0,0 +1,155 @@
+#![allow(unused_imports)]
+
+use std::fs;
+
+use pretty_assertions::{assert_eq, assert_ne};
+
+use crate::NoChooser;
+use crate::dao::ChatHistoryDao;
+
+use super::*;
+
+//
+// Tests
+//
+
+#[test]
+fn batch_import() -> EmptyRes {
+    let tmp_dir = TmpDir::new();
+    let import_dir = tmp_dir.path.join("to import");
+    fs::create_dir(&import_dir)?;
+
+    let copy_options = fs_extra::dir::CopyOptions::new();
+    fs_extra::dir::copy(resource("csv_2026-10"), &import_dir, &copy_options)?;
+    fs_extra::dir::copy(resource("whatsapp-text_2023-10"), &import_dir, &copy_options)?;
+
+    // Broken file that is recognized but fails to load
+    let broken_dir = import_dir.join("broken");
+    fs::create_dir(&broken_dir)?;
+    fs::write(broken_dir.join("chat.csv"), "Date,From,Message\nnot a date,Me,Hello\n")?;
+    fs::write(broken_dir.join("chat.mapping.json"), r#"{
+        "timestamp_column": "Date",
+        "timestamp_format": "%Y-%m-%d %H:%M:%S",
+        "sender_column": "From",
+        "text_column": "Message",
+        "myself": "Me"
+    }"#)?;
+
+    // Archives within exports are their attachments, not separate exports
+    fs::write(import_dir.join("csv_2026-10").join("attachment.zip"), "")?;
+    fs::create_dir(import_dir.join("whatsapp-text_2023-10").join("nested"))?;
+    fs::write(import_dir.join("whatsapp-text_2023-10").join("nested").join("attachment.tar.gz"), "")?;
+
+    let loader = Loader::new(&*HTTP_CLIENT);
+    let found = loader.find_loadable_files(&import_dir)?;
+    assert_eq!(found, vec![
+        broken_dir.join("chat.csv"),
+        import_dir.join("csv_2026-10").join("chat.csv"),
+        import_dir.join("whatsapp-text_2023-10").join("WhatsApp Chat with +123 45 6789.txt"),
+    ]);
+
+    let dst_dir = tmp_dir.path.join("dst");
+    fs::create_dir(&dst_dir)?;
+    let mut dst_dao = SqliteDao::create(&dst_dir.join(SqliteDao::FILENAME))?;
+    let results = loader.batch_import(&import_dir, &mut dst_dao, &NoChooser, &LoadContext::new(LoadMode::Strict))?;
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].ds_uuids, vec![]);
+    assert!(results[0].error_option.as_ref().is_some_and(|e| e.contains("Invalid timestamp")));
+    for r in &results[1..] {
+        assert_eq!(r.error_option, None);
+        assert_eq!(r.ds_uuids.len(), 1);
+    }
+
+    let datasets = dst_dao.datasets()?;
+    assert_eq!(datasets.iter().map(|ds| ds.uuid.value.clone()).sorted().collect_vec(),
+               results[1..].iter().map(|r| r.ds_uuids[0].value.clone()).sorted().collect_vec());
+    for ds in datasets.iter() {
+        assert_eq!(dst_dao.chats(&ds.uuid)?.len(), 1);
+    }
+    Ok(())
+}
+
+#[test]
+fn glob_matching() {
+    assert!(glob_matches("*.chat", "history.chat"));
+    assert!(glob_matches("*.chat", ".chat"));
+    assert!(!glob_matches("*.chat", "history.chat.bak"));
+    assert!(glob_matches("WhatsApp Chat with *.txt", "WhatsApp Chat with Bob.txt"));
+    assert!(glob_matches("msg?.db", "msg1.db"));
+    assert!(!glob_matches("msg?.db", "msg.db"));
+    assert!(glob_matches("result.json", "result.json"));
+    assert!(!glob_matches("result.json", "Result.json"));
+}
+
+#[test]
+fn list_loaders() {
+    let loader = Loader::new(&*HTTP_CLIENT);
+    let infos = loader.list_loaders();
+    assert_eq!(infos.len(), loader.loaders.len() + 2);
+    assert!(infos.iter().all(|info| !info.file_globs.is_empty()));
+
+    let by_name = |name: &str| infos.iter().find(|info| info.name == name).unwrap();
+    assert_eq!(by_name("Telegram").src_type_option, Some(SourceType::Telegram as i32));
+    assert!(by_name("Telegram").needs_myself_chooser);
+    assert!(by_name("Tinder (db)").needs_network);
+    assert!(!by_name("WhatsApp (db)").needs_network);
+    assert_eq!(by_name("WhatsApp (db)").file_globs, vec!["msgstore.db".to_owned()]);
+    assert_eq!(by_name("Archive").src_type_option, None);
+}
+
+#[test]
+fn probe() -> EmptyRes {
+    let loader = Loader::new(&*HTTP_CLIENT);
+
+    let res = loader.probe(&resource("csv_2026-10").join("chat.csv"))?;
+    assert_eq!(res.accepted_by, vec!["CSV (generic)".to_owned()]);
+    assert_eq!(res.rejections.len(), loader.loaders.len() - 1);
+    let mra_rejection = res.rejections.iter().find(|r| r.loader_name == "Mail.Ru Agent").unwrap();
+    assert_eq!(mra_rejection.reason, "Given file is not mra.dbs");
+
+    let tmp_dir = TmpDir::new();
+    let zip_path = tmp_dir.path.join("export.zip");
+    fs::write(&zip_path, "")?;
+    let res = loader.probe(&zip_path)?;
+    assert_eq!(res.accepted_by, vec!["Archive".to_owned()]);
+    assert_eq!(res.rejections, vec![]);
+
+    assert!(loader.probe(&tmp_dir.path.join("missing.json")).is_err());
+    Ok(())
+}
+
+#[test]
+fn progress_and_cancellation() -> EmptyRes {
+    let loader = Loader::new(&*HTTP_CLIENT);
+    let res = resource("telegram_2020-01").join("result.json");
+
+    let progress = Arc::new(LoadProgress::default());
+    let mut ctx = LoadContext::with_progress(LoadMode::Strict, progress.clone());
+    let dao = loader.parse_with_context(&res, &NoChooser, &mut ctx)?;
+    let cwms = dao.cwms_single_ds();
+    assert_eq!(progress.phase(), "Parsing Telegram");
+    assert_eq!(progress.chats_parsed(), cwms.len());
+    assert_eq!(progress.messages_parsed(), cwms.iter().map(|cwm| cwm.messages.len()).sum::<usize>());
+    assert_eq!(progress.files_copied(), 0);
+
+    let progress = Arc::new(LoadProgress::default());
+    progress.cancel();
+    let mut ctx = LoadContext::with_progress(LoadMode::Strict, progress.clone());
+    let result = loader.parse_with_context(&res, &NoChooser, &mut ctx);
+    assert!(result.is_err_and(|e| e.to_string() == "Load cancelled"));
+    assert_eq!(progress.chats_parsed(), 1);
+    Ok(())
+}
+
+#[test]
+fn cancellation_within_chat() -> EmptyRes {
+    let progress = Arc::new(LoadProgress::default());
+    let ctx = LoadContext::with_progress(LoadMode::Strict, progress.clone());
+    progress.cancel();
+    for idx in 0..(CANCEL_CHECK_MSG_INTERVAL - 1) {
+        ctx.message_parsed(idx)?;
+    }
+    assert!(ctx.message_parsed(CANCEL_CHECK_MSG_INTERVAL - 1).is_err_and(|e| e.to_string() == "Load cancelled"));
+    Ok(())
+}
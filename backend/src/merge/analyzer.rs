@@ -1,6 +1,5 @@
 use std::cmp::Ordering;
-
-use itertools::Itertools;
+use std::iter;
 
 use crate::dao::ChatHistoryDao;
 use crate::prelude::*;
@@ -75,6 +74,7 @@ impl<'a> DatasetDiffAnalyzer<'a> {
                     let (mm, sm) = cx.advance_both()?;
                     let mm_internal_id = mm.typed_id();
                     let sm_internal_id = sm.typed_id();
+                    cx.advance_split_album(&mm, &sm)?;
 
                     // Matching subsequence starts
                     state = InProgress(Match {
@@ -157,7 +157,8 @@ impl<'a> DatasetDiffAnalyzer<'a> {
                 //
 
                 ((Some(mm), Some(sm)), InProgress(Match { .. })) if matches(mm, sm)? => {
-                    cx.advance_both()?;
+                    let (mm, sm) = cx.advance_both()?;
+                    cx.advance_split_album(&mm, &sm)?;
                 }
 
                 //
@@ -415,6 +416,19 @@ impl AnalysContext<'_> {
         assert!(next.internal_id != *NO_INTERNAL_ID);
         Ok(next)
     }
+
+    /// If matching messages are the same album, merged into one message on one side and split into several
+    /// on the other, advances the split side past the rest of its album items.
+    fn advance_split_album(&mut self, mm: &MasterMessage, sm: &SlaveMessage) -> EmptyRes {
+        let (mut mm_len, mut sm_len) = (mm.album_len(), sm.album_len());
+        while mm_len > sm_len && self.sm_stream.peek().is_some_and(|next| next.0.is_album_continuation_of(&sm.0)) {
+            sm_len += self.advance_slave()?.album_len();
+        }
+        while sm_len > mm_len && self.mm_stream.peek().is_some_and(|next| next.0.is_album_continuation_of(&mm.0)) {
+            mm_len += self.advance_master()?.album_len();
+        }
+        Ok(())
+    }
 }
 
 const BATCH_SIZE: usize = 1000;
@@ -485,7 +499,7 @@ impl<'a, T: WithTypedId> Iterator for BatchedMessageIterator<'a, T> {
 
 /**
  * Equality test, but treats master and slave messages as equal if either of them has content - unless they both do
- * and it's mismatching.
+ * and it's mismatching. Same goes for forward origin and TTL.
  * Also ignores edit timestamp if nothing else is changed.
  */
 fn equals_with_no_mismatching_content(mm_eq: PracticalEqTuple<MasterMessage>,
@@ -495,29 +509,48 @@ fn equals_with_no_mismatching_content(mm_eq: PracticalEqTuple<MasterMessage>,
 
     // Special case: Telegram 2023-11 started exporting double styles (bold+X)
     // as bold instead of an X. We want to ignore this change.
-    fn text_to_comparable(rte: &RichTextElement) -> RichTextElement {
+    // Also, mentions and other entities used to be stored as plaintext, so we compare them as such.
+    fn text_to_comparable(rtes: &[RichTextElement]) -> Vec<RichTextElement> {
         use rich_text_element::Val::*;
-        match rte.val {
-            Some(Italic(ref v)) => RichText::make_bold(v.text.clone()),
-            Some(Underline(ref v)) => RichText::make_bold(v.text.clone()),
-            Some(Strikethrough(ref v)) => RichText::make_bold(v.text.clone()),
-            _ => rte.clone()
+        let mut result: Vec<RichTextElement> = vec![];
+        for rte in rtes {
+            let rte = match rte.val {
+                Some(Italic(ref v)) => RichText::make_bold(v.text.clone()),
+                Some(Underline(ref v)) => RichText::make_bold(v.text.clone()),
+                Some(Strikethrough(ref v)) => RichText::make_bold(v.text.clone()),
+                Some(Mention(ref v)) => RichText::make_plain(format!("@{}", v.text)),
+                Some(CustomEmoji(_)) | Some(Hashtag(_)) | Some(Cashtag(_)) | Some(BotCommand(_)) |
+                Some(Email(_)) | Some(Phone(_)) | Some(BankCard(_)) =>
+                    RichText::make_plain(rte.get_text().unwrap_or_default().to_owned()),
+                _ => rte.clone()
+            };
+            match (result.last().map(|last| &last.val), &rte.val) {
+                (Some(Some(Plain(last_v))), Some(Plain(v))) => {
+                    let merged = RichText::make_plain(format!("{}{}", last_v.text, v.text));
+                    *result.last_mut().unwrap() = merged;
+                }
+                _ => result.push(rte)
+            }
         }
+        result
     }
     fn regular_msg_to_comparable(m: &Message, mr: &MessageRegular) -> Message {
         Message {
             typed: Some(message_regular! {
                 content_option: None,
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
                 edit_timestamp_option: None,
                 reply_to_message_id_option: None,
                 ..mr.clone()
             }),
-            text: m.text.iter().map(text_to_comparable).collect_vec(),
+            text: text_to_comparable(&m.text),
             ..m.clone()
         }
     }
-    fn has_content(c: &Option<Content>, root: &DatasetRoot) -> bool {
-        c.as_ref().and_then(|c| c.path_file_option(root))
+    fn has_content(c: Option<&Content>, root: &DatasetRoot) -> bool {
+        c.and_then(|c| c.path_file_option(root))
             .map(|p| p.exists())
             .unwrap_or(false)
     }
@@ -537,12 +570,33 @@ fn equals_with_no_mismatching_content(mm_eq: PracticalEqTuple<MasterMessage>,
                 return Ok(false);
             }
 
-            if !has_content(&mm_regular.content_option, mm_eq.ds_root) ||
-                !has_content(&sm_regular.content_option, sm_eq.ds_root) {
-                return Ok(true);
+            // Forward origin and TTL are only known for newer exports, so they're compared only when both have them
+            if let (Some(mm_fo), Some(sm_fo)) = (&mm_regular.forward_origin_option, &sm_regular.forward_origin_option) {
+                if mm_fo != sm_fo {
+                    return Ok(false);
+                }
+            }
+            if let (Some(mm_ttl), Some(sm_ttl)) = (mm_regular.ttl_sec_option, sm_regular.ttl_sec_option) {
+                if mm_ttl != sm_ttl {
+                    return Ok(false);
+                }
             }
 
-            mm_eq.with(&mm_regular.content_option).practically_equals(&sm_eq.with(&sm_regular.content_option))
+            // Album might be merged in one dataset and split into separate messages in another, so only contents
+            // both have are compared here - the rest of the split album is matched by its remaining messages
+            let content_pairs =
+                iter::once((mm_regular.content_option.as_ref(), sm_regular.content_option.as_ref()))
+                    .chain(mm_regular.extra_contents.iter().zip(sm_regular.extra_contents.iter())
+                        .map(|(mm_c, sm_c)| (Some(mm_c), Some(sm_c))));
+            for (mm_c, sm_c) in content_pairs {
+                if !has_content(mm_c, mm_eq.ds_root) || !has_content(sm_c, sm_eq.ds_root) {
+                    continue;
+                }
+                if !mm_eq.with(mm_c.unwrap()).practically_equals(&sm_eq.with(sm_c.unwrap()))? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
         }
         (message_service_pat!(GroupEditPhoto(MessageServiceGroupEditPhoto { photo: mm_photo })),
             message_service_pat!(GroupEditPhoto(MessageServiceGroupEditPhoto { photo: sm_photo }))) => {
@@ -558,6 +612,12 @@ fn equals_with_no_mismatching_content(mm_eq: PracticalEqTuple<MasterMessage>,
             }
             mm_eq_sm()
         }
+        (message_service_pat!(Notice(_)), message_service_pat!(SetMessagesTtl(_))) |
+        (message_service_pat!(SetMessagesTtl(_)), message_service_pat!(Notice(_))) => {
+            // Messages TTL changes used to be stored as notices, text is enough to compare them
+            let mm_copy = Message { typed: sm_eq.v.0.typed.clone(), ..mm_eq.v.0.clone() };
+            mm_eq.with(&mm_copy).practically_equals(&sm_eq.apply(|m| &m.0))
+        }
         _ => mm_eq_sm()
     }
 }
@@ -3,6 +3,7 @@
 use std::fmt::format;
 use chrono::Duration;
 use chrono::prelude::*;
+use itertools::Itertools;
 use lazy_static::lazy_static;
 use pretty_assertions::{assert_eq, assert_ne};
 
@@ -682,6 +683,9 @@ fn present_absent_not_downloaded() -> EmptyRes {
                 content_option: Some(Content {
                     sealed_value_optional: Some(content::SealedValueOptional::Photo(photo.clone()))
                 }),
+                extra_contents: vec![],
+                forward_origin_option: None,
+                ttl_sec_option: None,
             }
         } else {
             message_service!(message_service::SealedValueOptional::GroupEditPhoto(
@@ -697,6 +701,9 @@ fn present_absent_not_downloaded() -> EmptyRes {
             searchable_string: make_searchable_string(&text, &typed),
             text: text,
             typed: Some(typed),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         }
     };
 
@@ -864,6 +871,100 @@ fn telegram_2023_11_amending_double_style_export() -> EmptyRes {
     Ok(())
 }
 
+/// Mentions used to be stored as plain text, e.g. WhatsApp "@<phone number>"
+#[test]
+fn plaintext_mention_export() -> EmptyRes {
+    let msgs = create_messages(src_id(0));
+    let msgs_a = msgs.iter().map(|m| Message {
+        text: vec![RichText::make_plain("Hi @11111!".to_owned())],
+        ..m.clone()
+    }).collect_vec();
+    let msgs_b = msgs.iter().map(|m| Message {
+        text: vec![
+            RichText::make_plain("Hi ".to_owned()),
+            RichText::make_mention(Some(1), "11111".to_owned()),
+            RichText::make_plain("!".to_owned()),
+        ],
+        ..m.clone()
+    }).collect_vec();
+    let helper = MergerHelper::new_as_is(MAX_USER_ID, msgs_a, msgs_b);
+    let analysis = analyzer(&helper).analyze(helper.m.cwd(), helper.s.cwd(), "", false)?;
+    assert_eq!(
+        analysis, vec![
+            Match(MergeAnalysisSectionMatch {
+                first_master_msg_id: helper.m.msgs[&src_id(0)].typed_id(),
+                last_master_msg_id: helper.m.msgs[&src_id(0)].typed_id(),
+                first_slave_msg_id: helper.s.msgs[&src_id(0)].typed_id(),
+                last_slave_msg_id: helper.s.msgs[&src_id(0)].typed_id(),
+            }),
+        ]
+    );
+    Ok(())
+}
+
+/// Forward origin missing on one side (e.g. older export) is fine, but mismatching one is a conflict
+#[test]
+fn forward_origin() -> EmptyRes {
+    let msgs = create_messages(src_id(2));
+    let with_origin = |m: &Message, chat_name: &str| {
+        let mut m = m.clone();
+        let message_regular_pat! { forward_origin_option, .. } = m.typed_mut() else { unreachable!() };
+        *forward_origin_option = Some(ForwardOrigin {
+            from_id_option: None,
+            chat_name_option: Some(chat_name.to_owned()),
+            timestamp_option: Some(1234567),
+            source_id_option: None,
+            from_name_option: None,
+        });
+        m
+    };
+    let msgs_a = vec![msgs[0].clone(), with_origin(&msgs[1], "Chat"), with_origin(&msgs[2], "Chat")];
+    let msgs_b = vec![with_origin(&msgs[0], "Chat"), with_origin(&msgs[1], "Chat"), with_origin(&msgs[2], "Other Chat")];
+    let helper = MergerHelper::new_as_is(MAX_USER_ID, msgs_a, msgs_b);
+    let analysis = analyzer(&helper).analyze(helper.m.cwd(), helper.s.cwd(), "", false)?;
+    assert_eq!(
+        analysis, vec![
+            Match(MergeAnalysisSectionMatch {
+                first_master_msg_id: helper.m.msgs[&src_id(0)].typed_id(),
+                last_master_msg_id: helper.m.msgs[&src_id(1)].typed_id(),
+                first_slave_msg_id: helper.s.msgs[&src_id(0)].typed_id(),
+                last_slave_msg_id: helper.s.msgs[&src_id(1)].typed_id(),
+            }),
+            Conflict(MergeAnalysisSectionConflict {
+                first_master_msg_id: helper.m.msgs[&src_id(2)].typed_id(),
+                last_master_msg_id: helper.m.msgs[&src_id(2)].typed_id(),
+                first_slave_msg_id: helper.s.msgs[&src_id(2)].typed_id(),
+                last_slave_msg_id: helper.s.msgs[&src_id(2)].typed_id(),
+            }),
+        ]
+    );
+    Ok(())
+}
+
+/// Older exports have album split into separate messages, newer ones have it merged
+#[test]
+fn split_and_merged_album() -> EmptyRes {
+    let msgs = create_messages(src_id(3));
+    let (album, album_split) = create_album_messages(1, 1, &["1.jpg", "2.jpg"]);
+    let msgs_merged = vec![msgs[0].clone(), album, msgs[3].clone()];
+    let msgs_split = [vec![msgs[0].clone()], album_split, vec![msgs[3].clone()]].concat();
+    for (msgs_a, msgs_b) in [(msgs_merged.clone(), msgs_split.clone()), (msgs_split, msgs_merged)] {
+        let helper = MergerHelper::new_as_is(MAX_USER_ID, msgs_a, msgs_b);
+        let analysis = analyzer(&helper).analyze(helper.m.cwd(), helper.s.cwd(), "", false)?;
+        assert_eq!(
+            analysis, vec![
+                Match(MergeAnalysisSectionMatch {
+                    first_master_msg_id: helper.m.msgs[&src_id(0)].typed_id(),
+                    last_master_msg_id: helper.m.msgs[&src_id(3)].typed_id(),
+                    first_slave_msg_id: helper.s.msgs[&src_id(0)].typed_id(),
+                    last_slave_msg_id: helper.s.msgs[&src_id(3)].typed_id(),
+                }),
+            ]
+        );
+    }
+    Ok(())
+}
+
 //
 // Helpers
 //
@@ -1,3 +1,6 @@
+use std::iter::{self, Peekable};
+use std::vec::IntoIter;
+
 use itertools::Itertools;
 
 use crate::dao::ChatHistoryDao;
@@ -121,6 +124,16 @@ fn merge_inner(
                     &master_ds_root
                 };
 
+                // Same goes for chat metadata that slave might lack
+                let master_chat = &master.cwds[chat_id].chat;
+                if chat_to_insert.chat.description_option.is_none() {
+                    chat_to_insert.chat.description_option = master_chat.description_option.clone();
+                }
+                if chat_to_insert.chat.created_timestamp_option.is_none() {
+                    chat_to_insert.chat.created_timestamp_option = master_chat.created_timestamp_option;
+                }
+                merge_member_details(&mut chat_to_insert.chat, master_chat);
+
                 Some((chat_to_insert, ds_root, cm))
             }
         }
@@ -134,18 +147,18 @@ fn merge_inner(
     ensure!(master_self.id == slave_self.id, "Myself of merged datasets doesn't match!");
     for um in user_merges {
         let user_to_insert_option = match um {
-            UserMergeDecision::Retain(user_id) => Some(master.users[&user_id].clone()),
-            UserMergeDecision::MatchOrDontReplace(user_id) => Some(master.users[&user_id].clone()),
-            UserMergeDecision::Add(user_id) => Some(slave.users[&user_id].clone()),
+            UserMergeDecision::Retain(user_id) => Some((master.users[&user_id].clone(), &master_ds_root)),
+            UserMergeDecision::MatchOrDontReplace(user_id) => Some((master.users[&user_id].clone(), &master_ds_root)),
+            UserMergeDecision::Add(user_id) => Some((slave.users[&user_id].clone(), &slave_ds_root)),
             UserMergeDecision::DontAdd(user_id) if selected_chat_members.contains(&user_id.0) =>
                 bail!("Cannot skip user {} because it's used in a chat that wasn't skipped", user_id.0),
             UserMergeDecision::DontAdd(_) => None,
-            UserMergeDecision::Replace(user_id) => Some(slave.users[&user_id].clone()),
+            UserMergeDecision::Replace(user_id) => Some((slave.users[&user_id].clone(), &slave_ds_root)),
         };
-        if let Some(mut user) = user_to_insert_option {
+        if let Some((mut user, user_ds_root)) = user_to_insert_option {
             user.ds_uuid = new_ds.uuid.clone();
             let is_myself = user.id == master_self.id;
-            new_dao.insert_user(user, is_myself)?;
+            new_dao.insert_user(user, is_myself, user_ds_root)?;
         }
     }
     let final_users = new_dao.users(&new_ds.uuid)?;
@@ -217,19 +230,16 @@ fn merge_inner(
                                 slave.dao.messages_slice(&slave_cwd.chat,
                                                          v.first_slave_msg_id.generalize(),
                                                          v.last_slave_msg_id.generalize())?;
-                            assert!(master_msgs.len() == slave_msgs.len());
-
-                            let grouped_total_msgs = master_msgs.into_iter().zip(slave_msgs)
-                                .map(|(mm, sm)| {
-                                    let mm_files = mm.files(&master_ds_root).into_iter().filter(|f| f.exists()).collect_vec();
-                                    let sm_files = sm.files(&slave_ds_root).into_iter().filter(|f| f.exists()).collect_vec();
-                                    if mm_files.len() >= sm_files.len() {
+                            let grouped_total_msgs = zip_albums(master_msgs, slave_msgs)
+                                .flat_map(|(mut mms, sms)| {
+                                    let existing_files_count = |msgs: &[Message], ds_root: &DatasetRoot|
+                                        msgs.iter().flat_map(|m| m.files(ds_root)).filter(|f| f.exists()).count();
+                                    if existing_files_count(&mms, &master_ds_root) >= existing_files_count(&sms, &slave_ds_root) {
                                         // Adopt slave source IDs
-                                        let mut mm = mm;
-                                        adopt_slave_source_ids(&mut mm, &sm);
-                                        (mm, Source::Master)
+                                        adopt_slave_source_ids(&mut mms[0], &sms[0]);
+                                        mms.into_iter().map(|mm| (mm, Source::Master)).collect_vec()
                                     } else {
-                                        (sm, Source::Slave)
+                                        sms.into_iter().map(|sm| (sm, Source::Slave)).collect_vec()
                                     }
                                 })
                                 .group_by(|(_m, src)| *src);
@@ -302,6 +312,26 @@ fn merge_inner(
     Ok(new_ds)
 }
 
+/// Fills in member details (roles, join and leave times) that slave chat lacks from master chat,
+/// for members the merged chat still has.
+fn merge_member_details(slave_chat: &mut Chat, master_chat: &Chat) {
+    for master_md in master_chat.member_details.iter().filter(|md| slave_chat.member_ids.contains(&md.user_id)) {
+        match slave_chat.member_details.iter_mut().find(|md| md.user_id == master_md.user_id) {
+            Some(slave_md) => {
+                slave_md.role_option = slave_md.role_option.or(master_md.role_option);
+                slave_md.joined_timestamp_option = slave_md.joined_timestamp_option.or(master_md.joined_timestamp_option);
+                // Master knowing the member left doesn't matter if slave knows they have joined since
+                if slave_md.left_timestamp_option.is_none() &&
+                    master_md.left_timestamp_option > slave_md.joined_timestamp_option {
+                    slave_md.left_timestamp_option = master_md.left_timestamp_option;
+                }
+            }
+            None => slave_chat.member_details.push(master_md.clone()),
+        }
+    }
+    slave_chat.member_details.sort_by_key(|md| md.user_id);
+}
+
 fn copy_all_messages(
     src_dao: &dyn ChatHistoryDao,
     src_cwd: &ChatWithDetails,
@@ -368,6 +398,33 @@ fn fixup_members(msg: &mut Message, final_users: &[User], cwd: &ChatWithDetails)
 
 /// Change master message by setting all source message IDs to those from slave message.
 /// Messages are assumed to be matching.
+/// Pairs up matching master and slave messages. Usually it's one message for one, but an album might be merged into
+/// one message on one side and split into several on the other - then all messages of the album are paired together.
+fn zip_albums(master_msgs: Vec<Message>, slave_msgs: Vec<Message>) -> impl Iterator<Item=(Vec<Message>, Vec<Message>)> {
+    fn take_album_items(head: &Message, msgs: &mut Peekable<IntoIter<Message>>, mut len: usize, target_len: usize) -> Vec<Message> {
+        let mut group = vec![];
+        while len < target_len {
+            let Some(next) = msgs.next_if(|next| next.is_album_continuation_of(head)) else { break };
+            len += next.album_len();
+            group.push(next);
+        }
+        group
+    }
+
+    let mut master_msgs = master_msgs.into_iter().peekable();
+    let mut slave_msgs = slave_msgs.into_iter().peekable();
+    iter::from_fn(move || {
+        let (mm, sm) = match (master_msgs.next(), slave_msgs.next()) {
+            (Some(mm), Some(sm)) => (mm, sm),
+            (None, None) => return None,
+            (mm, sm) => panic!("Matching sections have different length! Left: {mm:?}, right: {sm:?}"),
+        };
+        let mm_tail = take_album_items(&mm, &mut master_msgs, mm.album_len(), sm.album_len());
+        let sm_tail = take_album_items(&sm, &mut slave_msgs, sm.album_len(), mm.album_len());
+        Some((iter::once(mm).chain(mm_tail).collect_vec(), iter::once(sm).chain(sm_tail).collect_vec()))
+    })
+}
+
 fn adopt_slave_source_ids(mm: &mut Message, sm: &Message) {
     mm.source_id_option = sm.source_id_option;
     match (mm.typed_mut(), sm.typed()) {
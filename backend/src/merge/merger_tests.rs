@@ -1144,6 +1144,76 @@ fn merge_chats_content_appended_on_match() -> EmptyRes {
     Ok(())
 }
 
+/// Older slave export might lack member details master knows about
+#[test]
+fn merge_chats_match_split_and_merged_album() -> EmptyRes {
+    let msgs = (0..=3).map(|i| create_regular_message(i, 1)).collect_vec();
+    let (album, album_split) = create_album_messages(1, 1, &["1.jpg", "2.jpg"]);
+    let msgs_merged = vec![msgs[0].clone(), album, msgs[3].clone()];
+    let msgs_split = [vec![msgs[0].clone()], album_split, vec![msgs[3].clone()]].concat();
+    for (msgs_a, msgs_b) in [(msgs_merged.clone(), msgs_split.clone()), (msgs_split, msgs_merged)] {
+        let helper = MergerHelper::new_as_is(2, msgs_a.clone(), msgs_b);
+        let (new_dao, new_ds, _tmpdir) = merge(
+            &helper,
+            dont_replace_both_users(),
+            vec![ChatMergeDecision::Merge {
+                chat_id: ChatId(1),
+                message_merges: vec![
+                    MessagesMergeDecision::Match(MergeAnalysisSectionMatch {
+                        first_master_msg_id: first_id(&helper.m.msgs),
+                        last_master_msg_id: last_id(&helper.m.msgs),
+                        first_slave_msg_id: first_id(&helper.s.msgs),
+                        last_slave_msg_id: last_id(&helper.s.msgs),
+                    })
+                ],
+            }],
+        );
+
+        // Neither side has files, so master messages are kept as they are
+        let new_chats = new_dao.chats(&new_ds.uuid)?;
+        let new_messages = new_dao.first_messages(&new_chats[0].chat, usize::MAX)?;
+        assert_eq!(new_messages.iter().map(|m| (m.source_id_option, m.album_len())).collect_vec(),
+                   msgs_a.iter().map(|m| (m.source_id_option, m.album_len())).collect_vec());
+    }
+    Ok(())
+}
+
+#[test]
+fn merge_member_details_from_master() {
+    let md = |user_id: i64, role: Option<ChatMemberRole>, joined: Option<i64>, left: Option<i64>| ChatMemberDetails {
+        user_id,
+        role_option: role.map(|r| r as i32),
+        joined_timestamp_option: joined,
+        left_timestamp_option: left,
+    };
+    let master_chat = Chat {
+        member_ids: vec![1, 2, 3, 4],
+        member_details: vec![
+            md(1, Some(ChatMemberRole::Owner), Some(100), None),
+            md(2, None, Some(200), Some(300)),
+            md(3, None, Some(200), Some(300)),
+            md(4, None, Some(400), None),
+        ],
+        ..Default::default()
+    };
+    let mut slave_chat = Chat {
+        member_ids: vec![1, 2, 3],
+        member_details: vec![
+            md(1, None, Some(100), None),
+            md(3, None, Some(500), None),
+        ],
+        ..Default::default()
+    };
+    merge_member_details(&mut slave_chat, &master_chat);
+    assert_eq!(slave_chat.member_details, vec![
+        md(1, Some(ChatMemberRole::Owner), Some(100), None),
+        md(2, None, Some(200), Some(300)),
+        // Rejoined since master has seen them leave
+        md(3, None, Some(500), None),
+        // Member 4 is not in a merged chat
+    ]);
+}
+
 //
 // Helpers
 //
@@ -4,7 +4,9 @@ use std::net::SocketAddr;
 use std::ops::DerefMut;
 use std::sync::{Mutex, MutexGuard};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use itertools::Itertools;
 use tokio::runtime::Handle;
 use tonic::{Code, Request, Response, Status, transport::Server};
 use tonic::transport::Endpoint;
@@ -13,7 +15,7 @@ use myself_chooser::MyselfChooserImpl;
 
 use crate::prelude::*;
 use crate::dao::ChatHistoryDao;
-use crate::loader::Loader;
+use crate::loader::{Loader, LoadProgress};
 use crate::protobuf::history::history_dao_service_server::HistoryDaoServiceServer;
 use crate::protobuf::history::history_loader_service_server::HistoryLoaderServiceServer;
 use crate::protobuf::history::merge_service_server::MergeServiceServer;
@@ -35,21 +37,113 @@ type DaoRefCell = RefCell<Box<dyn ChatHistoryDao>>;
 
 type ChmLock<'a> = MutexGuard<'a, ChatHistoryManagerServer>;
 
+type LoadJobId = String;
+/// Load result, along with per-file results for batch import jobs
+type LoadJobOutcome = StdResult<(LoadResponse, Vec<BatchImportResult>), String>;
+
+/// Finished load jobs are kept around for watchers to pick up the outcome, but not forever
+const FINISHED_LOAD_JOB_TTL: Duration = Duration::from_secs(60 * 60);
+const MAX_LOAD_JOBS: usize = 100;
+
 // Should be used wrapped in Arc<Mutex<Self>>
 pub struct ChatHistoryManagerServer {
-    loader: Loader,
-    myself_chooser: Box<dyn MyselfChooser>,
+    // Shared with background load jobs
+    loader: Arc<Loader>,
+    myself_chooser: Arc<dyn MyselfChooser>,
     loaded_daos: HashMap<DaoKey, DaoRefCell>,
+    load_jobs: HashMap<LoadJobId, Arc<LoadJob>>,
 }
 
 impl ChatHistoryManagerServer {
     pub fn new_wrapped(loader: Loader, myself_chooser: Box<dyn MyselfChooser>) -> Arc<Mutex<Self>> {
         Arc::new(Mutex::new(ChatHistoryManagerServer {
-            loader,
-            myself_chooser,
+            loader: Arc::new(loader),
+            myself_chooser: Arc::from(myself_chooser),
             loaded_daos: HashMap::new(),
+            load_jobs: HashMap::new(),
         }))
     }
+
+    /// Drops finished load jobs that outlived their TTL, then the oldest finished ones beyond the limit.
+    /// Running jobs are never evicted.
+    fn evict_load_jobs(&mut self) {
+        let now = Instant::now();
+        self.load_jobs.retain(|_, job| {
+            job.finished_at().is_none_or(|finished_at| now.duration_since(finished_at) < FINISHED_LOAD_JOB_TTL)
+        });
+        let excess = self.load_jobs.len().saturating_sub(MAX_LOAD_JOBS);
+        let evicted_ids = self.load_jobs.iter()
+            .filter_map(|(id, job)| job.finished_at().map(|finished_at| (finished_at, id.clone())))
+            .sorted()
+            .take(excess)
+            .map(|(_, id)| id)
+            .collect_vec();
+        for id in evicted_ids {
+            self.load_jobs.remove(&id);
+        }
+    }
+}
+
+/// Load running in a background thread, not holding the server lock.
+#[derive(Debug)]
+struct LoadJob {
+    key: DaoKey,
+    /// Absolute path of a database the job writes into, if any
+    target_path_option: Option<PathBuf>,
+    progress: Arc<LoadProgress>,
+    /// Set once the job is finished
+    outcome: Mutex<Option<LoadJobOutcome>>,
+    finished_at: Mutex<Option<Instant>>,
+}
+
+impl LoadJob {
+    fn new(key: DaoKey, target_path_option: Option<PathBuf>) -> Self {
+        LoadJob {
+            key,
+            target_path_option,
+            progress: Default::default(),
+            outcome: Mutex::new(None),
+            finished_at: Mutex::new(None),
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.outcome.lock().unwrap().is_none()
+    }
+
+    fn finished_at(&self) -> Option<Instant> {
+        *self.finished_at.lock().unwrap()
+    }
+
+    fn finish(&self, outcome: LoadJobOutcome) {
+        *self.outcome.lock().unwrap() = Some(outcome);
+        *self.finished_at.lock().unwrap() = Some(Instant::now());
+    }
+
+    fn snapshot(&self) -> LoadJobProgress {
+        let outcome = self.outcome.lock().unwrap().clone();
+        let status = match outcome {
+            None => LoadJobStatus::Running,
+            Some(Ok(_)) => LoadJobStatus::Done,
+            Some(Err(_)) if self.progress.is_cancelled() => LoadJobStatus::Cancelled,
+            Some(Err(_)) => LoadJobStatus::Failed,
+        };
+        let (result_option, batch_results, error_option) = match outcome {
+            None => (None, vec![], None),
+            Some(Ok((response, batch_results))) => (Some(response), batch_results, None),
+            Some(Err(error)) => (None, vec![], Some(error)),
+        };
+        LoadJobProgress {
+            status: status as i32,
+            phase: self.progress.phase(),
+            chats_parsed: self.progress.chats_parsed() as u64,
+            messages_parsed: self.progress.messages_parsed() as u64,
+            files_copied: self.progress.files_copied() as u64,
+            result_option,
+            error_option,
+            batch_results,
+        }
+    }
 }
 
 trait ChatHistoryManagerServerTrait {
@@ -156,6 +250,10 @@ pub async fn debug_request_myself(port: u16) -> Result<usize> {
             last_name_option: None,
             username_option: None,
             phone_number_option: None,
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: None,
+            img_path_option: None,
         },
         User {
             ds_uuid,
@@ -164,6 +262,10 @@ pub async fn debug_request_myself(port: u16) -> Result<usize> {
             last_name_option: Some("User 200 LN".to_owned()),
             username_option: None,
             phone_number_option: None,
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: None,
+            img_path_option: None,
         },
     ])?;
     Ok(chosen)
@@ -4,6 +4,7 @@ use std::sync::{Arc, Mutex};
 use itertools::Itertools;
 use tonic::Request;
 
+use crate::dao::SearchScope;
 use crate::dao::sqlite_dao::SqliteDao;
 use crate::protobuf::history::history_dao_service_server::HistoryDaoService;
 
@@ -179,6 +180,97 @@ impl HistoryDaoService for Arc<Mutex<ChatHistoryManagerServer>> {
         })
     }
 
+    async fn message_receipts(&self, req: Request<MessageReceiptsRequest>) -> TonicResult<MessageReceiptsResponse> {
+        with_dao_by_key!(self, req, dao, {
+            let id = MessageInternalId(req.message_internal_id);
+            let msg = dao.messages_slice(&req.chat, id, id)?.into_iter().next()
+                .with_context(|| format!("Message {} not found!", *id))?;
+            Ok(MessageReceiptsResponse {
+                delivery_status_option: msg.delivery_status_option,
+                receipts: msg.receipts,
+            })
+        })
+    }
+
+    async fn filter_messages(&self, req: Request<FilterMessagesRequest>) -> TonicResult<MessagesResponse> {
+        with_dao_by_key!(self, req, dao, {
+            Ok(MessagesResponse {
+                messages: dao.filter_messages(&req.chat, &req.filter, req.offset as usize, req.limit as usize)?
+            })
+        })
+    }
+
+    async fn media_items(&self, req: Request<MediaItemsRequest>) -> TonicResult<MediaItemsResponse> {
+        with_dao_by_key!(self, req, dao, {
+            let scope = scope_from_request(&req.chat_option, &req.ds_uuid_option);
+            let kinds: Vec<ContentKind> = req.kinds.iter().map(|k| ContentKind::resolve(*k)).try_collect()?;
+            Ok(MediaItemsResponse {
+                items: dao.media_items(scope, &kinds, req.offset as usize, req.limit as usize)?
+            })
+        })
+    }
+
+    async fn links(&self, req: Request<LinksRequest>) -> TonicResult<LinksResponse> {
+        with_dao_by_key!(self, req, dao, {
+            let scope = scope_from_request(&req.chat_option, &req.ds_uuid_option);
+            Ok(LinksResponse {
+                items: dao.links(scope, &req.query, req.domain_option.as_deref(), req.offset as usize, req.limit as usize)?
+            })
+        })
+    }
+
+    async fn link_domains(&self, req: Request<LinkDomainsRequest>) -> TonicResult<LinkDomainsResponse> {
+        with_dao_by_key!(self, req, dao, {
+            let scope = scope_from_request(&req.chat_option, &req.ds_uuid_option);
+            Ok(LinkDomainsResponse {
+                domains: dao.link_domains(scope, &req.query)?
+            })
+        })
+    }
+
+    async fn search_messages(&self, req: Request<SearchMessagesRequest>) -> TonicResult<SearchMessagesResponse> {
+        with_dao_by_key!(self, req, dao, {
+            let scope = scope_from_request(&req.chat_option, &req.ds_uuid_option);
+            let hits = dao.search_messages(scope, &req.query, req.offset as usize, req.limit as usize)?;
+            Ok(SearchMessagesResponse {
+                hits: hits.into_iter().map(|h| MessageSearchHitPb {
+                    ds_uuid: h.ds_uuid,
+                    chat_id: *h.chat_id,
+                    message: h.message,
+                    snippet: h.snippet,
+                }).collect_vec(),
+            })
+        })
+    }
+
+    async fn timeline(&self, req: Request<TimelineRequest>) -> TonicResult<TimelineResponse> {
+        with_dao_by_key!(self, req, dao, {
+            let scope = scope_from_request(&req.chat_option, &req.ds_uuid_option);
+            Ok(TimelineResponse {
+                entries: dao.timeline(scope, req.timestamp_from_option, req.timestamp_to_option,
+                                      req.after_option.as_ref(), req.limit as usize)?
+            })
+        })
+    }
+
+    async fn statistics(&self, req: Request<StatisticsRequest>) -> TonicResult<StatisticsResponse> {
+        with_dao_by_key!(self, req, dao, {
+            let scope = scope_from_request(&req.chat_option, &req.ds_uuid_option);
+            Ok(StatisticsResponse {
+                statistics: dao.statistics(scope, req.top_words_limit as usize)?
+            })
+        })
+    }
+
+    async fn activity_counts(&self, req: Request<ActivityCountsRequest>) -> TonicResult<ActivityCountsResponse> {
+        with_dao_by_key!(self, req, dao, {
+            let granularity = ActivityGranularity::resolve(req.granularity)?;
+            Ok(ActivityCountsResponse {
+                counts: dao.activity_counts(&req.chat, req.timestamp_from_option, req.timestamp_to_option, granularity)?
+            })
+        })
+    }
+
     async fn is_loaded(&self, req: Request<IsLoadedRequest>) -> TonicResult<IsLoadedResponse> {
         with_dao_by_key!(self, req, dao, {
             Ok(IsLoadedResponse {
@@ -260,3 +352,12 @@ impl HistoryDaoService for Arc<Mutex<ChatHistoryManagerServer>> {
         })
     }
 }
+
+/// Chat takes precedence over dataset, if neither is set - the whole DAO is used.
+fn scope_from_request<'a>(chat_option: &'a Option<Chat>, ds_uuid_option: &'a Option<PbUuid>) -> SearchScope<'a> {
+    match (chat_option, ds_uuid_option) {
+        (Some(chat), _) => SearchScope::Chat(chat),
+        (None, Some(ds_uuid)) => SearchScope::Dataset(ds_uuid),
+        (None, None) => SearchScope::Everywhere,
+    }
+}
@@ -1,32 +1,100 @@
 use std::cell::RefCell;
+use std::collections::hash_map::Entry;
 use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use itertools::Itertools;
 
+use futures::Stream;
 use tonic::Request;
 
+use crate::dao::sqlite_dao::SqliteDao;
+use crate::loader::{LoadContext, LoadMode};
 use crate::protobuf::history::history_loader_service_server::*;
 
 use super::*;
 
+/// How often load job progress is reported to watchers
+const WATCH_LOAD_INTERVAL: Duration = Duration::from_millis(250);
+
+type LoadJobProgressStream = Pin<Box<dyn Stream<Item = StatusResult<LoadJobProgress>> + Send>>;
+
 #[tonic::async_trait]
 impl HistoryLoaderService for Arc<Mutex<ChatHistoryManagerServer>> {
+    type WatchLoadStream = LoadJobProgressStream;
+
     async fn load(&self, req: Request<LoadRequest>) -> TonicResult<LoadResponse> {
         self.process_request(&req, move |req, self_lock| {
             let path = fs::canonicalize(&req.path)?;
 
             if let Some(dao) = self_lock.loaded_daos.get(&req.key) {
                 let dao = dao.borrow();
-                return Ok(LoadResponse { name: dao.name().to_owned() });
+                return Ok(LoadResponse { name: dao.name().to_owned(), warnings: vec![] });
             }
+            ensure!(!self_lock.load_jobs.values().any(|job| job.key == req.key && job.is_running()),
+                    "Database {} is being loaded, wait for it to finish", req.key);
 
-            let dao = self_lock.loader.load(&path, self_lock.myself_chooser.as_ref())?;
-            let response = LoadResponse { name: dao.name().to_owned() };
+            let target_path_option = req.target_path_option.as_deref().map(Path::new);
+            if let Some(target_path) = target_path_option {
+                ensure_not_open(self_lock, target_path)?;
+            }
+
+            let mut ctx = LoadContext::new(load_mode(req.lenient_option));
+            let dao = self_lock.loader.load(&path, self_lock.myself_chooser.as_ref(), target_path_option, &mut ctx)?;
+            let response = LoadResponse { name: dao.name().to_owned(), warnings: ctx.into_warnings() };
             self_lock.loaded_daos.insert(req.key.clone(), RefCell::new(dao));
             Ok(response)
         })
     }
 
+    async fn start_load(&self, req: Request<LoadRequest>) -> TonicResult<StartLoadResponse> {
+        self.process_request(&req, |req, self_lock| {
+            let path = fs::canonicalize(&req.path)?;
+            let target_path_option = req.target_path_option.as_ref().map(PathBuf::from);
+            let reserved_path_option = req.target_path_option.as_deref().map(Path::new);
+            let job_id = spawn_load_job(self, self_lock, &req.key, reserved_path_option, load_mode(req.lenient_option),
+                                        move |loader, myself_chooser, ctx| {
+                let dao = loader.load(&path, myself_chooser, target_path_option.as_deref(), ctx)
+                    .inspect_err(|e| log::error!("Failed to load {}: {:?}", path.display(), e))?;
+                Ok((dao, vec![]))
+            })?;
+            Ok(StartLoadResponse { job_id })
+        })
+    }
+
+    async fn watch_load(&self, req: Request<LoadJobRequest>) -> TonicResult<Self::WatchLoadStream> {
+        let job_id = req.get_ref().job_id.clone();
+        let job = lock_or_status(self)?.load_jobs.get(&job_id).cloned()
+            .ok_or_else(|| Status::new(Code::NotFound, format!("Load job {} not found", job_id)))?;
+        // Reporting progress right away, then periodically until the job is finished.
+        // Finished job is left for eviction, so that its outcome can be watched again.
+        let stream = futures::stream::unfold((Some(job), true), move |(job_option, is_first)| {
+            async move {
+                let job = job_option?;
+                if !is_first {
+                    tokio::time::sleep(WATCH_LOAD_INTERVAL).await;
+                }
+                let progress = job.snapshot();
+                let job_option = (progress.status == LoadJobStatus::Running as i32).then_some(job);
+                Some((Ok(progress), (job_option, false)))
+            }
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn cancel_load(&self, req: Request<LoadJobRequest>) -> TonicResult<Empty> {
+        self.process_request(&req, |req, self_lock| {
+            let job = self_lock.load_jobs.get(&req.job_id)
+                .with_context(|| format!("Load job {} not found", req.job_id))?;
+            job.progress.cancel();
+            Ok(Empty {})
+        })
+    }
+
     async fn get_loaded_files(&self, req: Request<Empty>) -> TonicResult<GetLoadedFilesResponse> {
         self.process_request(&req, |_, self_lock| {
             let files = self_lock.loaded_daos.iter()
@@ -59,4 +127,112 @@ impl HistoryLoaderService for Arc<Mutex<ChatHistoryManagerServer>> {
             Ok(EnsureSameResponse { diffs })
         })
     }
+
+    async fn batch_import(&self, req: Request<BatchImportRequest>) -> TonicResult<StartLoadResponse> {
+        self.process_request(&req, |req, self_lock| {
+            let dir_path = fs::canonicalize(&req.dir_path)?;
+            let target_path = PathBuf::from(&req.target_path);
+            ensure!(path_file_name(&target_path)? == SqliteDao::FILENAME,
+                    "Target must be a {} file", SqliteDao::FILENAME);
+            let job_id = spawn_load_job(self, self_lock, &req.key, Some(Path::new(&req.target_path)),
+                                        load_mode(req.lenient_option),
+                                        move |loader, myself_chooser, ctx| {
+                let mut dst_dao = SqliteDao::load_or_create(&target_path)?;
+                let results = loader.batch_import(&dir_path, &mut dst_dao, myself_chooser, ctx)
+                    .inspect_err(|e| log::error!("Failed to import {}: {:?}", dir_path.display(), e))?;
+                Ok((Box::new(dst_dao) as Box<dyn ChatHistoryDao>, results))
+            })?;
+            Ok(StartLoadResponse { job_id })
+        })
+    }
+
+    async fn list_loaders(&self, req: Request<Empty>) -> TonicResult<ListLoadersResponse> {
+        self.process_request(&req, |_, self_lock| {
+            Ok(ListLoadersResponse { loaders: self_lock.loader.list_loaders() })
+        })
+    }
+
+    async fn probe(&self, req: Request<ProbeRequest>) -> TonicResult<ProbeResponse> {
+        self.process_request(&req, |req, self_lock| {
+            self_lock.loader.probe(Path::new(&req.path))
+        })
+    }
+}
+
+/// Registers a load job for the given key and runs it in a background thread, not holding the server lock.
+/// Once done, resulting DAO is loaded under that key.
+fn spawn_load_job<F>(server: &Arc<Mutex<ChatHistoryManagerServer>>,
+                     self_lock: &mut ChatHistoryManagerServer,
+                     key: &DaoKey,
+                     target_path_option: Option<&Path>,
+                     mode: LoadMode,
+                     logic: F) -> Result<LoadJobId>
+    where F: FnOnce(&Loader, &dyn MyselfChooser, &mut LoadContext)
+                    -> Result<(Box<dyn ChatHistoryDao>, Vec<BatchImportResult>)> + Send + 'static
+{
+    ensure!(!self_lock.loaded_daos.contains_key(key), "Database {} is already loaded", key);
+    ensure!(!self_lock.load_jobs.values().any(|job| &job.key == key && job.is_running()),
+            "Database {} is already being loaded", key);
+    self_lock.evict_load_jobs();
+    ensure!(self_lock.load_jobs.len() < MAX_LOAD_JOBS, "Too many load jobs are running");
+    if let Some(target_path) = target_path_option {
+        ensure_not_open(self_lock, target_path)?;
+    }
+
+    let job_id = PbUuid::random().value;
+    let job = Arc::new(LoadJob::new(key.clone(), target_path_option.map(absolute_db_path).transpose()?));
+    self_lock.load_jobs.insert(job_id.clone(), job.clone());
+
+    let server = server.clone();
+    let loader = self_lock.loader.clone();
+    let myself_chooser = self_lock.myself_chooser.clone();
+    let mut ctx = LoadContext::with_progress(mode, job.progress.clone());
+    thread::spawn(move || {
+        // Job has to be finished even if loading panics, otherwise it would stay running forever
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            logic(&loader, myself_chooser.as_ref(), &mut ctx).and_then(|(dao, batch_results)| {
+                let response = LoadResponse { name: dao.name().to_owned(), warnings: ctx.into_warnings() };
+                let mut server_lock = server.lock().map_err(|_| anyhow!("Mutex is poisoned!"))?;
+                // Key might have been taken while loading, in which case our DAO is discarded
+                match server_lock.loaded_daos.entry(job.key.clone()) {
+                    Entry::Occupied(_) => bail!("Database {} was loaded while the job was running", job.key),
+                    Entry::Vacant(entry) => { entry.insert(RefCell::new(dao)); }
+                }
+                Ok((response, batch_results))
+            })
+        })).unwrap_or_else(|payload| {
+            let panic_msg = payload.downcast_ref::<&str>().map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown cause".to_owned());
+            Err(anyhow!("Loading panicked: {panic_msg}"))
+        });
+        job.finish(outcome.map_err(|e| error_to_string(&e)));
+    });
+    Ok(job_id)
+}
+
+/// Database that's about to be written to should neither be open nor be written to by a running load job.
+fn ensure_not_open(self_lock: &ChatHistoryManagerServer, db_path: &Path) -> EmptyRes {
+    let db_path = absolute_db_path(db_path)?;
+    let storage_path = db_path.parent().expect("Absolute DB path has no parent!");
+    // DAO keys are arbitrary, so it's the storage that should be compared
+    let open_key_option = self_lock.loaded_daos.iter()
+        .find(|(_, dao)| fs::canonicalize(dao.borrow().storage_path()).is_ok_and(|p| p == storage_path))
+        .map(|(key, _)| key);
+    if let Some(key) = open_key_option {
+        bail!("Database {} is already open as {}, close it first", db_path.display(), key);
+    }
+    ensure!(!self_lock.load_jobs.values().any(|job| job.is_running() && job.target_path_option.as_ref() == Some(&db_path)),
+            "Database {} is being loaded into, wait for it to finish", db_path.display());
+    Ok(())
+}
+
+/// Database file might not exist yet, but its directory should.
+fn absolute_db_path(db_path: &Path) -> Result<PathBuf> {
+    let dir = db_path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    Ok(fs::canonicalize(dir)?.join(path_file_name(db_path)?))
+}
+
+fn load_mode(lenient_option: Option<bool>) -> LoadMode {
+    if lenient_option == Some(true) { LoadMode::Lenient } else { LoadMode::Strict }
 }
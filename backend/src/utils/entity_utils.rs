@@ -37,3 +37,46 @@ impl From<ChatWithDetails> for ChatWithDetailsPb {
         }
     }
 }
+
+impl From<&content::SealedValueOptional> for ContentKind {
+    fn from(value: &content::SealedValueOptional) -> Self {
+        use content::SealedValueOptional::*;
+        match value {
+            Sticker(_) => ContentKind::Sticker,
+            Photo(_) => ContentKind::Photo,
+            VoiceMsg(_) => ContentKind::VoiceMsg,
+            Audio(_) => ContentKind::Audio,
+            VideoMsg(_) => ContentKind::VideoMsg,
+            Video(_) => ContentKind::Video,
+            File(_) => ContentKind::File,
+            Location(_) => ContentKind::Location,
+            Poll(_) => ContentKind::Poll,
+            SharedContact(_) => ContentKind::SharedContact,
+            WebPage(_) => ContentKind::WebPage,
+        }
+    }
+}
+
+impl From<&message_service::SealedValueOptional> for ServiceKind {
+    fn from(value: &message_service::SealedValueOptional) -> Self {
+        use message_service::SealedValueOptional::*;
+        match value {
+            PhoneCall(_) => ServiceKind::PhoneCall,
+            SuggestProfilePhoto(_) => ServiceKind::SuggestProfilePhoto,
+            PinMessage(_) => ServiceKind::PinMessage,
+            ClearHistory(_) => ServiceKind::ClearHistory,
+            BlockUser(_) => ServiceKind::BlockUser,
+            StatusTextChanged(_) => ServiceKind::StatusTextChanged,
+            Notice(_) => ServiceKind::Notice,
+            SetMessagesTtl(_) => ServiceKind::SetMessagesTtl,
+            GroupCreate(_) => ServiceKind::GroupCreate,
+            GroupEditTitle(_) => ServiceKind::GroupEditTitle,
+            GroupEditPhoto(_) => ServiceKind::GroupEditPhoto,
+            GroupDeletePhoto(_) => ServiceKind::GroupDeletePhoto,
+            GroupInviteMembers(_) => ServiceKind::GroupInviteMembers,
+            GroupRemoveMembers(_) => ServiceKind::GroupRemoveMembers,
+            GroupMigrateFrom(_) => ServiceKind::GroupMigrateFrom,
+            GroupMigrateTo(_) => ServiceKind::GroupMigrateTo,
+        }
+    }
+}
@@ -80,9 +80,11 @@ macro_rules! cloned_equals_without {
 
 impl<'a> PracticalEq for Tup<'a, Chat> {
     fn practically_equals(&self, other: &Self) -> Result<bool> {
-        Ok(cloned_equals_without!(self.v, other.v, Chat, img_path_option: None, member_ids: vec![]) &&
+        Ok(cloned_equals_without!(self.v, other.v, Chat, img_path_option: None, member_ids: vec![], member_details: vec![]) &&
             self.v.member_ids.len() == other.v.member_ids.len() &&
             self.v.member_ids.iter().all(|e| other.v.member_ids.contains(e)) &&
+            self.v.member_details.len() == other.v.member_details.len() &&
+            self.v.member_details.iter().all(|e| other.v.member_details.contains(e)) &&
             self.apply(|v| &v.img_path_option).practically_equals(&other.apply(|v| &v.img_path_option))?)
     }
 }
@@ -97,8 +99,18 @@ impl<'a> PracticalEq for Tup<'a, Message> {
                                   internal_id: 0,
                                   source_id_option: None,
                                   searchable_string: "".to_owned(),
-                                  typed: None) &&
-            self.apply(|v| v.typed()).practically_equals(&other.apply(|v| v.typed()))?)
+                                  text: vec![],
+                                  typed: None,
+                                  reactions: vec![]) &&
+            self.v.text.len() == other.v.text.len() &&
+            self.v.text.iter().zip(other.v.text.iter())
+                .map(|(rte1, rte2)| self.with(rte1).practically_equals(&other.with(rte2)))
+                .fold_ok(true, |acc, eq| acc && eq)? &&
+            self.apply(|v| v.typed()).practically_equals(&other.apply(|v| v.typed()))? &&
+            self.v.reactions.len() == other.v.reactions.len() &&
+            self.v.reactions.iter().zip(other.v.reactions.iter())
+                .map(|(r1, r2)| self.with(r1).practically_equals(&other.with(r2)))
+                .fold_ok(true, |acc, eq| acc && eq)?)
     }
 }
 
@@ -115,8 +127,15 @@ impl<'a> PracticalEq for Tup<'a, message::Typed> {
 
 impl<'a> PracticalEq for Tup<'a, MessageRegular> {
     fn practically_equals(&self, other: &Self) -> Result<bool> {
-        Ok(cloned_equals_without!(self.v, other.v, MessageRegular, forward_from_name_option: None, content_option: None) &&
-            self.apply(|v| &v.content_option).practically_equals(&other.apply(|v| &v.content_option))?)
+        Ok(cloned_equals_without!(self.v, other.v, MessageRegular,
+                                  forward_from_name_option: None,
+                                  content_option: None,
+                                  extra_contents: vec![]) &&
+            self.apply(|v| &v.content_option).practically_equals(&other.apply(|v| &v.content_option))? &&
+            self.v.extra_contents.len() == other.v.extra_contents.len() &&
+            self.v.extra_contents.iter().zip(other.v.extra_contents.iter())
+                .map(|(c1, c2)| self.with(c1).practically_equals(&other.with(c2)))
+                .fold_ok(true, |acc, eq| acc && eq)?)
     }
 }
 
@@ -140,6 +159,7 @@ impl<'a> PracticalEq for Tup<'a, MessageService> {
             case!(BlockUser, c1, c2) => Ok(c1 == c2),
             case!(StatusTextChanged, c1, c2) => Ok(c1 == c2),
             case!(Notice, c1, c2) => Ok(c1 == c2),
+            case!(SetMessagesTtl, c1, c2) => Ok(c1 == c2),
             case!(GroupCreate, c1, c2) =>
                 Ok(c1.title == c2.title &&
                     members_practically_equals((&c1.members, self.cwd), (&c2.members, other.cwd))?),
@@ -161,6 +181,18 @@ impl<'a> PracticalEq for Tup<'a, MessageService> {
     }
 }
 
+impl<'a> PracticalEq for Tup<'a, RichTextElement> {
+    fn practically_equals(&self, other: &Self) -> Result<bool> {
+        use rich_text_element::Val::*;
+        match (self.v.val.as_ref(), other.v.val.as_ref()) {
+            (Some(CustomEmoji(v1)), Some(CustomEmoji(v2))) =>
+                Ok(self.v.searchable_string == other.v.searchable_string &&
+                    self.with(v1).practically_equals(&other.with(v2))?),
+            _ => Ok(self.v == other.v)
+        }
+    }
+}
+
 //
 // Content
 //
@@ -179,6 +211,7 @@ impl<'a> PracticalEq for Tup<'a, Content> {
             (Some(Location(c1)),      Some(Location(c2)))      => self.with(c1).practically_equals(&other.with(c2)),
             (Some(Poll(c1)),          Some(Poll(c2)))          => self.with(c1).practically_equals(&other.with(c2)),
             (Some(SharedContact(c1)), Some(SharedContact(c2))) => self.with(c1).practically_equals(&other.with(c2)),
+            (Some(WebPage(c1)),       Some(WebPage(c2)))       => self.with(c1).practically_equals(&other.with(c2)),
             _ => Ok(false)
         } // @formatter:on
     }
@@ -212,6 +245,9 @@ practical_eq_with_path!(ContentVideoMsg, path_option, thumbnail_path_option);
 practical_eq_with_path!(ContentVideo, path_option, thumbnail_path_option);
 practical_eq_with_path!(ContentFile, path_option, thumbnail_path_option);
 practical_eq_with_path!(ContentSharedContact, vcard_path_option);
+practical_eq_with_path!(ContentWebPage, image_path_option);
+practical_eq_with_path!(Reaction, custom_sticker_path_option);
+practical_eq_with_path!(RteCustomEmoji, path_option);
 
 impl<'a> PracticalEq for Tup<'a, ContentPoll> {
     fn practically_equals(&self, other: &Self) -> Result<bool> {
@@ -34,6 +34,9 @@ lazy_static! {
         forward_from_name_option: None,
         reply_to_message_id_option: None,
         content_option: None,
+        extra_contents: vec![],
+        forward_origin_option: None,
+        ttl_sec_option: None,
     };
 
     // TODO: Do we need cleanup?
@@ -280,6 +283,10 @@ pub fn create_user(ds_uuid: &PbUuid, id: i64) -> User {
         last_name_option: Some(id.to_string()),
         username_option: Some(format!("user{id}")),
         phone_number_option: Some("xxx xx xx".replace("x", &id.to_string())),
+        extra_phone_numbers: vec![],
+        emails: vec![],
+        bio_option: None,
+        img_path_option: None,
     }
 }
 
@@ -295,6 +302,9 @@ pub fn create_personal_chat(ds_uuid: &PbUuid, idx: i32, user: &User, member_ids:
         member_ids,
         msg_count: msg_count as i32,
         main_chat_id: None,
+        description_option: None,
+        created_timestamp_option: None,
+        member_details: vec![],
     }
 }
 
@@ -310,6 +320,9 @@ pub fn create_group_chat(ds_uuid: &PbUuid, id: i64, name_suffix: &str, member_id
         member_ids: member_ids,
         msg_count: msg_count as i32,
         main_chat_id: None,
+        description_option: None,
+        created_timestamp_option: None,
+        member_details: vec![],
     }
 }
 
@@ -331,6 +344,9 @@ pub fn create_regular_message(idx: usize, user_id: usize) -> Message {
                 content::SealedValueOptional::Poll(ContentPoll { question: format!("Hey, {idx}!") })
             )
         }),
+        extra_contents: vec![],
+        forward_origin_option: None,
+        ttl_sec_option: None,
     };
 
     let text = vec![RichText::make_plain(format!("Hello there, {idx}!"))];
@@ -343,9 +359,45 @@ pub fn create_regular_message(idx: usize, user_id: usize) -> Message {
         text,
         searchable_string,
         typed: Some(typed),
+        reactions: vec![],
+        delivery_status_option: None,
+        receipts: vec![],
     }
 }
 
+/// Album of photos sent at once by the same user, as a single message and split into a message per photo.
+/// Split messages take consecutive indices starting with `idx`.
+pub fn create_album_messages(idx: usize, user_id: usize, photo_paths: &[&str]) -> (Message, Vec<Message>) {
+    let head = create_regular_message(idx, user_id);
+    let split = photo_paths.iter().enumerate().map(|(i, path)| {
+        let mut m = head.clone();
+        m.internal_id = (idx + i) as i64 * 100;
+        m.source_id_option = Some((idx + i) as i64);
+        if i > 0 {
+            m.text = vec![];
+        }
+        let Some(message::Typed::Regular(ref mut mr)) = m.typed else { unreachable!() };
+        mr.content_option = Some(Content {
+            sealed_value_optional: Some(content::SealedValueOptional::Photo(ContentPhoto {
+                path_option: Some(path.to_string()),
+                width: 100,
+                height: 100,
+                is_one_time: false,
+            }))
+        });
+        if i > 0 {
+            mr.reply_to_message_id_option = None;
+        }
+        m.searchable_string = make_searchable_string(&m.text, m.typed());
+        m
+    }).collect_vec();
+    let merged = split[1..].iter().fold(split[0].clone(), |mut album, m| {
+        album.merge_album_item(m.clone());
+        album
+    });
+    (merged, split)
+}
+
 pub mod test_android {
     use rusqlite::Connection;
 
@@ -1 +1,3 @@
+// Generated code, regular message is expected to dwarf service one
+#[allow(clippy::large_enum_variant)]
 pub mod history;
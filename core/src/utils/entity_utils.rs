@@ -117,6 +117,10 @@ impl ShortUser {
             last_name_option: None,
             username_option: None,
             phone_number_option: None,
+            extra_phone_numbers: vec![],
+            emails: vec![],
+            bio_option: None,
+            img_path_option: None,
         }
     }
 }
@@ -256,6 +260,9 @@ impl Message {
             text,
             searchable_string,
             typed: Some(typed),
+            reactions: vec![],
+            delivery_status_option: None,
+            receipts: vec![],
         }
     }
 
@@ -277,7 +284,7 @@ impl Message {
         let possibilities: Vec<Option<&str>> = match self.typed() {
             message::Typed::Regular(ref mr) => {
                 use content::SealedValueOptional::*;
-                match mr.content_option.as_ref().and_then(|c| c.sealed_value_optional.as_ref()) {
+                mr.contents().flat_map(|c| match c.sealed_value_optional.as_ref() {
                     Some(Sticker(v)) => vec![v.path_option.as_deref(), v.thumbnail_path_option.as_deref()],
                     Some(Photo(v)) => vec![v.path_option.as_deref()],
                     Some(VoiceMsg(v)) => vec![v.path_option.as_deref()],
@@ -288,8 +295,9 @@ impl Message {
                     Some(Location(_)) => vec![],
                     Some(Poll(_)) => vec![],
                     Some(SharedContact(v)) => vec![v.vcard_path_option.as_deref()],
+                    Some(WebPage(v)) => vec![v.image_path_option.as_deref()],
                     None => vec![],
-                }
+                }).collect_vec()
             }
             message_service_pat!(ref ms) => {
                 use message_service::SealedValueOptional::*;
@@ -301,6 +309,7 @@ impl Message {
                     BlockUser(_) => vec![],
                     StatusTextChanged(_) => vec![],
                     Notice(_) => vec![],
+                    SetMessagesTtl(_) => vec![],
                     GroupCreate(_) => vec![],
                     GroupEditTitle(_) => vec![],
                     GroupEditPhoto(v) => vec![v.photo.path_option.as_deref()],
@@ -313,13 +322,59 @@ impl Message {
             }
             message_service_pat_unreachable!() => { unreachable!() }
         };
-        possibilities.into_iter().flatten().collect()
+        let custom_emoji_paths = self.text.iter().map(|rte| match rte.val {
+            Some(rich_text_element::Val::CustomEmoji(ref v)) => v.path_option.as_deref(),
+            _ => None,
+        });
+        let reaction_paths = self.reactions.iter().map(|r| r.custom_sticker_path_option.as_deref());
+        possibilities.into_iter().chain(custom_emoji_paths).chain(reaction_paths).flatten().collect()
     }
 
     /// Does not check files existence.
     pub fn files(&self, ds_root: &DatasetRoot) -> Vec<PathBuf> {
         self.files_relative().iter().map(|p| ds_root.to_absolute(p)).collect()
     }
+
+    /// Exports don't mark messages sent together as an album, each album item is exported as a separate message.
+    /// Album item is recognized as a caption-less media message sent by the same user at the same second as
+    /// a preceding (possibly captioned) album message.
+    pub fn is_album_continuation_of(&self, album_msg: &Message) -> bool {
+        let (Some(message::Typed::Regular(album)), Some(message::Typed::Regular(item))) =
+            (album_msg.typed.as_ref(), self.typed.as_ref()) else { return false; };
+        self.from_id == album_msg.from_id &&
+            self.timestamp == album_msg.timestamp &&
+            self.text.is_empty() &&
+            album.content_option.is_some() && album.contents().all(Content::is_album_item) &&
+            item.content_option.as_ref().is_some_and(Content::is_album_item) && item.extra_contents.is_empty() &&
+            item.forward_from_name_option == album.forward_from_name_option &&
+            item.forward_origin_option == album.forward_origin_option &&
+            (item.reply_to_message_id_option.is_none() ||
+                item.reply_to_message_id_option == album.reply_to_message_id_option)
+    }
+
+    /// Appends content and reactions of an album item to this message holding the album.
+    pub fn merge_album_item(&mut self, item: Message) {
+        let Some(message::Typed::Regular(item_regular)) = item.typed else { unreachable!("Album item is not regular") };
+        let message::Typed::Regular(album_regular) = self.typed_mut() else { unreachable!("Album is not regular") };
+        album_regular.extra_contents.extend(item_regular.content_option);
+        self.reactions.extend(item.reactions);
+        self.searchable_string = make_searchable_string(&self.text, self.typed());
+    }
+
+    /// Number of album items merged into this message, single-content message counts as one.
+    pub fn album_len(&self) -> usize {
+        match self.typed() {
+            message::Typed::Regular(mr) => mr.contents().count().max(1),
+            _ => 1,
+        }
+    }
+}
+
+impl MessageRegular {
+    /// Main content (if any) followed by extra ones.
+    pub fn contents(&self) -> impl Iterator<Item=&Content> {
+        self.content_option.iter().chain(self.extra_contents.iter())
+    }
 }
 
 impl RichTextElement {
@@ -334,7 +389,15 @@ impl RichTextElement {
             Val::PrefmtInline(RtePrefmtInline { text }) |
             Val::PrefmtBlock(RtePrefmtBlock { text, .. }) |
             Val::Blockquote(RteBlockquote { text }) |
-            Val::Spoiler(RteSpoiler { text }) => {
+            Val::Spoiler(RteSpoiler { text }) |
+            Val::Mention(RteMention { text, .. }) |
+            Val::CustomEmoji(RteCustomEmoji { text, .. }) |
+            Val::Hashtag(RteHashtag { text }) |
+            Val::Cashtag(RteCashtag { text }) |
+            Val::BotCommand(RteBotCommand { text }) |
+            Val::Email(RteEmail { text }) |
+            Val::Phone(RtePhone { text }) |
+            Val::BankCard(RteBankCard { text }) => {
                 Some(text)
             }
             Val::Link(RteLink { text_option, .. }) => {
@@ -354,7 +417,15 @@ impl RichTextElement {
             Val::PrefmtInline(RtePrefmtInline { text }) |
             Val::PrefmtBlock(RtePrefmtBlock { text, .. }) |
             Val::Blockquote(RteBlockquote { text }) |
-            Val::Spoiler(RteSpoiler { text }) => {
+            Val::Spoiler(RteSpoiler { text }) |
+            Val::Mention(RteMention { text, .. }) |
+            Val::CustomEmoji(RteCustomEmoji { text, .. }) |
+            Val::Hashtag(RteHashtag { text }) |
+            Val::Cashtag(RteCashtag { text }) |
+            Val::BotCommand(RteBotCommand { text }) |
+            Val::Email(RteEmail { text }) |
+            Val::Phone(RtePhone { text }) |
+            Val::BankCard(RteBankCard { text }) => {
                 Some(text)
             }
             Val::Link(RteLink { text_option, .. }) => {
@@ -416,6 +487,62 @@ impl RichText {
         }
     }
 
+    pub fn make_mention(user_id_option: Option<i64>, text: String) -> RichTextElement {
+        RichTextElement {
+            searchable_string: normalize_seachable_string(text.as_str()),
+            val: Some(rich_text_element::Val::Mention(RteMention { user_id_option, text })),
+        }
+    }
+
+    pub fn make_custom_emoji(text: String, path_option: Option<String>) -> RichTextElement {
+        RichTextElement {
+            searchable_string: normalize_seachable_string(text.as_str()),
+            val: Some(rich_text_element::Val::CustomEmoji(RteCustomEmoji { text, path_option })),
+        }
+    }
+
+    pub fn make_hashtag(text: String) -> RichTextElement {
+        RichTextElement {
+            searchable_string: normalize_seachable_string(text.as_str()),
+            val: Some(rich_text_element::Val::Hashtag(RteHashtag { text })),
+        }
+    }
+
+    pub fn make_cashtag(text: String) -> RichTextElement {
+        RichTextElement {
+            searchable_string: normalize_seachable_string(text.as_str()),
+            val: Some(rich_text_element::Val::Cashtag(RteCashtag { text })),
+        }
+    }
+
+    pub fn make_bot_command(text: String) -> RichTextElement {
+        RichTextElement {
+            searchable_string: normalize_seachable_string(text.as_str()),
+            val: Some(rich_text_element::Val::BotCommand(RteBotCommand { text })),
+        }
+    }
+
+    pub fn make_email(text: String) -> RichTextElement {
+        RichTextElement {
+            searchable_string: normalize_seachable_string(text.as_str()),
+            val: Some(rich_text_element::Val::Email(RteEmail { text })),
+        }
+    }
+
+    pub fn make_phone(text: String) -> RichTextElement {
+        RichTextElement {
+            searchable_string: normalize_seachable_string(text.as_str()),
+            val: Some(rich_text_element::Val::Phone(RtePhone { text })),
+        }
+    }
+
+    pub fn make_bank_card(text: String) -> RichTextElement {
+        RichTextElement {
+            searchable_string: normalize_seachable_string(text.as_str()),
+            val: Some(rich_text_element::Val::BankCard(RteBankCard { text })),
+        }
+    }
+
     pub fn make_link(text_option: Option<String>, href: String, hidden: bool) -> RichTextElement {
         let text = text_option.as_deref().unwrap_or("");
         let searchable_string =
@@ -462,9 +589,16 @@ impl Content {
             Some(VideoMsg(c))  => c.path_option.as_ref().map(|c| ds_root.to_absolute(c)),
             Some(Video(c))     => c.path_option.as_ref().map(|c| ds_root.to_absolute(c)),
             Some(File(c))      => c.path_option.as_ref().map(|c| ds_root.to_absolute(c)),
+            Some(WebPage(c))   => c.image_path_option.as_ref().map(|c| ds_root.to_absolute(c)),
             _ => None
         } // @formatter:on
     }
+
+    /// Whether this content can be sent as a part of an album.
+    pub fn is_album_item(&self) -> bool {
+        use content::SealedValueOptional::*;
+        matches!(self.sealed_value_optional, Some(Photo(_) | Video(_) | File(_) | Audio(_)))
+    }
 }
 
 impl ContentLocation {
@@ -553,32 +687,35 @@ pub fn make_searchable_string(components: &[RichTextElement], typed: &message::T
 
 
     let typed_component_text: Vec<String> = match typed {
-        message_regular_pat! { content_option, .. } => {
-            match content_option {
-                Some(Content { sealed_value_optional: Some(content::SealedValueOptional::Sticker(sticker)) }) =>
+        message_regular_pat! { content_option, extra_contents, .. } => {
+            content_option.iter().chain(extra_contents.iter()).flat_map(|c| match &c.sealed_value_optional {
+                Some(content::SealedValueOptional::Sticker(sticker)) =>
                     vec![&sticker.emoji_option].into_iter().flatten().cloned().collect_vec(),
-                Some(Content { sealed_value_optional: Some(content::SealedValueOptional::Audio(file)) }) =>
+                Some(content::SealedValueOptional::Audio(file)) =>
                     vec![&file.title_option, &file.performer_option].into_iter().flatten().cloned().collect_vec(),
-                Some(Content { sealed_value_optional: Some(content::SealedValueOptional::Video(file)) }) =>
+                Some(content::SealedValueOptional::Video(file)) =>
                     vec![&file.title_option, &file.performer_option].into_iter().flatten().cloned().collect_vec(),
-                Some(Content { sealed_value_optional: Some(content::SealedValueOptional::File(file)) }) =>
+                Some(content::SealedValueOptional::File(file)) =>
                     vec![&file.file_name_option].into_iter().flatten().cloned().collect_vec(),
-                Some(Content { sealed_value_optional: Some(content::SealedValueOptional::Location(loc)) }) => {
+                Some(content::SealedValueOptional::Location(loc)) => {
                     let mut vec1 = vec![&loc.address_option, &loc.title_option].into_iter().flatten().collect_vec();
                     let mut vec2 = vec![&loc.lat_str, &loc.lon_str];
                     vec1.append(&mut vec2);
                     vec1.into_iter().cloned().collect_vec()
                 }
-                Some(Content { sealed_value_optional: Some(content::SealedValueOptional::Poll(poll)) }) =>
+                Some(content::SealedValueOptional::Poll(poll)) =>
                     vec![poll.question.clone()],
-                Some(Content { sealed_value_optional: Some(content::SealedValueOptional::SharedContact(contact)) }) =>
+                Some(content::SealedValueOptional::SharedContact(contact)) =>
                     vec![&contact.first_name_option, &contact.last_name_option, &contact.phone_number_option]
                         .into_iter().flatten().cloned().collect_vec(),
+                Some(content::SealedValueOptional::WebPage(web_page)) =>
+                    vec![&web_page.site_name_option, &web_page.title_option, &web_page.description_option]
+                        .into_iter().flatten().cloned().collect_vec(),
                 _ => {
                     // Text is enough.
                     vec![]
                 }
-            }
+            }).collect_vec()
         }
         message_service_pat!(m) => {
             use message_service::SealedValueOptional::*;
@@ -26,6 +26,18 @@ enum Command {
     /// (For debugging purposes only) Parse and load a given file using whichever loader is appropriate,
     /// and print the result in-memory DB size to the log
     Parse { path: String },
+    /// Find every loadable file in the given directory tree and append each of them as a separate dataset
+    /// into the given data.sqlite file (created if missing)
+    BatchImport {
+        dir: String,
+        target: String,
+        /// Replace unparseable messages with placeholders instead of failing, reporting problems as warnings
+        #[arg(long)]
+        lenient: bool,
+    },
+    /// Parse a given file using whichever loader is appropriate and append it as a new dataset into the given
+    /// data.sqlite file (created if missing), writing it as it's being parsed rather than keeping it in memory
+    Import { path: String, target: String },
     /// (For debugging purposes only) Ask UI which user is "myself" and print it to the log
     RequestMyself { port: Option<u16> },
 }
@@ -62,6 +74,24 @@ fn execute_command(command: Command) -> EmptyRes {
             let size: usize = parsed.deep_size_of();
             log::info!("Size of parsed in-memory DB: {} MB ({} B)", size / 1024 / 1024, size);
         }
+        Command::BatchImport { dir, target, lenient } => {
+            let results = batch_import(&dir, &target, lenient, &NoChooser)?;
+            let (succeeded, failed): (Vec<_>, Vec<_>) = results.iter().partition(|r| r.error_option.is_none());
+            for r in succeeded.iter() {
+                log::info!("Imported {} ({} dataset(s))", r.path, r.ds_uuids.len());
+                for w in r.warnings.iter() {
+                    log::warn!("{}: {}: {}", r.path, w.location, w.message);
+                }
+            }
+            for r in failed.iter() {
+                log::error!("Failed to import {}: {}", r.path, r.error_option.as_ref().unwrap());
+            }
+            log::info!("Imported {} of {} files into {target}", succeeded.len(), results.len());
+        }
+        Command::Import { path, target } => {
+            let ds_uuids = import_file(&path, &target, &NoChooser).with_context(|| format!("Failed to import {path}"))?;
+            log::info!("Imported {path} into {target} ({} dataset(s))", ds_uuids.len());
+        }
         Command::RequestMyself { port } => {
             let port = port.unwrap_or(DEFAULT_SERVER_PORT + 1);
             debug_request_myself(port)?;